@@ -0,0 +1,94 @@
+use anyhow::{ anyhow, bail, Result };
+use log::info;
+use std::{
+    fs,
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::PathBuf
+};
+
+use crate::{ def::app::ACCOUNTS_DIR_NAME, jobs::state_dir };
+
+// -----------------------------------------------------------------------------------------------------------
+// Saved Reddit accounts
+// * Token of each account is kept in its own file, readable only by the user.
+// * The file starts with the kind of token, files saved before that hold a bare refresh token.
+// -----------------------------------------------------------------------------------------------------------
+
+// Token saved on login.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    // Exchanged for access tokens as they expire.
+    Refresh(String),
+    // Reddit gives no refresh token for temporary logins, the access token is used until it expires.
+    Access(String)
+}
+
+impl Token {
+    fn parse(saved: &str) -> Token {
+        match saved.trim().split_once(' ') {
+            Some(("refresh", token)) => Token::Refresh(token.to_string()),
+            Some(("access", token)) => Token::Access(token.to_string()),
+            // Logins ask for permanent tokens, so bare ones are refresh tokens.
+            _ => Token::Refresh(saved.trim().to_string())
+        }
+    }
+
+    fn to_saved(&self) -> String {
+        match self {
+            Token::Refresh(token) => format!("refresh {}", token),
+            Token::Access(token) => format!("access {}", token)
+        }
+    }
+}
+
+fn token_path(account: &str) -> Result<PathBuf> {
+    if account.is_empty() || !account.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        bail!("Invalid account name {}, use letters, digits, - and _", account);
+    }
+    let dir = state_dir().ok_or_else(|| anyhow!("No state directory found, set $XDG_STATE_HOME or $HOME"))?;
+    Ok(dir.join(ACCOUNTS_DIR_NAME).join(account))
+}
+
+pub fn save_token(account: &str, token: &Token) -> Result<()> {
+    let path = token_path(account)?;
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&path)?;
+    file.write_all(token.to_saved().as_bytes())?;
+    info!("Saved token of account {} at {:?}", account, path);
+    Ok(())
+}
+
+pub fn load_token(account: &str) -> Result<Option<Token>> {
+    let path = token_path(account)?;
+    if !path.exists() { return Ok(None); }
+    Ok(Some(Token::parse(&fs::read_to_string(path)?)))
+}
+
+// Remove saved token, returning whether there was one.
+pub fn remove_token(account: &str) -> Result<bool> {
+    let path = token_path(account)?;
+    if !path.exists() { return Ok(false); }
+    fs::remove_file(&path)?;
+    info!("Removed token of account {} at {:?}", account, path);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Token;
+
+    // Test if saved tokens keep their kind, and bare tokens saved before are refresh tokens.
+    #[test]
+    fn test_token_parse() {
+        for token in [Token::Refresh("1-abc".to_string()), Token::Access("2-def".to_string())].iter() {
+            assert_eq!(Token::parse(&token.to_saved()), *token);
+        }
+        assert_eq!(Token::parse("3-ghi\n"), Token::Refresh("3-ghi".to_string()));
+    }
+}
@@ -0,0 +1,476 @@
+use anyhow::{ anyhow, bail, Result };
+use log::debug;
+use reqwest::blocking;
+use serde::{ de::{ DeserializeOwned, IgnoredAny }, Deserialize };
+use std::{
+    sync::{ Arc, Mutex },
+    time::{ Duration, Instant }
+};
+
+use super::{
+    accounts::Token,
+    model::{ Comment, InboxTab, Item, Listing, ListingSrc, Message, NewMessage, Post, Submission, Vote },
+    oauth::refresh_access_token
+};
+
+const API_URL: &str = "https://oauth.reddit.com";
+const USER_AGENT: &str = concat!("ravana/", env!("CARGO_PKG_VERSION"));
+// Access tokens last an hour, refreshed a bit before.
+const ACCESS_TOKEN_TTL: Duration = Duration::from_secs(50 * 60);
+// Items fetched per page of a listing, Reddit gives 100 at most.
+const LISTING_LIMIT: &str = "25";
+
+// -----------------------------------------------------------------------------------------------------------
+// Client of Reddit's API, acting as a logged in account.
+// * Blocking, run it in background jobs, not on the main loop.
+// * Cheap to clone, clones share the access token and account name.
+// -----------------------------------------------------------------------------------------------------------
+#[derive(Clone)]
+pub struct Client {
+    http: blocking::Client,
+    // API_URL, or a stand-in in tests.
+    base_url: String,
+    // Token saved on login, see api::accounts.
+    token: Token,
+    access_token: Arc<Mutex<Option<(String, Instant)>>>,
+    // Name of the account, fetched once.
+    username: Arc<Mutex<Option<String>>>
+}
+
+impl Client {
+    pub fn new(token: &Token) -> Result<Client> {
+        Client::with_base_url(token, API_URL)
+    }
+
+    // Client sending requests to base_url instead of Reddit's API, e.g. to an unreachable address in tests.
+    pub fn with_base_url(token: &Token, base_url: &str) -> Result<Client> {
+        Ok(Client {
+            http: blocking::Client::builder().user_agent(USER_AGENT).build()?,
+            base_url: base_url.to_string(),
+            token: token.clone(),
+            access_token: Arc::new(Mutex::new(None)),
+            username: Arc::new(Mutex::new(None))
+        })
+    }
+
+    // Access token, refreshed once it expires. Nothing is cached if refreshing fails.
+    fn access_token(&self) -> Result<String> {
+        let refresh_token = match &self.token {
+            Token::Refresh(refresh_token) => refresh_token,
+            Token::Access(token) => { return Ok(token.clone()); }
+        };
+        let mut access_token = self.access_token.lock().map_err(|_| anyhow!("Unable to lock access token."))?;
+        if let Some((token, refreshed)) = access_token.as_ref() {
+            if refreshed.elapsed() < ACCESS_TOKEN_TTL { return Ok(token.clone()); }
+        }
+        let token = refresh_access_token(refresh_token)
+            .map_err(|e| anyhow!("Unable to refresh access token, try logging in again: {}", e))?;
+        *access_token = Some((token.clone(), Instant::now()));
+        Ok(token)
+    }
+
+    // GET JSON from API endpoint at path, e.g. /api/v1/me.
+    fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<T> {
+        debug!("GET {} {:?}", path, query);
+        let res = self.http.get(format!("{}{}", self.base_url, path))
+            .bearer_auth(self.access_token()?)
+            .query(&[("raw_json", "1")])
+            .query(query)
+            .send()?;
+        if !res.status().is_success() {
+            bail!("{} answered {}", path, res.status());
+        }
+        Ok(res.json()?)
+    }
+
+    // POST form to API endpoint at path, e.g. /api/vote.
+    fn post(&self, path: &str, form: &[(&str, &str)]) -> Result<()> {
+        debug!("POST {} {:?}", path, form);
+        let res = self.http.post(format!("{}{}", self.base_url, path))
+            .bearer_auth(self.access_token()?)
+            .form(form)
+            .send()?;
+        if !res.status().is_success() {
+            bail!("{} answered {}", path, res.status());
+        }
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * POST form to API endpoint at path answering in JSON, e.g. /api/comment.
+    // * Reddit answers refused requests with 200 too, listing errors in the JSON.
+    // -------------------------------------------------------------------------------------------------------
+    fn post_api(&self, path: &str, form: &[(&str, &str)]) -> Result<()> {
+        let form: Vec<(&str, &str)> = [("api_type", "json")].iter().chain(form).cloned().collect();
+        debug!("POST {} {:?}", path, form);
+        let res = self.http.post(format!("{}{}", self.base_url, path))
+            .bearer_auth(self.access_token()?)
+            .form(&form)
+            .send()?;
+        if !res.status().is_success() {
+            bail!("{} answered {}", path, res.status());
+        }
+        let errors = res.json::<ApiRes>()?.json.errors;
+        if !errors.is_empty() {
+            let errors: Vec<String> = errors.into_iter()
+                .map(|error| error.into_iter().flatten().collect::<Vec<String>>().join(": "))
+                .collect();
+            bail!("{}", errors.join(", "));
+        }
+        Ok(())
+    }
+
+    // Vote on post or comment with fullname.
+    pub fn vote(&self, fullname: &str, vote: Vote) -> Result<()> {
+        self.post("/api/vote", &[("id", fullname), ("dir", &vote.dir().to_string())])
+    }
+
+    // Save post or comment with fullname, or unsave it.
+    pub fn save(&self, fullname: &str, saved: bool) -> Result<()> {
+        self.post(if saved { "/api/save" } else { "/api/unsave" }, &[("id", fullname)])
+    }
+
+    // Hide post with fullname from listings, or unhide it.
+    pub fn hide(&self, fullname: &str, hidden: bool) -> Result<()> {
+        self.post(if hidden { "/api/hide" } else { "/api/unhide" }, &[("id", fullname)])
+    }
+
+    // Reply with markdown text to post or comment with fullname.
+    pub fn comment(&self, parent: &str, text: &str) -> Result<()> {
+        self.post_api("/api/comment", &[("thing_id", parent), ("text", text)])
+    }
+
+    // Replace markdown text of own text post or comment with fullname.
+    pub fn edit(&self, fullname: &str, text: &str) -> Result<()> {
+        self.post_api("/api/editusertext", &[("thing_id", fullname), ("text", text)])
+    }
+
+    // Delete own post or comment with fullname.
+    pub fn delete(&self, fullname: &str) -> Result<()> {
+        self.post("/api/del", &[("id", fullname)])
+    }
+
+    // Submit post, its flair given by text being looked up among the subreddit's link flairs.
+    pub fn submit(&self, submission: &Submission) -> Result<()> {
+        let flair = match &submission.flair {
+            Some(text) => Some(self.link_flair(&submission.subreddit, text)?),
+            None => None
+        };
+        let mut form = vec![
+            ("sr", submission.subreddit.as_str()),
+            ("title", submission.title.as_str()),
+            ("nsfw", if submission.nsfw { "true" } else { "false" }),
+            ("spoiler", if submission.spoiler { "true" } else { "false" })
+        ];
+        match &submission.url {
+            Some(url) => { form.extend([("kind", "link"), ("url", url.as_str())]); },
+            None => { form.extend([("kind", "self"), ("text", submission.body.as_str())]); }
+        }
+        if let Some(flair) = &flair {
+            form.extend([("flair_id", flair.id.as_str()), ("flair_text", flair.text.as_str())]);
+        }
+        self.post_api("/api/submit", &form)
+    }
+
+    // Link flair of subreddit with text, ignoring case. Reddit only takes flair text along with the flair's id.
+    fn link_flair(&self, subreddit: &str, text: &str) -> Result<LinkFlair> {
+        let flairs = self.get::<Vec<LinkFlair>>(&format!("/r/{}/api/link_flair_v2", subreddit), &[])
+            .map_err(|e| anyhow!("Unable to fetch flairs of r/{}: {}", subreddit, e))?;
+        find_link_flair(flairs, subreddit, text)
+    }
+
+    pub fn send_message(&self, message: &NewMessage) -> Result<()> {
+        self.post_api("/api/compose", &[("to", &message.to), ("subject", &message.subject), ("text", &message.body)])
+    }
+
+    // Mark inbox message or comment with fullname read, or unread.
+    pub fn mark_read(&self, fullname: &str, read: bool) -> Result<()> {
+        self.post(if read { "/api/read_message" } else { "/api/unread_message" }, &[("id", fullname)])
+    }
+
+    // Name of the account logged in, fetched on first use.
+    pub fn username(&self) -> Result<String> {
+        if let Some(username) = self.username.lock().map_err(|_| anyhow!("Unable to lock account name."))?.as_ref() {
+            return Ok(username.clone());
+        }
+        Ok(self.me()?.name)
+    }
+
+    // Unread messages and comments in the inbox.
+    pub fn unread_count(&self) -> Result<u32> {
+        Ok(self.me()?.inbox_count)
+    }
+
+    // Account logged in, keeping its name.
+    fn me(&self) -> Result<Me> {
+        let me = self.get::<Me>("/api/v1/me", &[])?;
+        *self.username.lock().map_err(|_| anyhow!("Unable to lock account name."))? = Some(me.name.clone());
+        Ok(me)
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Page of listing src, starting after the item given.
+    // * Fetching inbox tabs leaves their messages unread, they are marked read with mark_read.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn listing(&self, src: ListingSrc, after: Option<&str>) -> Result<Listing> {
+        let mut query = vec![("limit", LISTING_LIMIT)];
+        if let Some(after) = after {
+            query.push(("after", after));
+        }
+        match src {
+            ListingSrc::Saved => {
+                let path = format!("/user/{}/saved", self.username()?);
+                Ok(self.get::<ListingRes>(&path, &query)?.into())
+            },
+            ListingSrc::Inbox(tab) => {
+                query.push(("mark", "false"));
+                Ok(self.get::<MessageListingRes>(inbox_path(tab), &query)?.into())
+            }
+        }
+    }
+}
+
+fn find_link_flair(flairs: Vec<LinkFlair>, subreddit: &str, text: &str) -> Result<LinkFlair> {
+    if flairs.is_empty() {
+        bail!("r/{} has no flairs", subreddit);
+    }
+    let texts = flairs.iter().map(|flair| flair.text.clone()).collect::<Vec<String>>().join(", ");
+    flairs.into_iter()
+        .find(|flair| flair.text.eq_ignore_ascii_case(text))
+        .ok_or_else(|| anyhow!("r/{} has no flair {}, expected one of: {}", subreddit, text, texts))
+}
+
+fn inbox_path(tab: InboxTab) -> &'static str {
+    match tab {
+        InboxTab::Unread => "/message/unread",
+        InboxTab::All => "/message/inbox",
+        InboxTab::Messages => "/message/messages",
+        InboxTab::CommentReplies => "/message/comments",
+        InboxTab::Mentions => "/message/mentions"
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Responses of the API, converted into api::model items.
+// * Fields missing in a response take their defaults.
+// -----------------------------------------------------------------------------------------------------------
+#[derive(Deserialize)]
+struct ApiRes {
+    json: ApiJson
+}
+
+// Errors as [code, message, field], any of them may be null.
+#[derive(Deserialize)]
+struct ApiJson {
+    #[serde(default)]
+    errors: Vec<Vec<Option<String>>>
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct LinkFlair {
+    id: String,
+    #[serde(default)]
+    text: String
+}
+
+#[derive(Deserialize)]
+struct Me {
+    name: String,
+    #[serde(default)]
+    inbox_count: u32
+}
+
+#[derive(Deserialize)]
+struct ListingRes {
+    data: ListingData
+}
+
+#[derive(Deserialize)]
+struct ListingData {
+    after: Option<String>,
+    children: Vec<Thing>
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", content = "data")]
+enum Thing {
+    #[serde(rename = "t3")]
+    Post(PostData),
+    #[serde(rename = "t1")]
+    Comment(CommentData)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PostData {
+    id: String,
+    subreddit: String,
+    title: String,
+    author: String,
+    score: i64,
+    num_comments: u32,
+    selftext: String,
+    is_self: bool,
+    likes: Option<bool>,
+    saved: bool
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct CommentData {
+    id: String,
+    subreddit: String,
+    author: String,
+    score: i64,
+    body: String,
+    link_title: String,
+    likes: Option<bool>,
+    saved: bool
+}
+
+impl From<ListingRes> for Listing {
+    fn from(res: ListingRes) -> Listing {
+        let items = res.data.children.into_iter().map(|thing| match thing {
+            Thing::Post(data) => Item::Post(Post {
+                id: data.id,
+                subreddit: data.subreddit,
+                title: data.title,
+                author: data.author,
+                score: data.score,
+                num_comments: data.num_comments,
+                selftext: data.selftext,
+                is_self: data.is_self,
+                vote: Vote::from_likes(data.likes),
+                saved: data.saved
+            }),
+            Thing::Comment(data) => Item::Comment(Comment {
+                id: data.id,
+                subreddit: data.subreddit,
+                author: data.author,
+                score: data.score,
+                body: data.body,
+                link_title: data.link_title,
+                vote: Vote::from_likes(data.likes),
+                saved: data.saved
+            })
+        }).collect();
+        Listing { items, after: res.data.after }
+    }
+}
+
+// Inbox listings hold comments as messages, with was_comment set.
+#[derive(Deserialize)]
+struct MessageListingRes {
+    data: MessageListingData
+}
+
+#[derive(Deserialize)]
+struct MessageListingData {
+    after: Option<String>,
+    children: Vec<MessageThing>
+}
+
+#[derive(Deserialize)]
+struct MessageThing {
+    data: MessageData
+}
+
+// Fields null for some messages are options, e.g. subreddit of private messages.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct MessageData {
+    id: String,
+    author: Option<String>,
+    dest: String,
+    subject: String,
+    body: String,
+    subreddit: Option<String>,
+    link_title: Option<String>,
+    was_comment: bool,
+    new: bool,
+    replies: Option<Replies>
+}
+
+// Replies of a message, Reddit gives an empty string for none.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Replies {
+    Listing(MessageListingRes),
+    None(IgnoredAny)
+}
+
+impl From<MessageData> for Message {
+    fn from(data: MessageData) -> Message {
+        let replies = match data.replies {
+            Some(Replies::Listing(res)) => res.data.children.into_iter().map(|thing| thing.data.into()).collect(),
+            Some(Replies::None(_)) | None => vec![]
+        };
+        Message {
+            id: data.id,
+            author: data.author.unwrap_or_default(),
+            dest: data.dest,
+            subject: data.subject,
+            body: data.body,
+            subreddit: data.subreddit.unwrap_or_default(),
+            link_title: data.link_title.unwrap_or_default(),
+            was_comment: data.was_comment,
+            new: data.new,
+            replies
+        }
+    }
+}
+
+impl From<MessageListingRes> for Listing {
+    fn from(res: MessageListingRes) -> Listing {
+        let items = res.data.children.into_iter().map(|thing| Item::Message(thing.data.into())).collect();
+        Listing { items, after: res.data.after }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::model::{ Item, Listing };
+    use super::{ find_link_flair, LinkFlair, MessageListingRes };
+
+    // Test if conversations keep their replies, and null or empty fields of inbox listings are taken.
+    #[test]
+    fn test_message_listing() {
+        let json = r#"{ "kind": "Listing", "data": { "after": "t4_b", "children": [
+            { "kind": "t4", "data": { "id": "a", "author": "ferris", "dest": "me", "subject": "Hi", "body": "Hello",
+                "subreddit": null, "was_comment": false, "new": true, "replies": { "kind": "Listing", "data": {
+                    "after": null, "children": [ { "kind": "t4", "data": { "id": "b", "author": "me", "dest": "ferris",
+                        "subject": "re: Hi", "body": "Hey", "new": false, "replies": "" } } ] } } } },
+            { "kind": "t1", "data": { "id": "c", "author": "crab", "dest": "me", "subject": "comment reply",
+                "body": "Nice", "subreddit": "rust", "link_title": "Ravana", "was_comment": true, "new": false,
+                "replies": "" } }
+        ] } }"#;
+        let listing: Listing = serde_json::from_str::<MessageListingRes>(json).unwrap().into();
+        assert_eq!(listing.after, Some("t4_b".to_string()));
+        let messages: Vec<_> = listing.items.into_iter().map(|item| match item {
+            Item::Message(message) => message,
+            item => panic!("Not a message: {:?}", item)
+        }).collect();
+        assert_eq!(messages[0].fullname(), "t4_a");
+        assert_eq!(messages[0].subreddit, "");
+        assert!(messages[0].new);
+        assert_eq!(messages[0].replies.len(), 1);
+        assert_eq!(messages[0].replies[0].body, "Hey");
+        assert_eq!(messages[1].fullname(), "t1_c");
+        assert_eq!(messages[1].link_title, "Ravana");
+        assert!(messages[1].replies.is_empty());
+    }
+
+    // Test if link flairs are found by text ignoring case, listing the ones there are otherwise.
+    #[test]
+    fn test_find_link_flair() {
+        let flairs = || serde_json::from_str::<Vec<LinkFlair>>(r#"[
+            { "id": "a1", "text": "Tool", "text_editable": false, "type": "text" },
+            { "id": "b2", "text": "Question", "text_editable": true, "type": "text" }
+        ]"#).unwrap();
+        assert_eq!(find_link_flair(flairs(), "rust", "tool").unwrap(),
+            LinkFlair { id: "a1".to_string(), text: "Tool".to_string() });
+        assert_eq!(find_link_flair(flairs(), "rust", "News").unwrap_err().to_string(),
+            "r/rust has no flair News, expected one of: Tool, Question");
+        assert_eq!(find_link_flair(vec![], "rust", "Tool").unwrap_err().to_string(), "r/rust has no flairs");
+    }
+}
@@ -0,0 +1,300 @@
+// Author and text of items once deleted.
+pub const DELETED: &str = "[deleted]";
+
+// -----------------------------------------------------------------------------------------------------------
+// Reddit items as fetched, owned so they can be sent from background jobs to the main loop.
+// -----------------------------------------------------------------------------------------------------------
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Post {
+    pub id: String,
+    pub subreddit: String,
+    pub title: String,
+    pub author: String,
+    pub score: i64,
+    pub num_comments: u32,
+    pub selftext: String,
+    // Text post, which has markdown to edit, rather than a link post.
+    pub is_self: bool,
+    // Vote of the user, Reddit's likes.
+    pub vote: Vote,
+    pub saved: bool
+}
+
+impl Post {
+    // e.g. t3_abc, Reddit's id across item kinds.
+    pub fn fullname(&self) -> String {
+        format!("t3_{}", self.id)
+    }
+
+    // Vote, adjusting score by the change.
+    pub fn set_vote(&mut self, vote: Vote) {
+        self.score += vote.dir() - self.vote.dir();
+        self.vote = vote;
+    }
+
+    // Show as Reddit does once deleted.
+    pub fn mark_deleted(&mut self) {
+        self.author = DELETED.to_string();
+        self.selftext = DELETED.to_string();
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Comment {
+    pub id: String,
+    pub subreddit: String,
+    pub author: String,
+    pub score: i64,
+    pub body: String,
+    // Title of the post commented on.
+    pub link_title: String,
+    pub vote: Vote,
+    pub saved: bool
+}
+
+impl Comment {
+    // e.g. t1_abc.
+    pub fn fullname(&self) -> String {
+        format!("t1_{}", self.id)
+    }
+
+    pub fn set_vote(&mut self, vote: Vote) {
+        self.score += vote.dir() - self.vote.dir();
+        self.vote = vote;
+    }
+
+    pub fn mark_deleted(&mut self) {
+        self.author = DELETED.to_string();
+        self.body = DELETED.to_string();
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Item of the inbox: a private message, or a reply to the account's post or comment, or a mention of it.
+// * Private messages hold the rest of their conversation as replies, comments hold none.
+// -----------------------------------------------------------------------------------------------------------
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Message {
+    pub id: String,
+    pub author: String,
+    // Account the message was sent to.
+    pub dest: String,
+    pub subject: String,
+    pub body: String,
+    // Subreddit and title of the post commented on, empty for private messages.
+    pub subreddit: String,
+    pub link_title: String,
+    // Whether it is a comment, replying to the account or mentioning it.
+    pub was_comment: bool,
+    // Whether it is unread.
+    pub new: bool,
+    // Messages replying to it in its conversation, oldest first.
+    pub replies: Vec<Message>
+}
+
+impl Message {
+    // e.g. t4_abc, t1_abc for comments.
+    pub fn fullname(&self) -> String {
+        format!("{}_{}", if self.was_comment { "t1" } else { "t4" }, self.id)
+    }
+
+    pub fn mark_deleted(&mut self) {
+        self.author = DELETED.to_string();
+        self.body = DELETED.to_string();
+    }
+}
+
+// Post, comment or message, as listed together, e.g. among saved items.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Post(Post),
+    Comment(Comment),
+    Message(Message)
+}
+
+impl Item {
+    pub fn fullname(&self) -> String {
+        match self {
+            Item::Post(post) => post.fullname(),
+            Item::Comment(comment) => comment.fullname(),
+            Item::Message(message) => message.fullname()
+        }
+    }
+
+    // e.g. post, as told to the user.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Item::Post(_) => "post",
+            Item::Comment(_) => "comment",
+            Item::Message(_) => "message"
+        }
+    }
+
+    pub fn author(&self) -> &str {
+        match self {
+            Item::Post(post) => &post.author,
+            Item::Comment(comment) => &comment.author,
+            Item::Message(message) => &message.author
+        }
+    }
+
+    pub fn subreddit(&self) -> &str {
+        match self {
+            Item::Post(post) => &post.subreddit,
+            Item::Comment(comment) => &comment.subreddit,
+            Item::Message(message) => &message.subreddit
+        }
+    }
+
+    // Markdown source of text post, comment or message.
+    pub fn body(&self) -> &str {
+        match self {
+            Item::Post(post) => &post.selftext,
+            Item::Comment(comment) => &comment.body,
+            Item::Message(message) => &message.body
+        }
+    }
+
+    pub fn set_body(&mut self, body: &str) {
+        match self {
+            Item::Post(post) => post.selftext = body.to_string(),
+            Item::Comment(comment) => comment.body = body.to_string(),
+            Item::Message(message) => message.body = body.to_string()
+        }
+    }
+
+    pub fn mark_deleted(&mut self) {
+        match self {
+            Item::Post(post) => post.mark_deleted(),
+            Item::Comment(comment) => comment.mark_deleted(),
+            Item::Message(message) => message.mark_deleted()
+        }
+    }
+
+    // Messages are not voted on, nor saved.
+    pub fn vote(&self) -> Vote {
+        match self {
+            Item::Post(post) => post.vote,
+            Item::Comment(comment) => comment.vote,
+            Item::Message(_) => Vote::None
+        }
+    }
+
+    pub fn set_vote(&mut self, vote: Vote) {
+        match self {
+            Item::Post(post) => post.set_vote(vote),
+            Item::Comment(comment) => comment.set_vote(vote),
+            Item::Message(_) => {  }
+        }
+    }
+
+    pub fn saved(&self) -> bool {
+        match self {
+            Item::Post(post) => post.saved,
+            Item::Comment(comment) => comment.saved,
+            Item::Message(_) => false
+        }
+    }
+
+    pub fn set_saved(&mut self, saved: bool) {
+        match self {
+            Item::Post(post) => post.saved = saved,
+            Item::Comment(comment) => comment.saved = saved,
+            Item::Message(_) => {  }
+        }
+    }
+}
+
+// Page of a listing, with the cursor to fetch the next page after, None on the last page.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Listing {
+    pub items: Vec<Item>,
+    pub after: Option<String>
+}
+
+// Listing fetched a page at a time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListingSrc {
+    // Posts and comments saved by the account.
+    Saved,
+    Inbox(InboxTab)
+}
+
+// Tab of the inbox, each listing part of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InboxTab {
+    #[default]
+    Unread,
+    All,
+    Messages,
+    CommentReplies,
+    Mentions
+}
+
+impl InboxTab {
+    pub const TABS: [InboxTab; 5] = [InboxTab::Unread, InboxTab::All, InboxTab::Messages, InboxTab::CommentReplies, InboxTab::Mentions];
+
+    // Name as :inbox takes it, e.g. comment-replies.
+    pub fn name(&self) -> &'static str {
+        match self {
+            InboxTab::Unread => "unread",
+            InboxTab::All => "all",
+            InboxTab::Messages => "messages",
+            InboxTab::CommentReplies => "comment-replies",
+            InboxTab::Mentions => "mentions"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<InboxTab> {
+        InboxTab::TABS.iter().copied().find(|tab| tab.name() == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Vote {
+    Up,
+    Down,
+    #[default]
+    None
+}
+
+impl Vote {
+    // Direction as /api/vote takes it.
+    pub fn dir(&self) -> i64 {
+        match self {
+            Vote::Up => 1,
+            Vote::Down => -1,
+            Vote::None => 0
+        }
+    }
+
+    // Vote from Reddit's likes: true, false or null.
+    pub fn from_likes(likes: Option<bool>) -> Vote {
+        match likes {
+            Some(true) => Vote::Up,
+            Some(false) => Vote::Down,
+            None => Vote::None
+        }
+    }
+}
+
+// Post to submit to a subreddit, as composed in the editor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Submission {
+    pub subreddit: String,
+    pub title: String,
+    // Link of a link post, None for a text post.
+    pub url: Option<String>,
+    pub body: String,
+    pub flair: Option<String>,
+    pub nsfw: bool,
+    pub spoiler: bool
+}
+
+// Private message to send to an account, as composed in the editor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NewMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String
+}
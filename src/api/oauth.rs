@@ -1,3 +1,4 @@
+use anyhow::{ anyhow, bail, Result };
 use oauth2::basic::BasicClient;
 use oauth2::reqwest::http_client;
 use oauth2::{
@@ -8,32 +9,44 @@ use oauth2::{
     CsrfToken,
     Scope,
     TokenUrl,
+    RefreshToken,
     TokenResponse
 };
 use url::Url;
 use std::{
-    fs,
     net::TcpListener,
     io::{BufRead, BufReader, Write}
 };
 
+use super::accounts::Token;
+
 const CLIENT_ID: &str = "CO0m-UAASpcd25xiQdi30g";
 const AUTH_URL: &str = "https://www.reddit.com/api/v1/authorize"; 
 const TOKEN_URL: &str = "https://www.reddit.com/api/v1/access_token";
 const REDIRECT_URL: &str = "http://localhost:5555"; 
+const OAUTH_COMPLETE_HTML: &str = include_str!("oauth-complete.html");
 
-pub fn oauth_process() {
+// OAuth client of ravana, an installed app without secret.
+fn oauth_client() -> Result<BasicClient> {
     let client_id = ClientId::new(CLIENT_ID.to_string());
-    let auth_url = AuthUrl::new(AUTH_URL.to_string()).expect("Cannot set Auth URL");
-    let token_url = TokenUrl::new(TOKEN_URL.to_string()).expect("Cannot set Token URL");
-    let client = BasicClient::new(
+    let auth_url = AuthUrl::new(AUTH_URL.to_string())?;
+    let token_url = TokenUrl::new(TOKEN_URL.to_string())?;
+    Ok(BasicClient::new(
         client_id,
         None,
         auth_url,
         Some(token_url)
     ).set_redirect_uri(
-        RedirectUrl::new(REDIRECT_URL.to_string()).expect("Invalid redirect URL")
-    );
+        RedirectUrl::new(REDIRECT_URL.to_string())?
+    ))
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Ask user to authorize in browser, receive code on redirect to localhost.
+// * Exchange code for tokens, return refresh token ( access token if Reddit gives none ).
+// -----------------------------------------------------------------------------------------------------------
+pub fn oauth_process() -> Result<Token> {
+    let client = oauth_client()?;
     let (authorize_url, csrf_state) = client
         .authorize_url(CsrfToken::new_random)
         .add_scope(Scope::new("identity".to_string()))
@@ -43,6 +56,11 @@ pub fn oauth_process() {
         .add_scope(Scope::new("read".to_string()))
         .add_scope(Scope::new("wikiread".to_string()))
         .add_scope(Scope::new("submit".to_string()))
+        .add_scope(Scope::new("vote".to_string()))
+        .add_scope(Scope::new("save".to_string()))
+        .add_scope(Scope::new("report".to_string()))
+        .add_scope(Scope::new("privatemessages".to_string()))
+        .add_extra_param("duration", "permanent")
         .url();
 
     println!(
@@ -50,7 +68,7 @@ pub fn oauth_process() {
         authorize_url.to_string()
     );
 
-    let listener = TcpListener::bind("127.0.0.1:5555").unwrap();
+    let listener = TcpListener::bind("127.0.0.1:5555")?;
     for stream in listener.incoming() {
         if let Ok(mut stream) = stream {
             let code;
@@ -59,57 +77,53 @@ pub fn oauth_process() {
                 let mut reader = BufReader::new(&stream);
 
                 let mut request_line = String::new();
-                reader.read_line(&mut request_line).unwrap();
-
-                let redirect_url = request_line.split_whitespace().nth(1).unwrap();
-                let url = Url::parse(&("http://localhost".to_string() + redirect_url)).unwrap();
-
-                let code_pair = url
-                    .query_pairs()
-                    .find(|pair| {
-                        let &(ref key, _) = pair;
-                        key == "code"
-                    })
-                    .unwrap();
-
-                let (_, value) = code_pair;
-                code = AuthorizationCode::new(value.into_owned());
-
-                let state_pair = url
-                    .query_pairs()
-                    .find(|pair| {
-                        let &(ref key, _) = pair;
-                        key == "state"
-                    })
-                    .unwrap();
-
-                let (_, value) = state_pair;
-                state = CsrfToken::new(value.into_owned());
+                reader.read_line(&mut request_line)?;
+
+                let redirect_url = request_line.split_whitespace().nth(1)
+                    .ok_or_else(|| anyhow!("Invalid redirect request: {}", request_line))?;
+                let url = Url::parse(&("http://localhost".to_string() + redirect_url))?;
+
+                let query_value = |name: &str| url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned());
+
+                if let Some(e) = query_value("error") {
+                    bail!("Reddit denied authorization: {}", e);
+                }
+                code = AuthorizationCode::new(query_value("code")
+                    .ok_or_else(|| anyhow!("No code in redirect: {}", redirect_url))?);
+                state = CsrfToken::new(query_value("state")
+                    .ok_or_else(|| anyhow!("No state in redirect: {}", redirect_url))?);
             }
 
-            //let message = "<html><body><h1>Go back to your terminal</h1></body></html>";
-            let message = fs::read_to_string("src/api/oauth-complete.html").expect("Failed to read HTML to string.");
+            let message = OAUTH_COMPLETE_HTML;
             let response = format!(
                 "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
                 message.len(),
                 message
                 );
 
-            stream.write_all(response.as_bytes()).unwrap();
+            stream.write_all(response.as_bytes())?;
 
-            println!("Reddit returned the following code: \n{}\n", code.secret());
-            println!(
-                "Reddit returned state: \n{} (expected: `{}`)\n",
-                state.secret(),
-                csrf_state.secret()
-            );
-
-            let token_res = client.exchange_code(code).request(http_client).unwrap();
+            if state.secret() != csrf_state.secret() {
+                bail!("State returned by Reddit does not match, not logging in.");
+            }
 
-            println!("Reddit returned the following token:\n{:?}\n", token_res);
-            println!("{}", token_res.access_token().secret());
+            let token_res = client.exchange_code(code).request(http_client)?;
 
-            break;
+            return Ok(match token_res.refresh_token() {
+                Some(refresh_token) => Token::Refresh(refresh_token.secret().to_string()),
+                None => Token::Access(token_res.access_token().secret().to_string())
+            });
         }
     }
+    bail!("No redirect received from Reddit.")
+}
+
+// Exchange refresh token saved on login for an access token, valid for an hour.
+pub fn refresh_access_token(refresh_token: &str) -> Result<String> {
+    let token_res = oauth_client()?
+        .exchange_refresh_token(&RefreshToken::new(refresh_token.to_string()))
+        .request(http_client)?;
+    Ok(token_res.access_token().secret().to_string())
 }
@@ -0,0 +1,255 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub const USAGE: &str = "\
+Usage: ravana [OPTIONS] [COMMAND | r/<subreddit> | <reddit-url>]
+
+Commands:
+    login           Log in to Reddit with the account
+    logout          Log out the account, removing its saved token
+    check-config    Validate config without starting the TUI
+    config dump     Print resolved config, marking where each value came from
+    config init [<path>]
+                    Write commented starter config, to user config path if not given
+
+Arguments:
+    r/<subreddit>   Open subreddit at startup
+    <reddit-url>    Open subreddit at startup, e.g. https://www.reddit.com/r/rust ( post and user pages are
+                    not supported yet )
+
+Options:
+    --config <path>     Use config file ( or directory holding Config.toml )
+    --log-file <path>   Write logs to file instead of stderr, or instead of ravana.log in state directory for TUI
+    --account <name>    Reddit account to use, \"default\" if not given
+    -V, --version       Print version
+    -h, --help          Print this help";
+
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+// -----------------------------------------------------------------------------------------------------------
+// Parsed command-line.
+// -----------------------------------------------------------------------------------------------------------
+#[derive(Debug, PartialEq)]
+pub struct Cli {
+    pub cmd: Cmd,
+    pub config: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    pub account: String
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Cmd {
+    // Start TUI, opening target page if given.
+    Tui(Option<Target>),
+    Login,
+    Logout,
+    CheckConfig,
+    ConfigDump,
+    ConfigInit(Option<PathBuf>),
+    Version,
+    Help
+}
+
+// Page to open at startup.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    Subreddit(String),
+    Post { subreddit: String, id: String },
+    User(String)
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CliErr {
+    #[error("{flag} requires a value")]
+    MissingValue { flag: String },
+
+    #[error("unknown option {flag}")]
+    UnknownFlag { flag: String },
+
+    #[error("unexpected argument {arg}")]
+    UnexpectedArg { arg: String },
+
+    #[error("{arg} is not a command, r/<subreddit> or Reddit URL")]
+    InvalidTarget { arg: String },
+
+    #[error("{arg} is a {page} page, which cannot be opened yet")]
+    UnsupportedTarget { arg: String, page: String },
+
+    #[error("{cmd} requires one of: {expected}")]
+    MissingSubcommand { cmd: String, expected: String },
+
+    #[error("unknown {cmd} command {sub}, expected one of: {expected}")]
+    UnknownSubcommand { cmd: String, sub: String, expected: String }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Parse arguments, excluding program name.
+// * Options take values as --opt <value> or --opt=<value>.
+// -----------------------------------------------------------------------------------------------------------
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Cli, CliErr> {
+    let mut cli = Cli {
+        cmd: Cmd::Tui(None),
+        config: None,
+        log_file: None,
+        account: DEFAULT_ACCOUNT.to_string()
+    };
+    let mut positional: Vec<String> = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg.starts_with('-') && arg.len() > 1 {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None)
+            };
+            let mut value = || -> Result<String, CliErr> {
+                match inline_value.clone().or_else(|| args.next()) {
+                    Some(value) if !value.is_empty() => Ok(value),
+                    _ => Err(CliErr::MissingValue { flag: flag.clone() })
+                }
+            };
+            match flag.as_str() {
+                "--config" => { cli.config = Some(PathBuf::from(value()?)); },
+                "--log-file" => { cli.log_file = Some(PathBuf::from(value()?)); },
+                "--account" => { cli.account = value()?; },
+                "-V" | "--version" => { return Ok(Cli { cmd: Cmd::Version, ..cli }); },
+                "-h" | "--help" => { return Ok(Cli { cmd: Cmd::Help, ..cli }); },
+                // Kept from before subcommands.
+                "--check-config" => { positional.insert(0, "check-config".to_string()); },
+                _ => { return Err(CliErr::UnknownFlag { flag }); }
+            }
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let cmd = match positional.next() {
+        None => Cmd::Tui(None),
+        Some(arg) => match arg.as_str() {
+            "login" => Cmd::Login,
+            "logout" => Cmd::Logout,
+            "check-config" => Cmd::CheckConfig,
+            "help" => Cmd::Help,
+            "config" => {
+                let expected = "dump, init".to_string();
+                match positional.next().as_deref() {
+                    Some("dump") => Cmd::ConfigDump,
+                    Some("init") => Cmd::ConfigInit(positional.next().map(PathBuf::from)),
+                    Some(sub) => { return Err(CliErr::UnknownSubcommand { cmd: arg.clone(), sub: sub.to_string(), expected }); },
+                    None => { return Err(CliErr::MissingSubcommand { cmd: arg.clone(), expected }); }
+                }
+            },
+            _ => match parse_target(&arg).ok_or(CliErr::InvalidTarget { arg: arg.clone() })? {
+                Target::Post { .. } => { return Err(CliErr::UnsupportedTarget { arg, page: "post".to_string() }); },
+                Target::User(_) => { return Err(CliErr::UnsupportedTarget { arg, page: "user".to_string() }); },
+                target => Cmd::Tui(Some(target))
+            }
+        }
+    };
+    if let Some(arg) = positional.next() {
+        return Err(CliErr::UnexpectedArg { arg });
+    }
+    cli.cmd = cmd;
+    Ok(cli)
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Parse page to open from r/<sub>, u/<user> or Reddit URL, e.g.
+// * https://www.reddit.com/r/rust
+// * https://old.reddit.com/r/rust/comments/abc123/some_title/
+// * reddit.com/user/someone
+// -----------------------------------------------------------------------------------------------------------
+pub fn parse_target(arg: &str) -> Option<Target> {
+    let path = match arg.split_once("://") {
+        Some(("http", rest)) | Some(("https", rest)) => rest,
+        Some(_) => { return None; },
+        None => arg
+    };
+
+    // First part with a . is the host, it must be Reddit.
+    let (host, rest) = path.split_once('/').unwrap_or((path, ""));
+    let path = if host.contains('.') {
+        if host != "reddit.com" && !host.ends_with(".reddit.com") { return None; }
+        rest
+    } else { path };
+
+    let path = path.split(|c| c == '?' || c == '#').next().unwrap_or("");
+    let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+    match parts.as_slice() {
+        ["r", sub] => Some(Target::Subreddit(sub.to_string())),
+        ["r", sub, "comments", id, ..] => Some(Target::Post { subreddit: sub.to_string(), id: id.to_string() }),
+        ["r", sub, ..] => Some(Target::Subreddit(sub.to_string())),
+        ["u", user, ..] | ["user", user, ..] => Some(Target::User(user.to_string())),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{ parse_args, parse_target, Cli, CliErr, Cmd, Target, DEFAULT_ACCOUNT };
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|arg| arg.to_string()).collect()
+    }
+
+    // * Test if commands, targets and options are parsed.
+    // * Test if invalid arguments give errors.
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(args("")), Ok(Cli {
+            cmd: Cmd::Tui(None),
+            config: None,
+            log_file: None,
+            account: DEFAULT_ACCOUNT.to_string()
+        }));
+        assert_eq!(parse_args(args("--config /tmp/c.toml --log-file=/tmp/r.log --account alt login")), Ok(Cli {
+            cmd: Cmd::Login,
+            config: Some(PathBuf::from("/tmp/c.toml")),
+            log_file: Some(PathBuf::from("/tmp/r.log")),
+            account: "alt".to_string()
+        }));
+        assert_eq!(parse_args(args("logout")).unwrap().cmd, Cmd::Logout);
+        assert_eq!(parse_args(args("check-config")).unwrap().cmd, Cmd::CheckConfig);
+        assert_eq!(parse_args(args("--check-config")).unwrap().cmd, Cmd::CheckConfig);
+        assert_eq!(parse_args(args("r/rust --version")).unwrap().cmd, Cmd::Version);
+        assert_eq!(parse_args(args("config dump")).unwrap().cmd, Cmd::ConfigDump);
+        assert_eq!(parse_args(args("config init")).unwrap().cmd, Cmd::ConfigInit(None));
+        assert_eq!(parse_args(args("config init /tmp/c.toml")).unwrap().cmd, Cmd::ConfigInit(Some(PathBuf::from("/tmp/c.toml"))));
+        assert_eq!(parse_args(args("r/rust")).unwrap().cmd, Cmd::Tui(Some(Target::Subreddit("rust".to_string()))));
+
+        assert_eq!(parse_args(args("--config")), Err(CliErr::MissingValue { flag: "--config".to_string() }));
+        assert_eq!(parse_args(args("--foo")), Err(CliErr::UnknownFlag { flag: "--foo".to_string() }));
+        assert_eq!(parse_args(args("login logout")), Err(CliErr::UnexpectedArg { arg: "logout".to_string() }));
+        assert_eq!(parse_args(args("lgoin")), Err(CliErr::InvalidTarget { arg: "lgoin".to_string() }));
+        assert_eq!(parse_args(args("u/someone")),
+            Err(CliErr::UnsupportedTarget { arg: "u/someone".to_string(), page: "user".to_string() }));
+        assert_eq!(parse_args(args("reddit.com/r/rust/comments/abc123")), Err(CliErr::UnsupportedTarget {
+            arg: "reddit.com/r/rust/comments/abc123".to_string(), page: "post".to_string() }));
+        assert_eq!(parse_args(args("config")),
+            Err(CliErr::MissingSubcommand { cmd: "config".to_string(), expected: "dump, init".to_string() }));
+        assert_eq!(parse_args(args("config show")), Err(CliErr::UnknownSubcommand {
+            cmd: "config".to_string(), sub: "show".to_string(), expected: "dump, init".to_string() }));
+        assert_eq!(parse_args(args("config dump now")), Err(CliErr::UnexpectedArg { arg: "now".to_string() }));
+    }
+
+    // Test if subreddit, post and user pages are parsed from r/<sub> and URLs.
+    #[test]
+    fn test_parse_target() {
+        let sub = Some(Target::Subreddit("rust".to_string()));
+        assert_eq!(parse_target("r/rust"), sub);
+        assert_eq!(parse_target("/r/rust/"), sub);
+        assert_eq!(parse_target("https://www.reddit.com/r/rust"), sub);
+        assert_eq!(parse_target("reddit.com/r/rust/top?t=week"), sub);
+        assert_eq!(parse_target("https://old.reddit.com/r/rust/comments/abc123/some_title/"),
+            Some(Target::Post { subreddit: "rust".to_string(), id: "abc123".to_string() }));
+        assert_eq!(parse_target("https://www.reddit.com/user/someone"), Some(Target::User("someone".to_string())));
+        assert_eq!(parse_target("u/someone"), Some(Target::User("someone".to_string())));
+
+        assert_eq!(parse_target("https://example.com/r/rust"), None);
+        assert_eq!(parse_target("ftp://reddit.com/r/rust"), None);
+        assert_eq!(parse_target("rust"), None);
+    }
+}
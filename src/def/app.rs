@@ -1 +1,48 @@
-pub const CONFIG_DIR_PATHS: &[&str] = &["~/.ravana", "docs/.ravana", "~/Projects/ravana/docs/.ravana/Config.toml"];
+// Config is looked up in --config, $RAVANA_CONFIG, $XDG_CONFIG_HOME/ravana and ~/.config/ravana in that order.
+pub const CONFIG_ENV_VAR: &str = "RAVANA_CONFIG";
+pub const CONFIG_DIR_NAME: &str = "ravana";
+pub const CONFIG_FILE_NAME: &str = "Config.toml";
+// Config layered under the user's one.
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/ravana/Config.toml";
+// Key listing config files merged under the one listing them.
+pub const CONFIG_INCLUDE_KEY: &str = "include";
+pub const MAX_CONFIG_INCLUDE_DEPTH: usize = 8;
+// Documented config with all defaults, written by ravana config init.
+pub const STARTER_CONFIG: &str = include_str!("../../docs/.ravana/Config.toml");
+
+// Key combination replacing <leader> in key-bindings, also used when the leader in config cannot be parsed.
+pub const DEFAULT_LEADER: &str = "<Space>";
+// Milliseconds to wait for the rest of a key-binding before dropping keys typed, 0 waits forever.
+pub const DEFAULT_KEY_TIMEOUT: u64 = 1000;
+
+// Themes are looked up as themes/<name>.toml next to config files, then among presets shipped in the binary.
+pub const THEMES_DIR_NAME: &str = "themes";
+pub const DEFAULT_THEME: &str = "default";
+pub const THEME_PRESETS: &[(&str, &str)] = &[
+    ("default", include_str!("../../docs/.ravana/themes/default.toml")),
+    ("high-contrast", include_str!("../../docs/.ravana/themes/high-contrast.toml")),
+    ("colorblind", include_str!("../../docs/.ravana/themes/colorblind.toml")),
+    ("terminal", include_str!("../../docs/.ravana/themes/terminal.toml")),
+];
+
+// Directory under $XDG_STATE_HOME ( or ~/.local/state ) for data kept across sessions.
+pub const STATE_DIR_NAME: &str = "ravana";
+pub const MACROS_FILE_NAME: &str = "macros.toml";
+// TUI log in the state directory, rotated to ravana.log.1 .. ravana.log.LOG_FILES_KEPT when it grows past
+// MAX_LOG_FILE_SIZE bytes.
+pub const LOG_FILE_NAME: &str = "ravana.log";
+pub const MAX_LOG_FILE_SIZE: u64 = 1 << 20;
+pub const LOG_FILES_KEPT: usize = 3;
+// Log records kept in memory for :messages.
+pub const MAX_RECENT_LOG_RECORDS: usize = 1000;
+// Directory under the state directory for crash reports, each with this many last log records.
+pub const CRASH_REPORTS_DIR_NAME: &str = "crashes";
+pub const CRASH_REPORT_LOG_RECORDS: usize = 100;
+// Directory under the state directory holding a token file per Reddit account.
+pub const ACCOUNTS_DIR_NAME: &str = "accounts";
+// Directory under the state directory drafts composed in $EDITOR are kept in, readable only by the user.
+pub const DRAFTS_DIR_NAME: &str = "drafts";
+// Milliseconds a hidden post can be unhidden in, with :unhide.
+pub const UNDO_HIDE_WINDOW: u64 = 10000;
+// Milliseconds between refreshes of the unread count in the page bar.
+pub const UNREAD_REFRESH_INTERVAL: u64 = 60000;
@@ -3,6 +3,79 @@ pub const APP_QUIT      : &str = "app_quit";
 pub const SCROLL_DOWN   : &str = "scroll_down";
 pub const SCROLL_UP     : &str = "scroll_up";
 
+pub const SELECT_NEXT   : &str = "select_next"; // Select next item of page, e.g. post.
+pub const SELECT_PREV   : &str = "select_prev";
+
 pub const SWITCH_PAGE   : &str = "switch_page"; // Arguments: page number.
 pub const NEXT_PAGE     : &str = "next_page";
 pub const PREV_PAGE     : &str = "prev_page";
+
+pub const MAP           : &str = "map"; // Arguments: key-combination, command line.
+pub const UNMAP         : &str = "unmap"; // Arguments: key-combination.
+pub const SET           : &str = "set"; // Arguments: option, value.
+
+pub const CONFIG        : &str = "config"; // Show resolved config in a page.
+pub const RELOAD_CONFIG : &str = "reload-config";
+pub const RELOAD_CONFIG_ALIAS : &str = "reload_config"; // Same as reload-config.
+pub const MESSAGES      : &str = "messages"; // Arguments: optional level, optional search text.
+
+pub const SUSPEND       : &str = "suspend"; // Drop to shell, resume with fg.
+
+pub const UPVOTE        : &str = "upvote"; // Vote on selected item, e.g. post.
+pub const DOWNVOTE      : &str = "downvote";
+pub const UNVOTE        : &str = "unvote";
+pub const SAVE          : &str = "save";
+pub const UNSAVE        : &str = "unsave";
+pub const HIDE          : &str = "hide"; // Undo with unhide for a few seconds after.
+pub const UNHIDE        : &str = "unhide";
+
+pub const REPLY         : &str = "reply"; // Reply to selected item in $EDITOR.
+pub const SUBMIT        : &str = "submit"; // Submit post to subreddit of focused page in $EDITOR.
+pub const EDIT          : &str = "edit"; // Edit selected own item in $EDITOR.
+pub const DELETE        : &str = "delete"; // Delete selected own item, once confirmed.
+
+pub const SAVED         : &str = "saved"; // Open page of saved posts and comments.
+pub const LOAD_MORE     : &str = "load_more"; // Fetch next page of items on focused page.
+
+// Arguments: optional tab, one of unread, all, messages, comment-replies or mentions.
+pub const INBOX         : &str = "inbox";
+pub const MARK_READ     : &str = "mark_read"; // Mark selected message read.
+pub const MARK_UNREAD   : &str = "mark_unread";
+pub const COMPOSE       : &str = "compose"; // Arguments: user. Write private message in $EDITOR.
+
+// All commands, for validating command lines.
+pub const COMMANDS: &[&str] = &[
+    APP_QUIT,
+    SCROLL_DOWN,
+    SCROLL_UP,
+    SELECT_NEXT,
+    SELECT_PREV,
+    SWITCH_PAGE,
+    NEXT_PAGE,
+    PREV_PAGE,
+    MAP,
+    UNMAP,
+    SET,
+    CONFIG,
+    RELOAD_CONFIG,
+    RELOAD_CONFIG_ALIAS,
+    MESSAGES,
+    SUSPEND,
+    UPVOTE,
+    DOWNVOTE,
+    UNVOTE,
+    SAVE,
+    UNSAVE,
+    HIDE,
+    UNHIDE,
+    REPLY,
+    SUBMIT,
+    EDIT,
+    DELETE,
+    SAVED,
+    LOAD_MORE,
+    INBOX,
+    MARK_READ,
+    MARK_UNREAD,
+    COMPOSE,
+];
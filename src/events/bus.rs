@@ -0,0 +1,108 @@
+use anyhow::{ anyhow, Result };
+use nix::{
+    fcntl::{ fcntl, FcntlArg, FdFlag, OFlag },
+    unistd::{ close, pipe, read, write }
+};
+use std::{
+    os::unix::io::RawFd,
+    sync::{ mpsc::{ channel, Receiver, Sender }, Arc }
+};
+
+use super::event::Event;
+
+// -----------------------------------------------------------------------------------------------------------
+// Queue of events sent from anywhere, e.g. background jobs, to the main loop.
+// * Sending writes to a pipe, waking the main loop polling EventQueue::fd().
+// * Events are taken in the order they were sent.
+// -----------------------------------------------------------------------------------------------------------
+pub fn event_bus() -> Result<(EventSender, EventQueue)> {
+    let (read_fd, write_fd) = pipe()?;
+    for fd in [read_fd, write_fd] {
+        fcntl(fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
+        fcntl(fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+    }
+    let (sender, receiver) = channel();
+
+    Ok((
+        EventSender { sender, wake: Arc::new(Fd(write_fd)) },
+        EventQueue { receiver, wake: Fd(read_fd) }
+    ))
+}
+
+// Pipe end, closed on drop.
+struct Fd(RawFd);
+
+impl Drop for Fd {
+    fn drop(&mut self) {
+        let _ = close(self.0);
+    }
+}
+
+#[derive(Clone)]
+pub struct EventSender {
+    sender: Sender<Event>,
+    wake: Arc<Fd>
+}
+
+impl EventSender {
+    // Fails once the queue is dropped, i.e. the main loop ended.
+    pub fn send(&self, event: Event) -> Result<()> {
+        self.sender.send(event).map_err(|e| anyhow!("Unable to send event, main loop ended: {:?}", e.0))?;
+        // A full pipe is already readable, nothing to wake.
+        let _ = write(self.wake.0, &[0]);
+        Ok(())
+    }
+}
+
+pub struct EventQueue {
+    receiver: Receiver<Event>,
+    wake: Fd
+}
+
+impl EventQueue {
+    // Fd to poll, readable while events are queued.
+    pub fn fd(&self) -> RawFd {
+        self.wake.0
+    }
+
+    // Take queued events, oldest first.
+    pub fn take(&self) -> Vec<Event> {
+        let mut buf = [0u8; 64];
+        while let Ok(n) = read(self.wake.0, &mut buf) {
+            if n == 0 { break; }
+        }
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nix::poll::{ poll, PollFd, PollFlags };
+    use std::thread;
+
+    use super::event_bus;
+    use crate::events::event::Event;
+
+    // Test if events sent from other threads wake the poll and are taken in order.
+    #[test]
+    fn test_event_bus() {
+        let (sender, queue) = event_bus().unwrap();
+        let mut fds = [PollFd::new(queue.fd(), PollFlags::POLLIN)];
+        assert_eq!(poll(&mut fds, 0).unwrap(), 0);
+
+        let thread_sender = sender.clone();
+        thread::spawn(move || {
+            thread_sender.send(Event::Fetched { page: 1, posts: Err("timed out".to_string()) }).unwrap();
+            thread_sender.send(Event::Resize).unwrap();
+        }).join().unwrap();
+        assert_eq!(poll(&mut fds, 0).unwrap(), 1);
+
+        let events = queue.take();
+        assert!(matches!(events[..], [Event::Fetched { page: 1, posts: Err(_) }, Event::Resize]));
+        assert_eq!(poll(&mut fds, 0).unwrap(), 0);
+        assert!(queue.take().is_empty());
+
+        drop(queue);
+        assert!(sender.send(Event::Quit).is_err());
+    }
+}
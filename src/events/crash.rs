@@ -0,0 +1,130 @@
+use libnotcurses_sys::Nc;
+use log::error;
+use std::{
+    backtrace::Backtrace,
+    fs,
+    panic::{ self, PanicHookInfo },
+    path::PathBuf,
+    ptr,
+    sync::atomic::{ AtomicBool, AtomicPtr, Ordering },
+    thread,
+    time::SystemTime
+};
+
+use crate::{
+    def::app::{ CRASH_REPORT_LOG_RECORDS, CRASH_REPORTS_DIR_NAME },
+    jobs::{ logger::{ fmt_time, recent_records, LogRecord }, state_dir }
+};
+
+// Nc instance of the running TUI, for crash handling to stop it.
+static NC: AtomicPtr<Nc> = AtomicPtr::new(ptr::null_mut());
+// Whether crash handling stopped Nc, after which it must not be touched.
+static NC_STOPPED: AtomicBool = AtomicBool::new(false);
+
+pub fn register_nc(nc: &mut Nc) {
+    NC.store(nc as *mut Nc, Ordering::SeqCst);
+}
+
+// Unregister Nc before stopping it. Returns false if crash handling already stopped it.
+pub fn unregister_nc() -> bool {
+    NC.store(ptr::null_mut(), Ordering::SeqCst);
+    !NC_STOPPED.load(Ordering::SeqCst)
+}
+
+// Stop registered Nc, restoring the terminal.
+pub fn stop_nc() {
+    let nc = NC.swap(ptr::null_mut(), Ordering::SeqCst);
+    if !nc.is_null() {
+        NC_STOPPED.store(true, Ordering::SeqCst);
+        // Nothing left to report errors to, terminal is being given back.
+        let _ = unsafe { (*nc).stop() };
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * On panic in the main thread stop Nc, so the panic message lands on a usable terminal.
+// * Write crash report with backtrace and recent log records to the state directory, printing its path.
+// * Panics in other threads leave Nc to the main thread, only logging and reporting.
+// -----------------------------------------------------------------------------------------------------------
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let main_thread = thread::current().name() == Some("main");
+        if main_thread { stop_nc(); }
+
+        let backtrace = Backtrace::force_capture().to_string();
+        let report = crash_report(info, &backtrace, &recent_records());
+        error!("{}", panic_message(info));
+
+        default_hook(info);
+        match write_crash_report(&report) {
+            Some(path) => { eprintln!("ravana crashed, crash report written to {}", path.display()); },
+            None => { eprintln!("ravana crashed, unable to write crash report:\n{}", report); }
+        }
+    }));
+}
+
+// e.g. panicked at 'index out of bounds', src/input/command_to_event.rs:38:30
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown cause".to_string());
+    match info.location() {
+        Some(location) => format!("panicked at '{}', {}:{}:{}", message, location.file(), location.line(), location.column()),
+        None => format!("panicked at '{}'", message)
+    }
+}
+
+fn crash_report(info: &PanicHookInfo, backtrace: &str, records: &[LogRecord]) -> String {
+    fmt_crash_report(&panic_message(info), thread::current().name().unwrap_or("unnamed"), backtrace, records)
+}
+
+pub(crate) fn fmt_crash_report(message: &str, thread: &str, backtrace: &str, records: &[LogRecord]) -> String {
+    let mut report = format!("ravana {} crashed at {} UTC\nThread '{}' {}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"), fmt_time(SystemTime::now()), thread, message, backtrace.trim_end());
+
+    let skipped = records.len().saturating_sub(CRASH_REPORT_LOG_RECORDS);
+    report.push_str(&format!("\nLast {} log record(s):\n", records.len() - skipped));
+    for record in records.iter().skip(skipped) {
+        report.push_str(&format!("{}\n", record));
+    }
+    report
+}
+
+// Write report to crashes/ in the state directory, None if it cannot be written.
+fn write_crash_report(report: &str) -> Option<PathBuf> {
+    let dir = state_dir()?.join(CRASH_REPORTS_DIR_NAME);
+    fs::create_dir_all(&dir).ok()?;
+    let name = format!("crash-{}.txt", fmt_time(SystemTime::now()).replace(' ', "_").replace(':', "-"));
+    let path = dir.join(name);
+    fs::write(&path, report).ok()?;
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+    use std::time::UNIX_EPOCH;
+
+    use super::fmt_crash_report;
+    use crate::{ def::app::CRASH_REPORT_LOG_RECORDS, jobs::logger::LogRecord };
+
+    // Test if report has the panic, its backtrace and only the last log records.
+    #[test]
+    fn test_fmt_crash_report() {
+        let records: Vec<LogRecord> = (0..CRASH_REPORT_LOG_RECORDS + 5).map(|i| LogRecord {
+            time: UNIX_EPOCH,
+            level: Level::Info,
+            target: "ravana".to_string(),
+            message: format!("record {}", i)
+        }).collect();
+
+        let report = fmt_crash_report("panicked at 'boom', src/main.rs:1:1", "main", "0: main\n", &records);
+        assert!(report.contains("Thread 'main' panicked at 'boom', src/main.rs:1:1\n\nBacktrace:\n0: main\n"));
+        assert!(report.contains(&format!("Last {} log record(s):\n1970-01-01 00:00:00 INFO  ravana: record 5\n",
+            CRASH_REPORT_LOG_RECORDS)));
+        assert!(report.ends_with(&format!("record {}\n", CRASH_REPORT_LOG_RECORDS + 4)));
+        assert!(!report.contains("record 4\n"));
+    }
+}
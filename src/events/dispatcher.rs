@@ -0,0 +1,106 @@
+use anyhow::Result;
+use log::{ error, info };
+
+use super::{ event::Event, lifecycle::reload_config };
+use crate::{
+    input::{ command_to_event::exec_cmd, KeyBindingsTrie },
+    tools::log_err_desc,
+    tui::{ App, AppRes }
+};
+
+// Whether the main loop goes on after an event.
+#[derive(Debug, PartialEq)]
+pub enum Flow {
+    Continue,
+    Quit
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Route event to App, or to the page it concerns.
+// * Failing events are logged, failing commands shown in status as well. Only Quit or a quitting command ends
+//   the main loop.
+// -----------------------------------------------------------------------------------------------------------
+pub fn dispatch(app: &mut App, kbt: &mut KeyBindingsTrie, event: Event) -> Flow {
+    match event {
+        Event::Cmd(line) => {
+            let res = exec_cmd(app, &line);
+            return handle_cmd_res(app, kbt, &line, res);
+        },
+        Event::Fetched { page, posts } => {
+            log_err_desc!(app.add_fetched(page, posts), "Unable to show fetched posts");
+        },
+        Event::Voted { fullname, prev, res } => {
+            log_err_desc!(app.voted(&fullname, prev, res), "Unable to roll back vote");
+        },
+        Event::Saved { fullname, saved, res } => {
+            log_err_desc!(app.saved(&fullname, saved, res), "Unable to roll back saving");
+        },
+        Event::Hidden { fullname, hidden, res } => {
+            log_err_desc!(app.hidden(&fullname, hidden, res), "Unable to roll back hiding");
+        },
+        Event::FetchedListing { page, listing } => {
+            log_err_desc!(app.add_fetched_listing(page, listing), "Unable to show fetched items");
+        },
+        Event::Sent { what, draft, res } => {
+            log_err_desc!(app.sent(&what, &draft, res), "Unable to report sending");
+        },
+        Event::Edited { fullname, body, draft, res } => {
+            log_err_desc!(app.edited(&fullname, &body, &draft, res), "Unable to report editing");
+        },
+        Event::Deleted { fullname, res } => {
+            log_err_desc!(app.deleted(&fullname, res), "Unable to report deleting");
+        },
+        Event::LoggedIn { username } => {
+            app.logged_in(username);
+        },
+        Event::MarkedRead { fullname, read, res } => {
+            log_err_desc!(app.marked_read(&fullname, read, res), "Unable to roll back marking read");
+        },
+        Event::Unread { count } => {
+            log_err_desc!(app.unread_counted(count), "Unable to show unread count");
+        },
+        Event::Resize => {
+            log_err_desc!(app.resize(), "Unable to resize");
+        },
+        Event::ReloadConfig => {
+            log_err_desc!(reload_config(app, kbt), "Unable to reload config");
+        },
+        Event::Quit => {
+            info!("Quitting.");
+            return Flow::Quit;
+        },
+        Event::Timer(timer) => {
+            // Timers of App, e.g. undo windows, are not the page's.
+            if !app.timer_expired(timer) {
+                log_err_desc!(app.route_to_foc_page(&Event::Timer(timer)), "Unable to handle event");
+            }
+        }
+    }
+    Flow::Continue
+}
+
+// Act on response of executed command.
+fn handle_cmd_res(app: &mut App, kbt: &mut KeyBindingsTrie, line: &str, res: Result<Option<AppRes>>) -> Flow {
+    match res {
+        Ok(Some(AppRes::AppQuit)) => { return Flow::Quit; },
+        Ok(Some(AppRes::MapKeys(key_comb, cmd))) => {
+            if let Err(e) = kbt.map(&key_comb, &cmd) {
+                return handle_cmd_res(app, kbt, line, Err(e));
+            }
+        },
+        Ok(Some(AppRes::UnmapKeys(key_comb))) => {
+            if let Err(e) = kbt.unmap(&key_comb) {
+                return handle_cmd_res(app, kbt, line, Err(e));
+            }
+        },
+        Ok(Some(AppRes::ReloadConfig)) => {
+            return dispatch(app, kbt, Event::ReloadConfig);
+        },
+        Err(e) => {
+            error!("Unable to execute command {}: {}", line, e);
+            log_err_desc!(app.show_status(&format!("{}: {}", line, e)), "Unable to show command error");
+        },
+        _ => {  }
+    }
+    Flow::Continue
+}
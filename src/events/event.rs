@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use crate::api::model::{ Listing, Post, Vote };
+
+// Id of a timer, given when it is scheduled.
+pub type TimerId = u64;
+
+// -----------------------------------------------------------------------------------------------------------
+// Event handled by the main loop.
+// * Input, background jobs, timers, the terminal and config files each produce events, dispatch routes them
+//   to App or the focused page.
+// * Sendable across threads, e.g. fetch results from background jobs through events::bus.
+// -----------------------------------------------------------------------------------------------------------
+#[derive(Debug)]
+pub enum Event {
+    // Command line from a key-binding or the command palette, e.g. switch_page 1.
+    Cmd(String),
+    // Posts fetched for page at index, or why fetching failed.
+    Fetched { page: usize, posts: Result<Vec<Post>, String> },
+    // Vote sent for item with fullname, or why Reddit refused it. prev is the vote to roll back to.
+    Voted { fullname: String, prev: Vote, res: Result<(), String> },
+    // Item with fullname saved or unsaved, or why Reddit refused it.
+    Saved { fullname: String, saved: bool, res: Result<(), String> },
+    // Post with fullname hidden or unhidden, or why Reddit refused it.
+    Hidden { fullname: String, hidden: bool, res: Result<(), String> },
+    // Page of a listing fetched for page at index, e.g. saved items, or why fetching failed.
+    FetchedListing { page: usize, listing: Result<Listing, String> },
+    // Draft of what, e.g. reply, sent, or why Reddit refused it. The draft file is kept if refused.
+    Sent { what: String, draft: PathBuf, res: Result<(), String> },
+    // Own item with fullname edited to body, or why Reddit refused it. The draft file is kept if refused.
+    Edited { fullname: String, body: String, draft: PathBuf, res: Result<(), String> },
+    // Own item with fullname deleted, or why Reddit refused it.
+    Deleted { fullname: String, res: Result<(), String> },
+    // Name of the account logged in fetched, or why fetching failed.
+    LoggedIn { username: Result<String, String> },
+    // Message with fullname marked read or unread, or why Reddit refused it.
+    MarkedRead { fullname: String, read: bool, res: Result<(), String> },
+    // Unread messages in the inbox counted, or why counting failed.
+    Unread { count: Result<u32, String> },
+    // Timer scheduled in events::timers expired.
+    Timer(TimerId),
+    // Terminal resized.
+    Resize,
+    // Config files changed.
+    ReloadConfig,
+    // Quit, e.g. on a quit signal.
+    Quit
+}
+
+impl Event {
+    pub fn cmd(line: &str) -> Event {
+        Event::Cmd(line.trim().to_string())
+    }
+}
@@ -1,43 +1,228 @@
 use anyhow::{ anyhow, Result };
 use libnotcurses_sys::Nc;
-use log::{ error };
-use std::sync::{ Mutex, Arc };
+use log::{ error, info, warn, LevelFilter };
+use std::{
+    path::Path,
+    sync::{ Mutex, Arc }
+};
 
+use super::{ bus::event_bus, crash::{ install_panic_hook, register_nc }, signals::install_signal_handlers };
 use crate::{
-    input::{ listen, create_key_bindings_trie },
-    jobs::config::load_config,
-    tools::{ log_err_desc_ret, log_err_ret },
-    tui::{ App, TuiPrefs, val_tui_prefs_des },
+    api::{ accounts::{ load_token, remove_token, save_token }, client::Client, oauth::oauth_process },
+    cli::Target,
+    input::{ listen, create_key_bindings_trie, val_key_bindings, KeyBindingErr, KeyBindingsTrie, Macros },
+    def::{ app::MACROS_FILE_NAME, commands::COMMANDS },
+    jobs::{
+        config::{ dump_config, load_config, write_starter_config, LoadedConfig },
+        diagnostics::suggest,
+        logger::set_log_level,
+        state_dir,
+        user_config_path,
+        Diagnostic
+    },
+    tools::{ log_err_desc, log_err_desc_ret },
+    tui::{ App, TuiPrefs, terminal::save_cooked_mode, val_tui_prefs_des },
 };
 
 // -----------------------------------------------------------------------------------------------------------
 // * Main loop.
-// * Input --> Process --> State --> TUI --> Input.
+// * Input / Background jobs --> Event --> Dispatch --> State --> TUI --> Input.
 // -----------------------------------------------------------------------------------------------------------
-pub fn ravana() -> Result<()> {
-    let config = load_config();
-
-    // Instantiating Nc instance.
-    let nc = Arc::new(Mutex::new(unsafe { log_err_desc_ret!(Nc::new(), "Failed to instantiate Nc.")? }));
+pub fn ravana(config_path: Option<&Path>, target: Option<&Target>, account: &str) -> Result<()> {
+    let loaded = load_config(config_path)?;
+    let config = &loaded.config;
 
-    // Validate config
-    {
-        if !val_tui_prefs_des(&config.tui) { 
-            return log_err_ret!(Err(anyhow!("Invalid TUI format in config.")));
-        } 
+    // Report config problems on stderr before TUI takes over the terminal, and in TUI status later.
+    let diagnostics = config_diagnostics(&loaded);
+    for diagnostic in diagnostics.iter() {
+        warn!("{}", diagnostic);
+        eprintln!("{}", diagnostic);
     }
 
+    set_log_level(config.log_level.parse().unwrap_or(LevelFilter::Info));
+
+    // Built before TUI starts, problems in key-bindings having been reported above.
+    let kbt = log_err_desc_ret!(create_key_bindings_trie(&config.key_bindings, &config.leader), "Failed to create KB trie")?;
+
+    let client = match load_token(account) {
+        Ok(Some(token)) => {
+            info!("Using account {}.", account);
+            log_err_desc_ret!(Client::new(&token), "Unable to create Reddit client").ok()
+        },
+        Ok(None) => {
+            info!("Account {} not logged in, browsing anonymously.", account);
+            None
+        },
+        Err(e) => {
+            warn!("Unable to load account {}: {}", account, e);
+            None
+        }
+    };
+
+
+    // Instantiating Nc instance, restored on panic and quit signals.
+    install_panic_hook();
+    log_err_desc!(save_cooked_mode(), "Unable to save terminal mode, suspend will not work");
+    let nc_ref = unsafe { log_err_desc_ret!(Nc::new(), "Failed to instantiate Nc.")? };
+    register_nc(nc_ref);
+    log_err_desc!(install_signal_handlers(), "Unable to handle quit signals");
+    let nc = Arc::new(Mutex::new(nc_ref));
+
     let mut app = App::new(nc.clone(),
         log_err_desc_ret!(TuiPrefs::gen_tui_prefs(&config.tui),
             "Failed to generate TUI prefs"
         )?
     )?;
+    if let Some(client) = client {
+        app.set_client(client);
+    }
     app.dummy_render()?;
-    app.render().unwrap();
+    if let Some(target) = target {
+        log_err_desc!(app.open_target(target), "Unable to open page");
+    }
+    app.render()?;
+    if !diagnostics.is_empty() {
+        let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+        log_err_desc!(app.show_status(&format!("Config: {} error(s), {} warning(s), run ravana check-config for details",
+            errors, diagnostics.len() - errors)), "Unable to show config problems");
+    }
+
+    let macros = match (config.persist_macros, state_dir()) {
+        (true, Some(dir)) => Macros::with_file(dir.join(MACROS_FILE_NAME)),
+        (true, None) => {
+            warn!("No state directory found, macros will not persist.");
+            Macros::new()
+        },
+        _ => Macros::new()
+    };
+
+    app.set_loaded_config(loaded);
+
+    let (event_sender, events) = event_bus()?;
+    app.set_event_sender(event_sender);
+    log_err_desc!(app.fetch_username(), "Unable to fetch account name");
+    log_err_desc!(app.start_unread_refresh(), "Unable to count unread messages");
+    listen(nc, kbt, macros, events, &mut app)?;
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Validate config without starting TUI.
+// * Print problems found, return whether config is valid.
+// -----------------------------------------------------------------------------------------------------------
+pub fn check_config(config_path: Option<&Path>) -> bool {
+    let loaded = match load_config(config_path) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            println!("error: {}", e);
+            return false;
+        }
+    };
+    let diagnostics = config_diagnostics(&loaded);
+
+    for diagnostic in diagnostics.iter() {
+        println!("{}", diagnostic);
+    }
+    let valid = !diagnostics.iter().any(|d| d.is_error());
+    if diagnostics.is_empty() { println!("Config OK."); }
+    valid
+}
+
+// Print resolved config, marking where each value came from.
+pub fn print_config(config_path: Option<&Path>) -> Result<()> {
+    print!("{}", dump_config(&load_config(config_path)?));
+    Ok(())
+}
+
+// Write commented starter config to path, or to user config path.
+pub fn init_config(path: Option<&Path>) -> Result<()> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => user_config_path().ok_or_else(|| anyhow!("No config directory found, set $XDG_CONFIG_HOME or $HOME"))?
+    };
+    write_starter_config(&path)?;
+    println!("Wrote starter config to {}.", path.display());
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Reload config the app runs with, on :reload-config or when its files change.
+// * Re-theme widgets and replace key-bindings, dropping ones added by :map.
+// * Config with errors is not applied, the old one is kept and the errors reported.
+// -----------------------------------------------------------------------------------------------------------
+pub fn reload_config(app: &mut App, kbt: &mut KeyBindingsTrie) -> Result<()> {
+    let cli_path = app.loaded_config().and_then(|loaded| loaded.cli_path.clone());
+    let loaded = match load_config(cli_path.as_deref()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            warn!("{}", e);
+            return app.show_status(&format!("Config not reloaded: {}", e));
+        }
+    };
+
+    let diagnostics = config_diagnostics(&loaded);
+    for diagnostic in diagnostics.iter() {
+        warn!("{}", diagnostic);
+    }
+    let errors: Vec<&Diagnostic> = diagnostics.iter().filter(|d| d.is_error()).collect();
+    if let Some(e) = errors.first() {
+        return app.show_status(&format!("Config not reloaded, {} error(s): {}", errors.len(), e));
+    }
+
+    let tui_prefs = TuiPrefs::gen_tui_prefs(&loaded.config.tui)?;
+    *kbt = create_key_bindings_trie(&loaded.config.key_bindings, &loaded.config.leader)?;
+    app.set_tui_prefs(tui_prefs)?;
+    set_log_level(loaded.config.log_level.parse().unwrap_or(LevelFilter::Info));
+    app.set_loaded_config(loaded);
+    info!("Config reloaded.");
+
+    app.render()?;
+    if diagnostics.is_empty() {
+        app.show_status("Config reloaded.")
+    } else {
+        app.show_status(&format!("Config reloaded with {} warning(s), run ravana check-config for details",
+            diagnostics.len()))
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Problems found loading config, in TUI prefs and in key-bindings.
+// * Each pointing at the file setting the key, where possible.
+// -----------------------------------------------------------------------------------------------------------
+fn config_diagnostics(loaded: &LoadedConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = loaded.diagnostics.clone();
+    let config = &loaded.config;
 
-    let kbt = log_err_desc_ret!(create_key_bindings_trie(&config.key_bindings), "Failed to create KB trie")?;
+    let mut later = val_tui_prefs_des(&config.tui);
+    for e in val_key_bindings(&config.key_bindings, &config.leader) {
+        let mut diagnostic = Diagnostic::error(&e.key_path(), e.to_string());
+        if let KeyBindingErr::UnknownCommand { name, .. } = &e {
+            diagnostic.suggestion = suggest(name, COMMANDS.iter().copied());
+        }
+        later.push(diagnostic);
+    }
+    for mut diagnostic in later {
+        loaded.locate(&mut diagnostic);
+        diagnostics.push(diagnostic);
+    }
+    diagnostics
+}
 
-    listen(nc, kbt, &mut app).unwrap();
+// -----------------------------------------------------------------------------------------------------------
+// * Log in account through OAuth in browser.
+// * Save token for later sessions.
+// -----------------------------------------------------------------------------------------------------------
+pub fn login(account: &str) -> Result<()> {
+    let token = oauth_process()?;
+    save_token(account, &token)?;
+    println!("Logged in as account {}.", account);
+    Ok(())
+}
 
+// Remove saved token of account.
+pub fn logout(account: &str) -> Result<()> {
+    if remove_token(account)? { println!("Logged out account {}.", account); }
+    else { println!("Account {} is not logged in.", account); }
     Ok(())
 }
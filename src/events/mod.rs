@@ -1,2 +1,7 @@
+pub mod bus;
+pub mod crash;
+pub mod dispatcher;
+pub mod event;
 pub mod lifecycle;
-pub mod user_events;
+pub mod signals;
+pub mod timers;
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use nix::{
+    fcntl::{ fcntl, FcntlArg, OFlag },
+    sys::signal::{ sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal },
+    unistd::{ pipe, read, write }
+};
+use std::{
+    convert::TryFrom,
+    os::unix::io::RawFd,
+    sync::atomic::{ AtomicI32, Ordering }
+};
+
+// Signals quitting the TUI gracefully, restoring the terminal.
+pub const QUIT_SIGNALS: [Signal; 3] = [Signal::SIGTERM, Signal::SIGHUP, Signal::SIGINT];
+
+// Ends of the pipe signal handlers write caught signals to, -1 until installed.
+static PIPE_READ: AtomicI32 = AtomicI32::new(-1);
+static PIPE_WRITE: AtomicI32 = AtomicI32::new(-1);
+
+// Only async-signal-safe work here: hand signal to the poll loop.
+extern "C" fn on_signal(signal: i32) {
+    let fd = PIPE_WRITE.load(Ordering::SeqCst);
+    if fd >= 0 {
+        let _ = write(fd, &[signal as u8]);
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Catch QUIT_SIGNALS, writing them to a pipe polled by listen.
+// * Install after Nc is created, replacing its handlers, so quitting goes through App's Drop.
+// -----------------------------------------------------------------------------------------------------------
+pub fn install_signal_handlers() -> Result<()> {
+    if PIPE_READ.load(Ordering::SeqCst) < 0 {
+        let (read_fd, write_fd) = pipe()?;
+        for fd in [read_fd, write_fd] {
+            fcntl(fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
+            fcntl(fd, FcntlArg::F_SETFD(nix::fcntl::FdFlag::FD_CLOEXEC))?;
+        }
+        PIPE_WRITE.store(write_fd, Ordering::SeqCst);
+        PIPE_READ.store(read_fd, Ordering::SeqCst);
+    }
+
+    let action = SigAction::new(SigHandler::Handler(on_signal), SaFlags::SA_RESTART, SigSet::empty());
+    for signal in QUIT_SIGNALS {
+        unsafe { sigaction(signal, &action)?; }
+    }
+    Ok(())
+}
+
+// Fd to poll for caught signals, None until handlers are installed.
+pub fn signal_fd() -> Option<RawFd> {
+    Some(PIPE_READ.load(Ordering::SeqCst)).filter(|fd| *fd >= 0)
+}
+
+// Read caught signals, returning the last one.
+pub fn take_signal() -> Option<Signal> {
+    let fd = signal_fd()?;
+    let mut buf = [0u8; 16];
+    let mut signal = None;
+    while let Ok(n) = read(fd, &mut buf) {
+        if n == 0 { break; }
+        signal = Signal::try_from(buf[n - 1] as i32).ok().or(signal);
+    }
+    signal
+}
+
+#[cfg(test)]
+mod tests {
+    use nix::sys::signal::{ raise, Signal };
+
+    use super::{ install_signal_handlers, take_signal };
+
+    // Test if a caught signal reaches the pipe instead of ending the process.
+    #[test]
+    fn test_signal_pipe() {
+        install_signal_handlers().unwrap();
+        assert_eq!(take_signal(), None);
+
+        raise(Signal::SIGHUP).unwrap();
+        assert_eq!(take_signal(), Some(Signal::SIGHUP));
+        assert_eq!(take_signal(), None);
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::{ Duration, Instant };
+
+use super::event::TimerId;
+
+// Resolution of timers, delays are rounded up to whole ticks.
+const TICK: Duration = Duration::from_millis(10);
+// Slots of the wheel, one tick each. Timers further out than a turn wait in their slot for later rounds.
+const SLOTS: u64 = 256;
+
+struct Timer {
+    id: TimerId,
+    due: u64, // Tick it is due at.
+    period: Option<u64> // Ticks between expiries of repeating timers.
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Timer wheel the main loop polls with.
+// * Timers are one-shot or repeating, expiring as Event::Timer with the id they were scheduled with.
+// * timeout() is the poll timeout until the next timer is due, expire() takes the timers due.
+// * Repeating timers which missed expiries, e.g. while suspended, expire once and keep their period.
+// -----------------------------------------------------------------------------------------------------------
+pub struct Timers {
+    start: Instant,
+    now: u64, // Ticks expired up to.
+    slots: Vec<Vec<Timer>>,
+    next_id: TimerId
+}
+
+impl Timers {
+    pub fn new() -> Timers {
+        Timers::starting_at(Instant::now())
+    }
+
+    fn starting_at(start: Instant) -> Timers {
+        Timers {
+            start,
+            now: 0,
+            slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+            next_id: 0
+        }
+    }
+
+    // Schedule timer expiring once after delay.
+    pub fn once(&mut self, delay: Duration) -> TimerId {
+        self.schedule_at(Instant::now(), delay, None)
+    }
+
+    // Schedule timer expiring every period.
+    pub fn every(&mut self, period: Duration) -> TimerId {
+        self.schedule_at(Instant::now(), period, Some(period))
+    }
+
+    // Cancel timer, returning whether it was scheduled.
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        for slot in self.slots.iter_mut() {
+            if let Some(i) = slot.iter().position(|timer| timer.id == id) {
+                slot.swap_remove(i);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|slot| slot.is_empty())
+    }
+
+    // Time from now until the next timer is due, None if none is scheduled.
+    pub fn timeout(&self, now: Instant) -> Option<Duration> {
+        // First slot with a timer due this round holds the next one, else it is rounds away.
+        let round_end = self.now + SLOTS;
+        let next_due = (self.now + 1..=round_end)
+            .find_map(|tick| self.slot(tick).iter().map(|timer| timer.due).filter(|due| *due <= round_end).min())
+            .or_else(|| self.slots.iter().flatten().map(|timer| timer.due).min())?;
+        Some((self.start + Duration::from_millis(next_due * TICK.as_millis() as u64)).saturating_duration_since(now))
+    }
+
+    // Ids of timers due by now, soonest first. Repeating ones are scheduled again.
+    pub fn expire(&mut self, now: Instant) -> Vec<TimerId> {
+        let target = self.tick_of(now);
+        let mut expired = Vec::new();
+        // Gaps longer than a turn visit each slot once.
+        for tick in self.now + 1..=target.min(self.now + SLOTS) {
+            let slot = &mut self.slots[(tick % SLOTS) as usize];
+            let mut i = 0;
+            while i < slot.len() {
+                if slot[i].due <= target { expired.push(slot.swap_remove(i)); } else { i += 1; }
+            }
+        }
+        self.now = self.now.max(target);
+
+        expired.sort_by_key(|timer| (timer.due, timer.id));
+        let ids = expired.iter().map(|timer| timer.id).collect();
+        for mut timer in expired.into_iter().filter(|timer| timer.period.is_some()) {
+            let period = timer.period.unwrap_or(1);
+            timer.due += ((target - timer.due) / period + 1) * period;
+            self.insert(timer);
+        }
+        ids
+    }
+
+    fn schedule_at(&mut self, now: Instant, delay: Duration, period: Option<Duration>) -> TimerId {
+        let id = self.next_id;
+        self.next_id += 1;
+        // Ticks already passed are expired with the next expire().
+        let due = self.now.max(self.tick_of(now)) + ticks(delay);
+        self.insert(Timer { id, due, period: period.map(ticks) });
+        id
+    }
+
+    fn insert(&mut self, timer: Timer) {
+        self.slots[(timer.due % SLOTS) as usize].push(timer);
+    }
+
+    fn slot(&self, tick: u64) -> &Vec<Timer> {
+        &self.slots[(tick % SLOTS) as usize]
+    }
+
+    fn tick_of(&self, instant: Instant) -> u64 {
+        (instant.saturating_duration_since(self.start).as_millis() / TICK.as_millis()) as u64
+    }
+}
+
+// Whole ticks in duration rounded up, at least one.
+fn ticks(duration: Duration) -> u64 {
+    ((duration.as_millis() + TICK.as_millis() - 1) / TICK.as_millis()).max(1) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{ Duration, Instant };
+
+    use super::{ Timers, SLOTS, TICK };
+
+    // Test if one-shot timers expire once, in order due, and cancelled ones not at all.
+    #[test]
+    fn test_one_shot_timers() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut timers = Timers::starting_at(start);
+        assert_eq!(timers.timeout(start), None);
+
+        let late = timers.schedule_at(start, Duration::from_millis(50), None);
+        let soon = timers.schedule_at(start, Duration::from_millis(15), None);
+        let cancelled = timers.schedule_at(start, Duration::from_millis(30), None);
+        assert_eq!(timers.timeout(start), Some(Duration::from_millis(20)));
+        assert!(timers.cancel(cancelled));
+        assert!(!timers.cancel(cancelled));
+
+        assert!(timers.expire(at(10)).is_empty());
+        assert_eq!(timers.timeout(at(10)), Some(Duration::from_millis(10)));
+        assert_eq!(timers.expire(at(60)), vec![soon, late]);
+        assert!(timers.expire(at(100)).is_empty());
+        assert!(timers.is_empty());
+    }
+
+    // Test if repeating timers expire every period, once for expiries missed, and wait rounds past a turn.
+    #[test]
+    fn test_repeating_timers() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut timers = Timers::starting_at(start);
+
+        let tick = timers.schedule_at(start, Duration::from_millis(100), Some(Duration::from_millis(100)));
+        let far = timers.schedule_at(start, TICK * (SLOTS as u32 + 10), None);
+        assert_eq!(timers.expire(at(100)), vec![tick]);
+        assert!(timers.expire(at(150)).is_empty());
+        assert_eq!(timers.expire(at(200)), vec![tick]);
+        assert_eq!(timers.timeout(at(200)), Some(Duration::from_millis(100)));
+
+        // Missed expiries at 300..=1000 expire once, next one stays on the period.
+        assert_eq!(timers.expire(at(1050)), vec![tick]);
+        assert_eq!(timers.timeout(at(1050)), Some(Duration::from_millis(50)));
+
+        assert_eq!(timers.expire(at(TICK.as_millis() as u64 * (SLOTS + 10))), vec![tick, far]);
+        assert!(timers.cancel(tick));
+        assert!(timers.is_empty());
+    }
+}
@@ -1,10 +0,0 @@
-use anyhow::Result;
-
-use crate::tui::App;
-
-// -----------------------------------------------------------------------------------------------------------
-// * Send message to quit TUI.
-// -----------------------------------------------------------------------------------------------------------
-pub fn e_app_quit(app: &mut App) -> Result<()> {
-        Ok(())
-}
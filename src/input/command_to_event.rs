@@ -1,7 +1,11 @@
-use anyhow::Result;
+use anyhow::{ anyhow, Result };
+use log::LevelFilter;
 
 use crate::{
+    api::model::{ InboxTab, Vote },
     def::commands::*,
+    input::val_cmd,
+    jobs::logger::messages_text,
     tui::{ App, AppRes }
 };
 
@@ -11,6 +15,12 @@ use crate::{
 // -----------------------------------------------------------------------------------------------------------
 pub fn exec_cmd(app: &mut App,
                       cmd: &str) -> Result<Option<AppRes>> {
+    // Commands only changing app state.
+    if app.model_mut().exec(cmd)? {
+        app.render()?;
+        return Ok(None);
+    }
+
     let args: Vec<&str> = cmd.split(" ").collect();
 
     match args[0] {
@@ -22,31 +32,121 @@ pub fn exec_cmd(app: &mut App,
             // }
             Ok(Some(AppRes::AppQuit))
         },
-        SCROLL_DOWN => {
-            app.scroll_down();
+        MAP => {
+            let key_comb = args.get(1).ok_or_else(|| anyhow!("{}: missing key-combination", MAP))?;
+            if args.len() < 3 { return Err(anyhow!("{}: missing command line", MAP)); }
+            let cmd_line = args[2..].join(" ");
+            val_cmd(&cmd_line).map_err(|e| anyhow!("{}: {}", MAP, e))?;
+            Ok(Some(AppRes::MapKeys(key_comb.to_string(), cmd_line)))
+        },
+        UNMAP => {
+            let key_comb = args.get(1).ok_or_else(|| anyhow!("{}: missing key-combination", UNMAP))?;
+            Ok(Some(AppRes::UnmapKeys(key_comb.to_string())))
+        },
+        SET => {
+            let option = args.get(1).ok_or_else(|| anyhow!("{}: missing option", SET))?;
+            let value = args.get(2).ok_or_else(|| anyhow!("{}: missing value", SET))?;
+            app.set_pref(option, value)?;
             app.render()?;
             Ok(None)
         },
-        SCROLL_UP => {
-            app.scroll_up();
+        CONFIG => {
+            app.show_config()?;
             app.render()?;
             Ok(None)
         },
-        SWITCH_PAGE => {
-            app.set_foc_page(args[1].parse::<usize>()?);
-            app.render()?;
+        RELOAD_CONFIG | RELOAD_CONFIG_ALIAS => {
+            Ok(Some(AppRes::ReloadConfig))
+        },
+        SUSPEND => {
+            app.suspend()?;
             Ok(None)
         },
-        NEXT_PAGE => {
-            app.switch_next_page();
-            app.render()?;
+        UPVOTE => {
+            app.vote(Vote::Up)?;
+            Ok(None)
+        },
+        DOWNVOTE => {
+            app.vote(Vote::Down)?;
+            Ok(None)
+        },
+        UNVOTE => {
+            app.vote(Vote::None)?;
+            Ok(None)
+        },
+        SAVE => {
+            app.save(true)?;
+            Ok(None)
+        },
+        UNSAVE => {
+            app.save(false)?;
+            Ok(None)
+        },
+        HIDE => {
+            app.hide()?;
+            Ok(None)
+        },
+        UNHIDE => {
+            app.unhide()?;
+            Ok(None)
+        },
+        REPLY => {
+            app.reply()?;
             Ok(None)
         },
-        PREV_PAGE => {
-            app.switch_prev_page();
+        SUBMIT => {
+            app.submit()?;
+            Ok(None)
+        },
+        EDIT => {
+            app.edit()?;
+            Ok(None)
+        },
+        DELETE => {
+            app.delete()?;
+            Ok(None)
+        },
+        SAVED => {
+            app.open_saved()?;
+            Ok(None)
+        },
+        LOAD_MORE => {
+            let page = app.model().foc_page();
+            app.fetch_more(page)?;
+            Ok(None)
+        },
+        INBOX => {
+            let tab = args.get(1)
+                .map(|name| InboxTab::from_name(name).ok_or_else(|| anyhow!("{}: no tab {}, expected one of: {}", INBOX, name,
+                    InboxTab::TABS.iter().map(|tab| tab.name()).collect::<Vec<_>>().join(", "))))
+                .transpose()?;
+            app.open_inbox(tab)?;
+            Ok(None)
+        },
+        MARK_READ => {
+            app.mark_read(true)?;
+            Ok(None)
+        },
+        MARK_UNREAD => {
+            app.mark_read(false)?;
+            Ok(None)
+        },
+        COMPOSE => {
+            let to = args.get(1).ok_or_else(|| anyhow!("{}: missing user", COMPOSE))?;
+            app.compose_message(to)?;
+            Ok(None)
+        },
+        MESSAGES => {
+            // Level is optional, anything after it is searched for.
+            let (level, search) = match args.get(1).and_then(|arg| arg.parse::<LevelFilter>().ok()) {
+                Some(level) => (level, &args[2..]),
+                None => (LevelFilter::Trace, &args[1..])
+            };
+            let search = search.join(" ");
+            app.add_text_page("messages", &messages_text(level, Some(search.as_str()).filter(|search| !search.is_empty())))?;
             app.render()?;
             Ok(None)
-        }
+        },
         _ => { 
             Ok(None)
         }
@@ -8,14 +8,20 @@ use libnotcurses_sys::{
 }; 
 use log::{ error, warn };
 use nix::poll::{ poll, PollFd, PollFlags };
-use std::sync::{ Arc, Mutex };
+use std::{ os::unix::io::RawFd, sync::{ Arc, Mutex }, time::Instant };
 
 use crate::{ 
+    events::{
+        bus::EventQueue,
+        dispatcher::{ dispatch, Flow },
+        event::{ Event, TimerId },
+        signals::{ signal_fd, take_signal }
+    },
     tools::log_err_desc,
-    tui::{ App, AppRes, cmd_plt_val_input }
+    tui::{ App, AppRes, Mode, palette_key }
 };
 use super::{ 
-    command_to_event::exec_cmd,
+    macros::Macros,
     util::key_bindings::{ 
         Key,
         KeyBindingsTrie,
@@ -23,17 +29,53 @@ use super::{
     }
 };
 
+// Register awaited after q or @.
+enum PendingReg {
+    Record,
+    Replay
+}
+
+// Macros replaying macros are replayed up to this depth.
+const MAX_MACRO_DEPTH: u32 = 8;
+
+// -----------------------------------------------------------------------------------------------------------
+// State of input handling carried across inputs.
+// -----------------------------------------------------------------------------------------------------------
+pub struct InputState {
+    // Count typed before a key-binding or macro replay.
+    pub count: Option<u32>,
+
+    pub macros: Macros,
+    pending_reg: Option<PendingReg>,
+    replay_depth: u32,
+
+    // Timer dropping pending keys if the rest of a key-binding is not typed in time.
+    key_timer: Option<TimerId>
+}
+
+impl InputState {
+    pub fn new(macros: Macros) -> InputState {
+        InputState {
+            count: None,
+            macros,
+            pending_reg: None,
+            replay_depth: 0,
+            key_timer: None
+        }
+    }
+}
+
 // -----------------------------------------------------------------------------------------------------------
 // * Listen for user input by polling.
-// * Return event.
+// * Poll alongside for events queued by background jobs, config file changes and quit signals, dispatching
+//   them as events.
+// * Poll times out when the next timer is due, expired timers are dispatched as events too.
 // -----------------------------------------------------------------------------------------------------------
-pub fn listen(nc: Arc<Mutex<&mut Nc>>, kbt: KeyBindingsTrie, app: &mut App) -> Result<()> {
-    let mut buffer: KeyCombination = KeyCombination::new();
+pub fn listen(nc: Arc<Mutex<&mut Nc>>, mut kbt: KeyBindingsTrie, macros: Macros, events: EventQueue, app: &mut App)
+        -> Result<()> {
+    let mut state = InputState::new(macros);
     let mut input_details = NcInput::new_empty();
 
-    // Command mode - Enter command in palette.
-    let mut cmd_mode: bool = false;
-
     let mut nc_lock = nc.lock().unwrap();
     let input_fd = PollFd::new(
         unsafe { notcurses_inputready_fd(*nc_lock as &mut Nc as *mut Nc) },
@@ -41,18 +83,55 @@ pub fn listen(nc: Arc<Mutex<&mut Nc>>, kbt: KeyBindingsTrie, app: &mut App) -> R
     drop(nc_lock);
 
     loop {
-        if let Ok(_) = poll(&mut [input_fd], -1) {
+        let mut fds = vec![input_fd, PollFd::new(events.fd(), PollFlags::POLLIN)];
+        let mut push_fd = |fd: RawFd| { fds.push(PollFd::new(fd, PollFlags::POLLIN)); fds.len() - 1 };
+        let signal_i = signal_fd().map(&mut push_fd);
+        // Config watcher is replaced on reload, its fd is taken anew each time.
+        let watcher_i = app.config_watcher_fd().map(&mut push_fd);
+        // Wake for the next timer, rounding up so it is due on waking.
+        let timeout = app.timers.timeout(Instant::now())
+            .map_or(-1, |timeout| timeout.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32);
+        if poll(&mut fds, timeout).is_err() { continue; }
+        let readable = |i: Option<usize>| i.and_then(|i| fds[i].revents()).map_or(false, |revents| revents.contains(PollFlags::POLLIN));
+
+        let mut pending = Vec::new();
+        if readable(signal_i) {
+            if let Some(signal) = take_signal() {
+                warn!("Received {}, quitting.", signal);
+                pending.push(Event::Quit);
+            }
+        }
+        if readable(watcher_i) && app.config_changed() {
+            pending.push(Event::ReloadConfig);
+        }
+        if readable(Some(1)) {
+            pending.extend(events.take());
+        }
+        for timer in app.timers.expire(Instant::now()) {
+            if state.key_timer == Some(timer) {
+                // Input state is the listener's, drop keys typed here.
+                state.key_timer = None;
+                app.model_mut().clear_pending_keys();
+                state.count = None;
+            } else {
+                pending.push(Event::Timer(timer));
+            }
+        }
+        for event in pending {
+            if dispatch(app, &mut kbt, event) == Flow::Quit { return Ok(()); }
+        }
+
+        if readable(Some(0)) {
             nc_lock = nc.lock().unwrap();
             let recorded_input = nc_lock.get_nblock(Some(&mut input_details))?;
             drop(nc_lock);
 
             if !handle_input(
                     app,
-                    &mut buffer,
-                    &mut cmd_mode,
+                    &mut state,
                     &recorded_input,
                     &mut input_details,
-                    &kbt
+                    &mut kbt
                 )? {
                 break
             };
@@ -65,44 +144,85 @@ pub fn listen(nc: Arc<Mutex<&mut Nc>>, kbt: KeyBindingsTrie, app: &mut App) -> R
 // Separately handle input for better testability.
 pub fn handle_input(
     app: &mut App,
-    buffer: &mut KeyCombination,
-    cmd_mode: &mut bool,
+    state: &mut InputState,
     recorded_input: &NcReceived,
     input_details: &mut NcInput,
-    kbt: &KeyBindingsTrie) -> Result<bool> { // true for continue, false for break
+    kbt: &mut KeyBindingsTrie) -> Result<bool> { // true for continue, false for break
+
+    // Nc reports resizes as input.
+    if let NcReceived::Event(NcKey::Resize) = recorded_input {
+        return Ok(dispatch(app, kbt, Event::Resize) == Flow::Continue);
+    }
+
+    // -----------------------------------------------------------------------------------------------
+    // Record input, q outside cmd mode stops recording unless it names a register after @.
+    // Registers after @ are recorded too, so replaying the macro leaves no register pending.
+    // -----------------------------------------------------------------------------------------------
+    if state.macros.is_recording() && state.replay_depth == 0 {
+        if state.pending_reg.is_none() && app.model().mode() == Mode::Normal && app.model().pending_keys().is_empty()
+                && *recorded_input == NcReceived::Char('q') {
+            state.macros.stop_recording();
+            return Ok(true);
+        }
+        state.macros.record(input_details);
+    }
+
+    // -----------------------------------------------------------------------------------------------
+    // Register after q / @
+    // -----------------------------------------------------------------------------------------------
+    if let Some(pending_reg) = state.pending_reg.take() {
+        if let NcReceived::Char(reg) = recorded_input {
+            match pending_reg {
+                PendingReg::Record => {
+                    log_err_desc!(state.macros.start_recording(*reg), "Unable to record macro");
+                },
+                PendingReg::Replay => {
+                    return replay_macro(app, state, *reg, kbt);
+                }
+            }
+        }
+        state.count = None;
+        return Ok(true);
+    }
     
+    // -----------------------------------------------------------------------------------------------
+    // Confirm mode - y confirms, n or escape cancels, other inputs are dropped.
+    // -----------------------------------------------------------------------------------------------
+    if app.model().mode() == Mode::Confirm {
+        let confirmed = match recorded_input {
+            NcReceived::Char('y') | NcReceived::Char('Y') => true,
+            NcReceived::Char('n') | NcReceived::Char('N') | NcReceived::Event(NcKey::Esc) => false,
+            _ => { return Ok(true); }
+        };
+        log_err_desc!(app.answer_confirm(confirmed), "Unable to act on confirmation");
+        return Ok(true);
+    }
+
     // -----------------------------------------------------------------------------------------------
     // Cmd mode - true
     // -----------------------------------------------------------------------------------------------
-    if *cmd_mode {
+    if app.model().mode() == Mode::Cmd {
         match recorded_input {
             // Execute command.
             NcReceived::Event(NcKey::Enter) => {
-                *cmd_mode = false;
-                match app.exec_cmd() {
-                    Ok(Some(AppRes::AppQuit)) => { return Ok(false); },
-                    Err(e) => { error!("Unable to execute command {}", e); },
-                    _ => {  }
-                };
+                match app.take_cmd() {
+                    Ok(cmd) => { return Ok(dispatch(app, kbt, Event::cmd(&cmd)) == Flow::Continue); },
+                    Err(e) => { error!("Unable to take command: {}", e); }
+                }
                 return Ok(true);
             },
 
             // Escape command mode.
             NcReceived::Event(NcKey::Esc) => {
-                *cmd_mode = false;
                 log_err_desc!(app.exit_cmd(), "Unable to exit command palette");
                 return Ok(true);
             },
 
             _ => {
-                // Validate if input recieved is compatible.
-                if cmd_plt_val_input(&recorded_input) { 
-                    match app.input_cmd_plt(input_details.clone()) {
-                        Ok(AppRes::CmdModeCont) => {
-                            return Ok(true);
-                        },
-                        Ok(AppRes::CmdModeQuit) => {
-                            *cmd_mode = false;
+                // Only inputs editing the palette are taken.
+                if let Some(key) = palette_key(recorded_input, input_details) {
+                    match app.input_cmd_plt(key) {
+                        Ok(AppRes::CmdModeCont) | Ok(AppRes::CmdModeQuit) => {
                             return Ok(true);
                         },
                         Err(e) => {
@@ -121,33 +241,84 @@ pub fn handle_input(
     // -----------------------------------------------------------------------------------------------
     else {
         if let NcReceived::Char(':') = recorded_input {
-            *cmd_mode = true;
             log_err_desc!(app.enter_cmd(), "Unable to enter command palette");
-            buffer.clear();
+            state.count = None;
+            return Ok(true);
+        } else if app.model().pending_keys().is_empty() && is_count_or_macro_key(state, recorded_input) {
+            match recorded_input {
+                NcReceived::Char('q') => { state.pending_reg = Some(PendingReg::Record); },
+                NcReceived::Char('@') => { state.pending_reg = Some(PendingReg::Replay); },
+                NcReceived::Char(c) => {
+                    let digit = c.to_digit(10).unwrap_or(0);
+                    state.count = Some(state.count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                },
+                _ => {  }
+            }
             return Ok(true);
         } else {
-            if let Some(mut key) = gen_key(&recorded_input, &input_details) {
-                buffer.append(&mut key);
-                if let None = kbt.get_node(buffer as &KeyCombination) {
-                    buffer.clear();
-                }
-                else {
-                    if let Some(cmd) = kbt.get(buffer as &KeyCombination) {
-                        log_err_desc!(exec_cmd(app, cmd), "");
-
-                        // If AppQuit, leave.
-                        if cmd.eq("app_quit") {
-                            return Ok(false);
-                        } 
-                        buffer.clear();
-                    }
+            if let Some(key) = gen_key(&recorded_input, &input_details) {
+                match app.model_mut().press(kbt, key) {
+                    Some(cmd) => {
+                        for _ in 0..state.count.take().unwrap_or(1) {
+                            if dispatch(app, kbt, Event::cmd(&cmd)) == Flow::Quit { return Ok(false); }
+                        }
+                    },
+                    // Keys typed are no key-binding's prefix.
+                    None if app.model().pending_keys().is_empty() => { state.count = None; },
+                    None => {  }
                 }
+                restart_key_timer(app, state);
             }
         }
     }
     Ok(true)
 }
 
+// Time out partly typed key-binding anew, from the last key typed.
+fn restart_key_timer(app: &mut App, state: &mut InputState) {
+    if let Some(timer) = state.key_timer.take() {
+        app.timers.cancel(timer);
+    }
+    if let (false, Some(key_timeout)) = (app.model().pending_keys().is_empty(), app.key_timeout()) {
+        state.key_timer = Some(app.timers.once(key_timeout));
+    }
+}
+
+// Whether input starts / continues a count or starts macro recording / replay.
+fn is_count_or_macro_key(state: &InputState, recorded_input: &NcReceived) -> bool {
+    match recorded_input {
+        NcReceived::Char('q') => !state.macros.is_recording(),
+        NcReceived::Char('@') => true,
+        NcReceived::Char('1'..='9') => true,
+        NcReceived::Char('0') => state.count.is_some(),
+        _ => false
+    }
+}
+
+// Replay macro in register, count times.
+fn replay_macro(app: &mut App, state: &mut InputState, reg: char, kbt: &mut KeyBindingsTrie) -> Result<bool> {
+    let count = state.count.take().unwrap_or(1);
+    if state.replay_depth >= MAX_MACRO_DEPTH {
+        warn!("Macro replay depth exceeds {}, not replaying {}", MAX_MACRO_DEPTH, reg);
+        return Ok(true);
+    }
+    let inputs = match state.macros.replay(reg) {
+        Ok(inputs) => inputs,
+        Err(e) => { error!("Unable to replay macro: {}", e); return Ok(true); }
+    };
+
+    state.replay_depth += 1;
+    let mut res = Ok(true);
+    'replay: for _ in 0..count {
+        for mut input in inputs.iter().cloned() {
+            res = handle_input(app, state, &NcReceived::from(input), &mut input, kbt);
+            if !matches!(res, Ok(true)) { break 'replay; }
+        }
+    }
+    state.replay_depth -= 1;
+    res
+}
+
 // Generate KeyCombination for NcReceived & NcInput.
 fn gen_key(ncr: &NcReceived, id: &NcInput) -> Option<KeyCombination> {
     if id.evtype == 3 { return None; } // Ignore Kitty release events.
@@ -185,6 +356,7 @@ fn gen_key(ncr: &NcReceived, id: &NcInput) -> Option<KeyCombination> {
                     'x' => {  key_comb_vec.push(Key::KeyX); }, 
                     'y' => {  key_comb_vec.push(Key::KeyY); }, 
                     'z' => {  key_comb_vec.push(Key::KeyZ); }, 
+                    ' ' => {  key_comb_vec.push(Key::KeySpace); }, 
                     _ => { warn!{"Found no key matching char: {}", ch}; return None; }  
                 }
             }
@@ -228,3 +400,47 @@ fn gen_key(ncr: &NcReceived, id: &NcInput) -> Option<KeyCombination> {
         _ => { return None; }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use libnotcurses_sys::NcReceived;
+
+    use crate::{ jobs::Config, tui::{ App, TuiPrefs } };
+    use super::{ handle_input, InputState };
+    use super::super::{
+        macros::Macros,
+        notation::{ inputs_to_notation, notation_to_inputs },
+        util::key_bindings::{ create_key_bindings_trie, KeyBindingsTrie }
+    };
+
+    fn run(app: &mut App, state: &mut InputState, kbt: &mut KeyBindingsTrie, script: &str) {
+        for mut input in notation_to_inputs(script).unwrap() {
+            assert!(handle_input(app, state, &NcReceived::from(input), &mut input, kbt).unwrap());
+        }
+    }
+
+    // Test if a macro replaying another one records the register after @, leaving none pending once replayed.
+    #[test]
+    fn test_record_replay() {
+        let config = Config::default();
+        let mut app = App::headless(TuiPrefs::gen_tui_prefs(&config.tui).unwrap(), 80, 24);
+        app.dummy_render().unwrap();
+        let mut kbt = create_key_bindings_trie(&config.key_bindings, &config.leader).unwrap();
+        let mut state = InputState::new(Macros::new());
+        let scrolled = |app: &App| app.model().foc_page_ref().unwrap().scrolled();
+
+        run(&mut app, &mut state, &mut kbt, "qbjq");
+        assert_eq!(scrolled(&app), 2);
+        run(&mut app, &mut state, &mut kbt, "qa@bq");
+        assert_eq!(scrolled(&app), 4);
+        assert!(!state.macros.is_recording());
+
+        run(&mut app, &mut state, &mut kbt, "@a");
+        assert_eq!(scrolled(&app), 6);
+        assert!(state.pending_reg.is_none());
+        // Next key is a key-binding again, not a register.
+        run(&mut app, &mut state, &mut kbt, "j");
+        assert_eq!(scrolled(&app), 8);
+        assert_eq!(inputs_to_notation(&state.macros.replay('a').unwrap()), "@b");
+    }
+}
@@ -0,0 +1,154 @@
+use anyhow::{ bail, Result };
+use libnotcurses_sys::{ NcInput, NcInputType };
+use log::{ error, info, warn };
+use std::{
+    collections::{ BTreeMap, HashMap },
+    fs,
+    path::PathBuf
+};
+
+use crate::tools::log_err_desc;
+use super::notation::{ inputs_to_notation, notation_to_inputs };
+
+// -----------------------------------------------------------------------------------------------------------
+// Keyboard macros
+// * Inputs are recorded into a register ( a..z ) and replayed from it.
+// * If a file is given, registers are loaded from and saved to it in input notation.
+// -----------------------------------------------------------------------------------------------------------
+pub struct Macros {
+    registers: HashMap<char, Vec<NcInput>>,
+    recording: Option<(char, Vec<NcInput>)>,
+    last_replayed: Option<char>,
+    file: Option<PathBuf>
+}
+
+impl Macros {
+    pub fn new() -> Macros {
+        Macros {
+            registers: HashMap::new(),
+            recording: None,
+            last_replayed: None,
+            file: None
+        }
+    }
+
+    // Macros persisted in file, loading registers already saved.
+    pub fn with_file(file: PathBuf) -> Macros {
+        let mut macros = Macros::new();
+        if file.exists() {
+            log_err_desc!(macros.load(&file), "Unable to load macros");
+        }
+        macros.file = Some(file);
+        macros
+    }
+
+    fn load(&mut self, file: &PathBuf) -> Result<()> {
+        let saved: BTreeMap<String, String> = toml::from_str(&fs::read_to_string(file)?)?;
+        for (reg, notation) in saved.iter() {
+            match (reg.chars().next(), reg.chars().count()) {
+                (Some(reg), 1) if val_register(reg) => {
+                    self.registers.insert(reg, notation_to_inputs(notation)?);
+                },
+                _ => { warn!("Skipping invalid macro register {} in {:?}", reg, file); }
+            }
+        }
+        info!("Loaded macros from {:?}", file);
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(file) = &self.file {
+            let saved: BTreeMap<String, String> = self.registers.iter()
+                .map(|(reg, inputs)| (reg.to_string(), inputs_to_notation(inputs)))
+                .collect();
+            if let Some(dir) = file.parent() { fs::create_dir_all(dir)?; }
+            fs::write(file, toml::to_string(&saved)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn start_recording(&mut self, reg: char) -> Result<()> {
+        if !val_register(reg) {
+            bail!("Invalid macro register {}", reg);
+        }
+        info!("Recording macro in register {}", reg);
+        self.recording = Some((reg, Vec::new()));
+        Ok(())
+    }
+
+    // Stop recording, storing recorded inputs in register.
+    pub fn stop_recording(&mut self) {
+        if let Some((reg, inputs)) = self.recording.take() {
+            info!("Recorded macro in register {}", reg);
+            self.registers.insert(reg, inputs);
+            log_err_desc!(self.save(), "Unable to save macros");
+        }
+    }
+
+    // Record input, skipping key release events.
+    pub fn record(&mut self, input: &NcInput) {
+        if input.evtype == u32::from(NcInputType::Release) { return; }
+        if let Some((_, inputs)) = self.recording.as_mut() {
+            inputs.push(*input);
+        }
+    }
+
+    // Inputs in register, @ being the last register replayed.
+    pub fn replay(&mut self, reg: char) -> Result<Vec<NcInput>> {
+        let reg = if reg == '@' {
+            match self.last_replayed {
+                Some(reg) => reg,
+                None => bail!("No macro replayed yet")
+            }
+        } else { reg };
+        match self.registers.get(&reg) {
+            Some(inputs) => {
+                self.last_replayed = Some(reg);
+                Ok(inputs.clone())
+            },
+            None => bail!("No macro in register {}", reg)
+        }
+    }
+}
+
+fn val_register(reg: char) -> bool {
+    reg.is_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use libnotcurses_sys::NcInput;
+    use std::{ env, fs };
+
+    use super::Macros;
+
+    // * Test if recorded inputs are replayed from register, @ replaying the last one.
+    // * Test if registers persist in file.
+    #[test]
+    fn test_macros() {
+        let file = env::temp_dir().join(format!("ravana-test-macros-{}.toml", std::process::id()));
+        let _ = fs::remove_file(&file);
+
+        let mut macros = Macros::with_file(file.clone());
+        assert!(macros.start_recording('A').is_err());
+        macros.start_recording('a').unwrap();
+        assert!(macros.is_recording());
+        macros.record(&NcInput::new(':'));
+        macros.record(&NcInput::new('x'));
+        macros.stop_recording();
+        assert!(!macros.is_recording());
+
+        assert!(macros.replay('@').is_err());
+        assert!(macros.replay('b').is_err());
+        assert_eq!(macros.replay('a').unwrap(), vec![NcInput::new(':'), NcInput::new('x')]);
+        assert_eq!(macros.replay('@').unwrap(), vec![NcInput::new(':'), NcInput::new('x')]);
+
+        let mut macros = Macros::with_file(file.clone());
+        assert_eq!(macros.replay('a').unwrap(), vec![NcInput::new(':'), NcInput::new('x')]);
+        fs::remove_file(&file).unwrap();
+    }
+}
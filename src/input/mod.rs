@@ -1,8 +1,13 @@
 mod listener;
+mod macros;
+mod notation;
+mod script_driver;
 pub(super) mod util;
 pub mod command_to_event;
 
-pub use listener::{ listen, handle_input };
+pub use listener::{ listen, handle_input, InputState };
+pub use macros::Macros;
+pub use script_driver::ScriptDriver;
 pub use util::key_bindings::KeyCombination;
-pub use util::key_bindings::create_key_bindings_trie;
+pub use util::key_bindings::{ create_key_bindings_trie, val_cmd, val_key_bindings, KeyBindingErr, KeyBindingsTrie };
 pub use util::key_bindings::DEFAULT_KEY_BINDINGS;
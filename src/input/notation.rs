@@ -0,0 +1,167 @@
+use anyhow::{ bail, Result };
+use libnotcurses_sys::{ NcInput, NcKey, NcKeyMod, NcReceived };
+
+// -----------------------------------------------------------------------------------------------------------
+// Input notation
+// * Vim-style text form of a sequence of inputs, e.g. ":switch_page 1<CR>jj<C-d>".
+// * Characters stand for themselves, <lt> for <. Keys and modifiers go inside <>, same as in key-bindings.
+// -----------------------------------------------------------------------------------------------------------
+
+// Keys written inside <> in input notation.
+const NAMED_KEYS: &[(&str, NcKey)] = &[
+    ("CR", NcKey::Enter),
+    ("Esc", NcKey::Esc),
+    ("Tab", NcKey::Tab),
+    ("BS", NcKey::Backspace),
+    ("Up", NcKey::Up),
+    ("Down", NcKey::Down),
+    ("Left", NcKey::Left),
+    ("Right", NcKey::Right),
+    ("F1", NcKey::F01),
+    ("F2", NcKey::F02),
+    ("F3", NcKey::F03),
+    ("F4", NcKey::F04),
+    ("F5", NcKey::F05),
+    ("F6", NcKey::F06),
+    ("F7", NcKey::F07),
+    ("F8", NcKey::F08),
+    ("F9", NcKey::F09),
+    ("F10", NcKey::F10),
+    ("F11", NcKey::F11),
+    ("F12", NcKey::F12),
+    ("Insert", NcKey::Ins),
+    ("Del", NcKey::Del),
+    ("Home", NcKey::Home),
+    ("End", NcKey::End),
+    ("PageUp", NcKey::PgUp),
+    ("PageDown", NcKey::PgDown),
+];
+
+// Convert inputs to input notation. Inputs not representable are skipped.
+pub fn inputs_to_notation(inputs: &[NcInput]) -> String {
+    let mut notation = String::new();
+    for input in inputs {
+        let name = match NcReceived::from(input) {
+            NcReceived::Event(key) => {
+                match NAMED_KEYS.iter().find(|(_, named_key)| *named_key == key) {
+                    Some((name, _)) => name.to_string(),
+                    None => { continue; }
+                }
+            },
+            NcReceived::Char('<') => "lt".to_string(),
+            NcReceived::Char(c) => c.to_string(),
+            NcReceived::NoInput => { continue; }
+        };
+
+        let mut mods = String::new();
+        if input.ctrl { mods.push_str("C-"); }
+        if input.alt { mods.push_str("A-"); }
+        if input.shift { mods.push_str("S-"); }
+
+        if mods.is_empty() && name.chars().count() == 1 { notation.push_str(&name); }
+        else { notation.push_str(&format!("<{}{}>", mods, name)); }
+    }
+    notation
+}
+
+// Convert input notation to inputs.
+pub fn notation_to_inputs(notation: &str) -> Result<Vec<NcInput>> {
+    let mut inputs: Vec<NcInput> = Vec::new();
+    let mut chars = notation.chars();
+    while let Some(c) = chars.next() {
+        if c != '<' {
+            inputs.push(NcInput::new(c));
+            continue;
+        }
+
+        let mut token = String::new();
+        loop {
+            match chars.next() {
+                Some('>') => { break; },
+                Some(c) => { token.push(c); },
+                None => { bail!("Invalid input notation {}, < not closed", notation); }
+            }
+        }
+
+        // Modifiers are separated by -, the last part being the key itself which can be - too.
+        let mut parts: Vec<&str> = token.split('-').collect();
+        let mut key = parts.pop().unwrap_or("");
+        if key.is_empty() && parts.last() == Some(&"") {
+            parts.pop();
+            key = "-";
+        }
+
+        let key = match key {
+            "Enter" | "Return" => "CR",
+            "lt" => "<",
+            "Space" => " ",
+            _ => key
+        };
+        let mut input = NcInput::new_empty();
+        if let (Some(c), 1) = (key.chars().next(), key.chars().count()) {
+            input = NcInput::new(c);
+        } else {
+            match NAMED_KEYS.iter().find(|(name, _)| *name == key) {
+                Some((_, named_key)) => { input.id = named_key.0; },
+                None => { bail!("Invalid input notation {}, unknown key {}", notation, key); }
+            }
+        }
+
+        for part in parts {
+            match part {
+                "C" => { input.ctrl = true; input.modifiers |= NcKeyMod::Ctrl.0; },
+                "A" | "M" => { input.alt = true; input.modifiers |= NcKeyMod::Alt.0; },
+                "S" => { input.shift = true; input.modifiers |= NcKeyMod::Shift.0; },
+                _ => { bail!("Invalid input notation {}, unknown modifier {}", notation, part); }
+            }
+        }
+        inputs.push(input);
+    }
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use libnotcurses_sys::{ NcKey, NcReceived };
+
+    use super::{ inputs_to_notation, notation_to_inputs };
+
+    // * Test if notation is parsed to inputs with right keys and modifiers.
+    // * Test if inputs convert back to the same notation.
+    #[test]
+    fn test_notation() {
+        let inputs = notation_to_inputs(":switch_page 1<CR>jJ<C-d><lt><Esc><A-F5>").unwrap();
+        let received: Vec<NcReceived> = inputs.iter().map(NcReceived::from).collect();
+        assert_eq!(received, vec![
+            NcReceived::Char(':'),
+            NcReceived::Char('s'),
+            NcReceived::Char('w'),
+            NcReceived::Char('i'),
+            NcReceived::Char('t'),
+            NcReceived::Char('c'),
+            NcReceived::Char('h'),
+            NcReceived::Char('_'),
+            NcReceived::Char('p'),
+            NcReceived::Char('a'),
+            NcReceived::Char('g'),
+            NcReceived::Char('e'),
+            NcReceived::Char(' '),
+            NcReceived::Char('1'),
+            NcReceived::Event(NcKey::Enter),
+            NcReceived::Char('j'),
+            NcReceived::Char('J'),
+            NcReceived::Char('d'),
+            NcReceived::Char('<'),
+            NcReceived::Event(NcKey::Esc),
+            NcReceived::Event(NcKey::F05),
+        ]);
+        assert!(inputs[17].ctrl);
+        assert!(inputs[20].alt);
+
+        assert_eq!(inputs_to_notation(&inputs), ":switch_page 1<CR>jJ<C-d><lt><Esc><A-F5>");
+        assert_eq!(notation_to_inputs("<C-->").unwrap()[0].id, '-' as u32);
+        assert!(notation_to_inputs("<CR").is_err());
+        assert!(notation_to_inputs("<X-a>").is_err());
+        assert!(notation_to_inputs("<Foo>").is_err());
+    }
+}
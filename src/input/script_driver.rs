@@ -0,0 +1,104 @@
+use anyhow::Result;
+use libnotcurses_sys::NcReceived;
+
+use crate::{
+    jobs::Config,
+    tui::{ App, Mode, TuiPrefs }
+};
+use super::{
+    listener::{ handle_input, InputState },
+    macros::Macros,
+    notation::notation_to_inputs,
+    util::key_bindings::{ create_key_bindings_trie, KeyBindingsTrie }
+};
+
+// -----------------------------------------------------------------------------------------------------------
+// Drives a headless App with inputs scripted in input notation, e.g. ":switch_page 1<CR>jjj<C-d>".
+// * Inputs go through handle_input, as if typed at the terminal.
+// * Assertions on the App state and the screen rendered tell the script run so far on failing.
+// -----------------------------------------------------------------------------------------------------------
+pub struct ScriptDriver {
+    pub app: App<'static>,
+    state: InputState,
+    kbt: KeyBindingsTrie,
+
+    // Inputs fed so far, in input notation.
+    script: String,
+    quit: bool
+}
+
+impl ScriptDriver {
+    // Driver of a headless App dim_x x dim_y, with TUI prefs and key-bindings from config.
+    pub fn new(config: &Config, dim_x: u32, dim_y: u32) -> Result<ScriptDriver> {
+        Ok(ScriptDriver {
+            app: App::headless(TuiPrefs::gen_tui_prefs(&config.tui)?, dim_x, dim_y),
+            state: InputState::new(Macros::new()),
+            kbt: create_key_bindings_trie(&config.key_bindings, &config.leader)?,
+            script: String::new(),
+            quit: false
+        })
+    }
+
+    // Feed inputs in script, returning false once App quits. Inputs after quitting are dropped.
+    pub fn run(&mut self, script: &str) -> Result<bool> {
+        for mut input in notation_to_inputs(script)? {
+            if self.quit { break; }
+            if !handle_input(&mut self.app, &mut self.state, &NcReceived::from(input), &mut input, &mut self.kbt)? {
+                self.quit = true;
+            }
+        }
+        self.script.push_str(script);
+        // Not all inputs render, e.g. keys pending.
+        self.app.render()?;
+        Ok(!self.quit)
+    }
+
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn foc_page(&self) -> usize {
+        self.app.model().foc_page()
+    }
+
+    // Lines focused page is scrolled down, None without pages.
+    pub fn scrolled(&self) -> Option<u32> {
+        self.app.model().foc_page_ref().map(|page| page.scrolled())
+    }
+
+    // Text typed in command palette, None outside command mode.
+    pub fn palette(&self) -> Option<String> {
+        Some(self.app.model().palette().text()).filter(|_| self.app.model().mode() == Mode::Cmd)
+    }
+
+    // Screen rendered last, as text.
+    pub fn screen(&self) -> String {
+        self.app.buffer().map(|buffer| buffer.text()).unwrap_or_default()
+    }
+
+    #[track_caller]
+    pub fn assert_foc_page(&self, foc_page: usize) -> &Self {
+        assert_eq!(self.foc_page(), foc_page, "Focused page after {}", self.script);
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_scrolled(&self, scrolled: u32) -> &Self {
+        assert_eq!(self.scrolled(), Some(scrolled), "Scrolled after {}", self.script);
+        self
+    }
+
+    // Assert palette holds text in command mode, None for normal mode.
+    #[track_caller]
+    pub fn assert_palette(&self, text: Option<&str>) -> &Self {
+        assert_eq!(self.palette().as_deref(), text, "Command palette after {}", self.script);
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_screen_contains(&self, text: &str) -> &Self {
+        let screen = self.screen();
+        assert!(screen.contains(text), "No {:?} on screen after {}:\n{}", text, self.script, screen);
+        self
+    }
+}
@@ -2,9 +2,13 @@ pub mod key_bindings {
     use anyhow::{ bail, Result };
     #[cfg(test)]
     use enum_iterator::IntoEnumIterator; // Required in a unit test.
+    use log::{ info, warn };
     use phf::{ phf_map, Map };
     use sequence_trie::SequenceTrie;
     use std::collections::HashMap;
+    use thiserror::Error;
+
+    use crate::{ def::{ app::DEFAULT_LEADER, commands::COMMANDS }, jobs::KeyBindingsDes };
 
     // Supported keys.
     #[cfg_attr(test, derive(IntoEnumIterator))]
@@ -80,6 +84,7 @@ pub mod key_bindings {
         "h" => Key::KeyH,
         "i" => Key::KeyI,
         "j" => Key::KeyJ,
+        "k" => Key::KeyK,
         "l" => Key::KeyL,
         "m" => Key::KeyM,
         "n" => Key::KeyN,
@@ -199,30 +204,252 @@ pub mod key_bindings {
         }
     }
 
-    // Default map for key-bindings ( field-name -> key-binding )
-    pub const DEFAULT_KEY_BINDINGS: Map<&'static str, &'static str> = phf_map!{
-        "app_quit" => "zz",
+    // Default map for key-bindings ( command line -> key-bindings )
+    pub const DEFAULT_KEY_BINDINGS: Map<&'static str, &'static [&'static str]> = phf_map!{
+        "app_quit" => &["zz"],
+        "scroll_down" => &["j", "<Down>"],
+        "scroll_up" => &["k", "<Up>"],
+        "select_next" => &["<S-j>"],
+        "select_prev" => &["<S-k>"],
+        "next_page" => &["gt"],
+        "prev_page" => &["g<S-t>"],
+        "suspend" => &["<C-z>"],
+        "upvote" => &["u"],
+        "downvote" => &["d"],
+        "unvote" => &["<S-u>"],
+        "save" => &["s"],
+        "unsave" => &["<S-s>"],
+        "hide" => &["h"],
+        "unhide" => &["<S-h>"],
+        "load_more" => &["<S-m>"],
+        "reply" => &["r"],
+        "submit" => &["c"],
+        "edit" => &["e"],
+        "delete" => &["<S-d>"],
+        "inbox" => &["i"],
+        "mark_read" => &["m"],
+        "mark_unread" => &["<S-r>"],
     };
 
     pub type KeyCombination = Vec<Key>;
 
-    pub type KeyBindingsTrie = SequenceTrie<Key, String>;
+    // Token in key-combination string replaced by leader key combination.
+    const LEADER: &str = "leader";
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Whether key combination starts with a key taken before key-bindings when typed first, to start a count
+    //   or macro recording / replay.
+    // * @ and digits are reserved as well but have no Key, failing to parse instead.
+    // -------------------------------------------------------------------------------------------------------
+    fn starts_with_reserved(key_comb: &KeyCombination) -> bool {
+        key_comb.first() == Some(&Key::KeyQ)
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // Trie of key combinations to command lines.
+    // * Keeps leader for parsing key-bindings mapped at runtime.
+    // -------------------------------------------------------------------------------------------------------
+    pub struct KeyBindingsTrie {
+        leader: KeyCombination,
+        trie: SequenceTrie<Key, String>
+    }
 
-    pub fn create_key_bindings_trie(kb: &HashMap<String, String>) -> Result<KeyBindingsTrie> {
-        let mut kb_trie: KeyBindingsTrie = KeyBindingsTrie::new();
-        for (&key, &def_val) in DEFAULT_KEY_BINDINGS.entries() {
-            if let Some(val) = kb.get(key) {
-                kb_trie.insert_owned(parse_to_key_combination(val)?, key.to_string());
-            }  
-            else {
-                kb_trie.insert_owned(parse_to_key_combination(def_val)?, key.to_string());
+    impl KeyBindingsTrie {
+        pub fn new(leader: KeyCombination) -> KeyBindingsTrie {
+            KeyBindingsTrie {
+                leader,
+                trie: SequenceTrie::new()
+            }
+        }
+
+        // Command line bound to key combination.
+        pub fn get(&self, key_comb: &KeyCombination) -> Option<&String> {
+            self.trie.get(key_comb)
+        }
+
+        // Whether key combination is bound or is a prefix of a bound key combination.
+        pub fn is_prefix(&self, key_comb: &KeyCombination) -> bool {
+            self.trie.get_node(key_comb).is_some()
+        }
+
+        // Bind key-binding string to command line, replacing the command line previously bound to it.
+        pub fn map(&mut self, key_comb_str: &str, cmd: &str) -> Result<()> {
+            let key_comb = parse_to_key_combination(key_comb_str, &self.leader)?;
+            if starts_with_reserved(&key_comb) {
+                bail!("{} starts with a key reserved for counts and macros", key_comb_str);
+            }
+            if let Some(prev_cmd) = self.trie.get(&key_comb) {
+                info!("Replacing key-binding {} -> {} with {}", key_comb_str, prev_cmd, cmd);
+            }
+            self.trie.insert_owned(key_comb, cmd.to_string());
+            Ok(())
+        }
+
+        // Parse key-binding string, e.g. <leader>x, with this trie's leader.
+        pub fn parse(&self, key_comb_str: &str) -> Result<KeyCombination> {
+            parse_to_key_combination(key_comb_str, &self.leader)
+        }
+
+        // Unbind key-binding string.
+        pub fn unmap(&mut self, key_comb_str: &str) -> Result<()> {
+            let key_comb = parse_to_key_combination(key_comb_str, &self.leader)?;
+            if self.trie.get(&key_comb).is_none() {
+                bail!("No key-binding for {}", key_comb_str);
+            }
+            self.trie.remove(&key_comb);
+            Ok(())
+        }
+    }
+
+    // Problems found in key-bindings of config.
+    #[derive(Debug, Error, PartialEq, Eq)]
+    pub enum KeyBindingErr {
+        #[error("cannot parse \"{leader}\": {reason}")]
+        InvalidLeader { leader: String, reason: String },
+        #[error("cannot parse \"{key_str}\": {reason}")]
+        Unparsable { cmd: String, key_str: String, reason: String },
+        #[error("unknown command \"{name}\"")]
+        UnknownCommand { cmd: String, name: String },
+        #[error("\"{key_str}\" is also bound to \"{other_cmd}\"")]
+        Duplicate { cmd: String, key_str: String, other_cmd: String },
+        #[error("\"{key_str}\" is unreachable, its prefix \"{prefix_key_str}\" is bound to \"{prefix_cmd}\"")]
+        Shadowed { cmd: String, key_str: String, prefix_cmd: String, prefix_key_str: String },
+        #[error("\"{key_str}\" is unreachable, q, @ and digits typed first start counts and macros")]
+        Reserved { cmd: String, key_str: String }
+    }
+
+    impl KeyBindingErr {
+        // Key path of the problem in config.
+        pub fn key_path(&self) -> Vec<String> {
+            match self {
+                KeyBindingErr::InvalidLeader { .. } => vec!["leader".to_string()],
+                KeyBindingErr::Unparsable { cmd, .. }
+                    | KeyBindingErr::UnknownCommand { cmd, .. }
+                    | KeyBindingErr::Duplicate { cmd, .. }
+                    | KeyBindingErr::Shadowed { cmd, .. }
+                    | KeyBindingErr::Reserved { cmd, .. } => vec!["key-bindings".to_string(), cmd.clone()]
+            }
+        }
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Key-bindings in config replace the default key-bindings of the same command line.
+    // * Command lines with no default key-binding are bound as well.
+    // * Returns ( command line, key-binding string ) pairs sorted by command line.
+    // -------------------------------------------------------------------------------------------------------
+    fn effective_key_bindings(kb: &HashMap<String, KeyBindingsDes>) -> Vec<(String, String)> {
+        let mut key_bindings: Vec<(String, String)> = Vec::new();
+        for (&cmd, &def_key_strs) in DEFAULT_KEY_BINDINGS.entries() {
+            if kb.contains_key(cmd) { continue; }
+            for key_str in def_key_strs {
+                key_bindings.push((cmd.to_string(), key_str.to_string()));
+            }
+        }
+        for (cmd, key_strs) in kb.iter() {
+            for key_str in key_strs.key_strs() {
+                key_bindings.push((cmd.to_string(), key_str.to_string()));
+            }
+        }
+        key_bindings.sort();
+        key_bindings
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Build trie of key-bindings in effect.
+    // * Unparsable key-bindings and ones starting with a reserved key are skipped, and an invalid leader falls
+    //   back to DEFAULT_LEADER, all being reported by val_key_bindings.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn create_key_bindings_trie(kb: &HashMap<String, KeyBindingsDes>, leader: &str) -> Result<KeyBindingsTrie> {
+        let leader = match parse_to_key_combination(leader, &KeyCombination::new()) {
+            Ok(leader) => leader,
+            Err(e) => {
+                warn!("Using leader {} instead of {}: {}", DEFAULT_LEADER, leader, e);
+                parse_to_key_combination(DEFAULT_LEADER, &KeyCombination::new())?
+            }
+        };
+        let mut kb_trie: KeyBindingsTrie = KeyBindingsTrie::new(leader);
+        for (cmd, key_str) in effective_key_bindings(kb) {
+            if let Err(e) = kb_trie.map(&key_str, &cmd) {
+                warn!("Skipping key-binding {} -> {}: {}", key_str, cmd, e);
             }
         }
         Ok(kb_trie)
     }
 
-    // Parse key combination string to KeyCombination.
-    pub(super) fn parse_to_key_combination(key_comb_str: &str) -> Result<KeyCombination> {    
+    // -------------------------------------------------------------------------------------------------------
+    // * Validate key-bindings in effect.
+    // * Report unknown commands, unparsable key-binding strings, key-combinations bound to multiple command
+    //   lines and key-combinations made unreachable by a bound prefix or a reserved first key.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn val_key_bindings(kb: &HashMap<String, KeyBindingsDes>, leader: &str) -> Vec<KeyBindingErr> {
+        let mut errs: Vec<KeyBindingErr> = Vec::new();
+        let mut parsed: Vec<(String, String, KeyCombination)> = Vec::new();
+
+        let leader = match parse_to_key_combination(leader, &KeyCombination::new()) {
+            Ok(leader) => leader,
+            Err(e) => {
+                errs.push(KeyBindingErr::InvalidLeader { leader: leader.to_string(), reason: e.to_string() });
+                KeyCombination::new()
+            }
+        };
+
+        for (cmd, key_str) in effective_key_bindings(kb) {
+            if let Err(err) = val_cmd(&cmd) {
+                if !errs.contains(&err) { errs.push(err); }
+            }
+            match parse_to_key_combination(&key_str, &leader) {
+                Ok(key_comb) if starts_with_reserved(&key_comb) => { errs.push(KeyBindingErr::Reserved { cmd, key_str }); },
+                Ok(key_comb) => { parsed.push((cmd, key_str, key_comb)); },
+                Err(e) => { errs.push(KeyBindingErr::Unparsable { cmd, key_str, reason: e.to_string() }); }
+            }
+        }
+
+        for (i, (cmd, key_str, key_comb)) in parsed.iter().enumerate() {
+            for (other_cmd, other_key_str, other_key_comb) in parsed[..i].iter() {
+                if key_comb == other_key_comb {
+                    if cmd != other_cmd {
+                        errs.push(KeyBindingErr::Duplicate {
+                            cmd: cmd.clone(),
+                            key_str: key_str.clone(),
+                            other_cmd: other_cmd.clone()
+                        });
+                    }
+                }
+                else if key_comb.starts_with(other_key_comb) {
+                    errs.push(KeyBindingErr::Shadowed {
+                        cmd: cmd.clone(),
+                        key_str: key_str.clone(),
+                        prefix_cmd: other_cmd.clone(),
+                        prefix_key_str: other_key_str.clone()
+                    });
+                }
+                else if other_key_comb.starts_with(key_comb) {
+                    errs.push(KeyBindingErr::Shadowed {
+                        cmd: other_cmd.clone(),
+                        key_str: other_key_str.clone(),
+                        prefix_cmd: cmd.clone(),
+                        prefix_key_str: key_str.clone()
+                    });
+                }
+            }
+        }
+        errs
+    }
+
+    // Check command line bound is of a known command, e.g. for :map.
+    pub fn val_cmd(cmd: &str) -> Result<(), KeyBindingErr> {
+        let name = cmd.split(' ').next().unwrap_or("");
+        if !COMMANDS.contains(&name) {
+            return Err(KeyBindingErr::UnknownCommand { cmd: cmd.to_string(), name: name.to_string() });
+        }
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Parse key combination string to KeyCombination.
+    // * <leader> is replaced by leader key combination.
+    // -------------------------------------------------------------------------------------------------------
+    pub(super) fn parse_to_key_combination(key_comb_str: &str, leader: &KeyCombination) -> Result<KeyCombination> {    
         let mut key_comb: Vec<Key> = Vec::new();
         let find_key = |s: &str| -> Result<Key> {
                 if let Some(key) = STRING_TO_KEYS.get(s) { Ok(key.clone()) } 
@@ -231,6 +458,7 @@ pub mod key_bindings {
                 } 
         };
         let mut is_special_key: bool = false;
+        let mut special_key_start = 0;
         let mut special_key_index = 0;
         for (i, c) in key_comb_str.chars().enumerate() {
             match c {
@@ -238,7 +466,7 @@ pub mod key_bindings {
                     if is_special_key { 
                        bail!("Invalid key-binding format {}, < wrongly placed", key_comb_str)
                     }
-                    is_special_key = true; special_key_index = i+1; 
+                    is_special_key = true; special_key_start = i+1; special_key_index = i+1; 
                 },
                 '-' => {
                     if !is_special_key { 
@@ -251,7 +479,18 @@ pub mod key_bindings {
                     if !is_special_key { 
                         bail!("Invalid key-binding format {}, < wrongly placed", key_comb_str)
                     }
-                    key_comb.push(find_key(&key_comb_str[special_key_index..i])?);
+                    if key_comb_str[special_key_index..i].eq_ignore_ascii_case(LEADER) {
+                        if special_key_index != special_key_start {
+                            bail!("Invalid key-binding format {}, <{}> cannot be held with other keys",
+                                key_comb_str, LEADER)
+                        }
+                        if leader.is_empty() {
+                            bail!("Invalid key-binding format {}, no leader set", key_comb_str)
+                        }
+                        key_comb.extend(leader.iter().cloned());
+                    } else {
+                        key_comb.push(find_key(&key_comb_str[special_key_index..i])?);
+                    }
                     is_special_key = false; 
                 },
                 _ =>  { 
@@ -278,8 +517,9 @@ mod tests {
         path::Path
     };
 
-    use crate::jobs::Config;
-    use super::key_bindings::{ DEFAULT_KEY_BINDINGS, Key, key_to_code };
+    use crate::jobs::{ Config, KeyBindingsDes };
+    use super::key_bindings::{ create_key_bindings_trie, DEFAULT_KEY_BINDINGS, Key, key_to_code };
+    use super::key_bindings::{ KeyBindingErr, val_cmd, val_key_bindings };
     use super::key_bindings::{ KeyCombination, parse_to_key_combination };
 
     // * Test if all key-values pairs in DEFAULT_KEY_BINDINGS map and Config.toml match exactly
@@ -288,11 +528,11 @@ mod tests {
         let mut file = File::open(Path::new("docs/.ravana/Config.toml"))?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
-        let key_bindings_res: HashMap<String,String> = toml::from_str::<Config>(&*contents)?.key_bindings;  
+        let key_bindings_res: HashMap<String, KeyBindingsDes> = toml::from_str::<Config>(&*contents)?.key_bindings;  
 
         for res_key in key_bindings_res.keys() {
             if let Some(res_val) = key_bindings_res.get(res_key) {
-                if let Some(exp_val) = DEFAULT_KEY_BINDINGS.get(&res_key) { assert_eq!(exp_val, res_val); }
+                if let Some(exp_val) = DEFAULT_KEY_BINDINGS.get(&res_key) { assert_eq!(exp_val.to_vec(), res_val.key_strs()); }
                 else { 
                     return Err(anyhow!("Cannot find key-value in default key-binding map for {}", res_key));
                 }
@@ -301,7 +541,7 @@ mod tests {
 
         for exp_key in DEFAULT_KEY_BINDINGS.keys() {
             if let Some(exp_val) = DEFAULT_KEY_BINDINGS.get(exp_key) {
-                if let Some(res_val) = key_bindings_res.get(exp_key.to_owned()) { assert_eq!(exp_val, res_val); } 
+                if let Some(res_val) = key_bindings_res.get(exp_key.to_owned()) { assert_eq!(exp_val.to_vec(), res_val.key_strs()); } 
                 else { 
                     return Err(anyhow!("Cannot find key-value in default Config.toml for {}", exp_key));
                 }
@@ -310,6 +550,118 @@ mod tests {
         Ok(())
     }
 
+    // * Test if a command line can have multiple key-bindings, with or without arguments.
+    // * Test if config key-bindings replace defaults, and empty ones unbind them.
+    // * Test if command lines without default key-bindings are bound.
+    #[test]
+    fn test_create_key_bindings_trie() -> Result<()> {
+        let kb: HashMap<String, KeyBindingsDes> = HashMap::from([
+            ("app_quit".to_owned(), KeyBindingsDes::Multiple(vec!["<C-q>".to_owned(), "xx".to_owned()])),
+            ("switch_page 3".to_owned(), KeyBindingsDes::Single("<F3>".to_owned())),
+            ("scroll_down".to_owned(), KeyBindingsDes::Single("".to_owned())),
+            ("scroll_up".to_owned(), KeyBindingsDes::Multiple(vec![]))
+        ]);
+        let kbt = create_key_bindings_trie(&kb, "")?;
+        let no_leader = KeyCombination::new();
+
+        assert_eq!(kbt.get(&parse_to_key_combination("<C-q>", &no_leader)?), Some(&"app_quit".to_owned()));
+        assert_eq!(kbt.get(&parse_to_key_combination("xx", &no_leader)?), Some(&"app_quit".to_owned()));
+        assert_eq!(kbt.get(&parse_to_key_combination("zz", &no_leader)?), None);
+        assert_eq!(kbt.get(&parse_to_key_combination("<F3>", &no_leader)?), Some(&"switch_page 3".to_owned()));
+        assert_eq!(kbt.get(&parse_to_key_combination("j", &no_leader)?), None);
+        assert_eq!(kbt.get(&parse_to_key_combination("<Up>", &no_leader)?), None);
+        assert_eq!(kbt.get(&parse_to_key_combination("gt", &no_leader)?), Some(&"next_page".to_owned()));
+
+        // Leader expands to its key combination.
+        let kb: HashMap<String, KeyBindingsDes> = HashMap::from([
+            ("next_page".to_owned(), KeyBindingsDes::Single("<leader>n".to_owned()))
+        ]);
+        let mut kbt = create_key_bindings_trie(&kb, "<Space>")?;
+        assert_eq!(kbt.get(&vec![Key::KeySpace, Key::KeyN]), Some(&"next_page".to_owned()));
+
+        // Map and unmap at runtime.
+        kbt.map("<leader>p", "switch_page 2")?;
+        assert_eq!(kbt.get(&vec![Key::KeySpace, Key::KeyP]), Some(&"switch_page 2".to_owned()));
+        assert!(kbt.is_prefix(&vec![Key::KeySpace]));
+        kbt.unmap("<Space>p")?;
+        assert_eq!(kbt.get(&vec![Key::KeySpace, Key::KeyP]), None);
+        assert!(kbt.unmap("<Space>p").is_err());
+        assert!(kbt.map("qa", "switch_page 2").is_err());
+
+        // Unparsable key-bindings are skipped and an invalid leader falls back to the default one.
+        let kb: HashMap<String, KeyBindingsDes> = HashMap::from([
+            ("next_page".to_owned(), KeyBindingsDes::Single("<leader>n".to_owned())),
+            ("switch_page 1".to_owned(), KeyBindingsDes::Single("<X-1>".to_owned()))
+        ]);
+        let kbt = create_key_bindings_trie(&kb, "<X-w>")?;
+        assert_eq!(kbt.get(&vec![Key::KeySpace, Key::KeyN]), Some(&"next_page".to_owned()));
+        assert_eq!(kbt.get(&parse_to_key_combination("zz", &no_leader)?), Some(&"app_quit".to_owned()));
+        Ok(())
+    }
+
+    // Test if command lines mapped at runtime are checked against known commands, arguments aside.
+    #[test]
+    fn test_val_cmd() {
+        assert_eq!(val_cmd("switch_page 1"), Ok(()));
+        assert_eq!(val_cmd("bogus 1"), Err(KeyBindingErr::UnknownCommand {
+            cmd: "bogus 1".to_owned(),
+            name: "bogus".to_owned()
+        }));
+    }
+
+    // * Test if duplicates, shadowed prefixes, reserved first keys, unknown commands and unparsable key-bindings
+    //   are reported.
+    // * Test if default key-bindings are valid.
+    #[test]
+    fn test_val_key_bindings() {
+        assert_eq!(val_key_bindings(&HashMap::new(), "<Space>"), vec![]);
+        assert_eq!(val_key_bindings(&HashMap::new(), "<leader>"), vec![
+            KeyBindingErr::InvalidLeader {
+                leader: "<leader>".to_owned(),
+                reason: "Invalid key-binding format <leader>, no leader set".to_owned()
+            }
+        ]);
+
+        let kb: HashMap<String, KeyBindingsDes> = HashMap::from([
+            ("app_quit".to_owned(), KeyBindingsDes::Single("j".to_owned())),
+            ("next_page".to_owned(), KeyBindingsDes::Single("g".to_owned())),
+            ("open_sub rust".to_owned(), KeyBindingsDes::Single("<C-o>".to_owned())),
+            ("switch_page 1".to_owned(), KeyBindingsDes::Single("<X-1>".to_owned())),
+            ("switch_page 2".to_owned(), KeyBindingsDes::Single("qa".to_owned())),
+            ("switch_page 3".to_owned(), KeyBindingsDes::Multiple(vec!["<leader>a".to_owned(), "<S-q>".to_owned()]))
+        ]);
+        assert_eq!(val_key_bindings(&kb, "q"), vec![
+            KeyBindingErr::UnknownCommand {
+                cmd: "open_sub rust".to_owned(),
+                name: "open_sub".to_owned()
+            },
+            KeyBindingErr::Unparsable {
+                cmd: "switch_page 1".to_owned(),
+                key_str: "<X-1>".to_owned(),
+                reason: "Cannot find any key corresponding to X".to_owned()
+            },
+            KeyBindingErr::Reserved {
+                cmd: "switch_page 2".to_owned(),
+                key_str: "qa".to_owned()
+            },
+            KeyBindingErr::Reserved {
+                cmd: "switch_page 3".to_owned(),
+                key_str: "<leader>a".to_owned()
+            },
+            KeyBindingErr::Shadowed {
+                cmd: "prev_page".to_owned(),
+                key_str: "g<S-t>".to_owned(),
+                prefix_cmd: "next_page".to_owned(),
+                prefix_key_str: "g".to_owned()
+            },
+            KeyBindingErr::Duplicate {
+                cmd: "scroll_down".to_owned(),
+                key_str: "j".to_owned(),
+                other_cmd: "app_quit".to_owned()
+            }
+        ]);
+    }
+
     // * Test if all keys are present in key_to_code
     // * Test all codes must be different
     #[test]
@@ -328,46 +680,62 @@ mod tests {
     // Test parse_to_key_combination parses key combination strings to Key enum variants proper.
     #[test]
     fn test_parse_to_key_combination() {
+        let no_leader = KeyCombination::new();
+
         let key_comb1: KeyCombination = vec!{
             Key::HoldCtrl,
             Key::KeyB
         };
-        assert_eq!(key_comb1, parse_to_key_combination("<C-b>").unwrap());
+        assert_eq!(key_comb1, parse_to_key_combination("<C-b>", &no_leader).unwrap());
 
         let key_comb2: KeyCombination = vec!{
             Key::KeyEsc,
         }; 
-        assert_eq!(key_comb2, parse_to_key_combination("<Esc>").unwrap());
+        assert_eq!(key_comb2, parse_to_key_combination("<Esc>", &no_leader).unwrap());
 
         let key_comb3: KeyCombination = vec!{
             Key::HoldCtrl,
             Key::KeyTab
         };
-        assert_eq!(key_comb3, parse_to_key_combination("<C-Tab>").unwrap());
+        assert_eq!(key_comb3, parse_to_key_combination("<C-Tab>", &no_leader).unwrap());
 
         let key_comb4: KeyCombination = vec!{
             Key::HoldCtrl,
             Key::KeyA,
             Key::KeyG
         };
-        assert_eq!(key_comb4, parse_to_key_combination("<C-a>g").unwrap());
+        assert_eq!(key_comb4, parse_to_key_combination("<C-a>g", &no_leader).unwrap());
 
         let key_comb5: KeyCombination = vec!{
             Key::KeyG,
             Key::KeyG
         };
-        assert_eq!(key_comb5, parse_to_key_combination("gg").unwrap());
+        assert_eq!(key_comb5, parse_to_key_combination("gg", &no_leader).unwrap());
 
         let key_comb6: KeyCombination = vec!{
             Key::KeySpace,
             Key::KeyX
         };
-        assert_eq!(key_comb6, parse_to_key_combination("<Space>x").unwrap());
+        assert_eq!(key_comb6, parse_to_key_combination("<Space>x", &no_leader).unwrap());
 
         let key_comb7: KeyCombination = vec!{
             Key::KeyG,
             Key::KeyEsc
         };
-        assert_eq!(key_comb7, parse_to_key_combination("g<Esc>").unwrap());
+        assert_eq!(key_comb7, parse_to_key_combination("g<Esc>", &no_leader).unwrap());
+
+        let leader: KeyCombination = vec!{
+            Key::HoldCtrl,
+            Key::KeyW
+        };
+        let key_comb8: KeyCombination = vec!{
+            Key::HoldCtrl,
+            Key::KeyW,
+            Key::KeyX
+        };
+        assert_eq!(key_comb8, parse_to_key_combination("<leader>x", &leader).unwrap());
+        assert_eq!(key_comb8, parse_to_key_combination("<Leader>x", &leader).unwrap());
+        assert!(parse_to_key_combination("<C-leader>", &leader).is_err());
+        assert!(parse_to_key_combination("<leader>x", &no_leader).is_err());
     }
 }
@@ -0,0 +1,354 @@
+use anyhow::{ anyhow, bail, Result };
+use nix::fcntl::OFlag;
+use std::{
+    env,
+    fs::{ self, DirBuilder, OpenOptions },
+    io::{ ErrorKind, Write },
+    os::unix::fs::{ DirBuilderExt, OpenOptionsExt },
+    path::{ Path, PathBuf },
+    process::{ self, Command }
+};
+use thiserror::Error;
+
+use crate::{
+    api::model::{ Item, NewMessage, Submission },
+    def::app::DRAFTS_DIR_NAME,
+    jobs::state_dir
+};
+
+// Lines of drafts starting with it are left out, e.g. hints of templates.
+pub const COMMENT_PREFIX: &str = "#:";
+const ERROR_PREFIX: &str = "#: Error:";
+// Line between the fields and the body of a submission or message.
+const SEPARATOR: &str = "---";
+const MAX_TITLE_LEN: usize = 300;
+// Lines of the item replied to quoted in reply template.
+const QUOTED_LINES: usize = 10;
+const DEFAULT_EDITOR: &str = "vi";
+
+// Problems in a draft, shown on top of it when the editor is opened again.
+#[derive(Debug, Error, PartialEq)]
+pub enum ComposeErr {
+    #[error("title is missing")]
+    MissingTitle,
+
+    #[error("subject is missing")]
+    MissingSubject,
+
+    #[error("message is empty, write it below the subject")]
+    MissingBody,
+
+    #[error("title is {len} characters long, {max} at most")]
+    TitleTooLong { len: usize, max: usize },
+
+    #[error("give either url or body, not both")]
+    UrlAndBody,
+
+    #[error("line \"{line}\" is not a field, fields are written as name: value")]
+    NotAField { line: String },
+
+    #[error("unknown field {name}, expected one of: {expected}")]
+    UnknownField { name: String, expected: &'static str },
+
+    #[error("{name} takes yes or no, not \"{value}\"")]
+    NotAFlag { name: String, value: String }
+}
+
+// Draft text without comment lines.
+fn strip_comments(text: &str) -> Vec<&str> {
+    text.lines().filter(|line| !line.starts_with(COMMENT_PREFIX)).collect()
+}
+
+// Lines of text as comment lines quoting it.
+fn quote(text: &str) -> String {
+    text.lines().take(QUOTED_LINES).map(|line| format!("{} > {}\n", COMMENT_PREFIX, line)).collect()
+}
+
+pub fn reply_template(item: &Item) -> String {
+    let (author, place, text) = match item {
+        Item::Post(post) => (&post.author, format!("r/{}", post.subreddit), format!("{}\n\n{}", post.title, post.selftext)),
+        Item::Comment(comment) => (&comment.author, format!("r/{}", comment.subreddit), comment.body.clone()),
+        Item::Message(message) if message.was_comment => (&message.author, format!("r/{}", message.subreddit), message.body.clone()),
+        Item::Message(message) => (&message.author, format!("\"{}\"", message.subject), message.body.clone())
+    };
+    format!("{0} Reply to u/{1} on {2}:\n{3}{0} Write markdown below. Lines starting with {0} are left out, \
+        leave it empty to cancel.\n\n", COMMENT_PREFIX, author, place, quote(text.trim_end()))
+}
+
+// Markdown source of own item to edit.
+pub fn edit_template(item: &Item) -> String {
+    format!("{0} Edit your {1} on r/{2}. Lines starting with {0} are left out, leave it empty to cancel.\n{3}\n",
+        COMMENT_PREFIX, item.kind(), item.subreddit(), item.body().trim_end())
+}
+
+pub fn submit_template(subreddit: &str) -> String {
+    format!("{0} Submit to r/{1}. Lines starting with {0} are left out, leave the title empty to cancel.\n\
+        {0} Give a url for a link post, or write the body of a text post below {2}.\n\
+        {0} flair takes the text of one of the subreddit's link flairs, nsfw and spoiler take yes or no.\n\
+        title: \nurl: \nflair: \nnsfw: no\nspoiler: no\n{2}\n\n", COMMENT_PREFIX, subreddit, SEPARATOR)
+}
+
+pub fn message_template(to: &str) -> String {
+    format!("{0} Message to u/{1}. Lines starting with {0} are left out, leave it empty to cancel.\n\
+        {0} Write the message below {2}.\n\
+        subject: \n{2}\n\n", COMMENT_PREFIX, to, SEPARATOR)
+}
+
+// Markdown of reply, None if left empty to cancel.
+pub fn parse_reply(text: &str) -> Result<Option<String>, ComposeErr> {
+    let body = strip_comments(text).join("\n");
+    Ok(Some(body.trim().to_string()).filter(|body| !body.is_empty()))
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Submission to subreddit from fields above the separator and body below it.
+// * None if title, url and body are left empty to cancel.
+// -----------------------------------------------------------------------------------------------------------
+pub fn parse_submit(subreddit: &str, text: &str) -> Result<Option<Submission>, ComposeErr> {
+    let (fields, body) = split_fields(text)?;
+    let mut submission = Submission { subreddit: subreddit.to_string(), body, ..Submission::default() };
+    for (name, value) in fields {
+        let flag = || match value {
+            "yes" | "true" => Ok(true),
+            "no" | "false" | "" => Ok(false),
+            _ => Err(ComposeErr::NotAFlag { name: name.to_string(), value: value.to_string() })
+        };
+        match name {
+            "title" => { submission.title = value.to_string(); },
+            "url" => { submission.url = Some(value.to_string()).filter(|url| !url.is_empty()); },
+            "flair" => { submission.flair = Some(value.to_string()).filter(|flair| !flair.is_empty()); },
+            "nsfw" => { submission.nsfw = flag()?; },
+            "spoiler" => { submission.spoiler = flag()?; },
+            _ => { return Err(ComposeErr::UnknownField { name: name.to_string(), expected: "title, url, flair, nsfw, spoiler" }); }
+        }
+    }
+
+    if submission.title.is_empty() {
+        if submission.url.is_none() && submission.body.is_empty() { return Ok(None); }
+        return Err(ComposeErr::MissingTitle);
+    }
+    let len = submission.title.chars().count();
+    if len > MAX_TITLE_LEN {
+        return Err(ComposeErr::TitleTooLong { len, max: MAX_TITLE_LEN });
+    }
+    if submission.url.is_some() && !submission.body.is_empty() {
+        return Err(ComposeErr::UrlAndBody);
+    }
+    Ok(Some(submission))
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Message to account from the subject above the separator and body below it.
+// * None if subject and body are left empty to cancel.
+// -----------------------------------------------------------------------------------------------------------
+pub fn parse_message(to: &str, text: &str) -> Result<Option<NewMessage>, ComposeErr> {
+    let (fields, body) = split_fields(text)?;
+    let mut message = NewMessage { to: to.to_string(), body, ..NewMessage::default() };
+    for (name, value) in fields {
+        match name {
+            "subject" => { message.subject = value.to_string(); },
+            _ => { return Err(ComposeErr::UnknownField { name: name.to_string(), expected: "subject" }); }
+        }
+    }
+
+    match (message.subject.is_empty(), message.body.is_empty()) {
+        (true, true) => Ok(None),
+        (true, false) => Err(ComposeErr::MissingSubject),
+        (false, true) => Err(ComposeErr::MissingBody),
+        (false, false) => Ok(Some(message))
+    }
+}
+
+// Fields of a draft, as trimmed name and value.
+type Fields<'a> = Vec<(&'a str, &'a str)>;
+
+// Fields above the separator and trimmed body below it.
+fn split_fields(text: &str) -> Result<(Fields<'_>, String), ComposeErr> {
+    let lines = strip_comments(text);
+    let (fields, body) = match lines.iter().position(|line| line.trim() == SEPARATOR) {
+        Some(i) => (&lines[..i], lines[i + 1..].join("\n")),
+        None => (&lines[..], String::new())
+    };
+    let fields = fields.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| match line.split_once(':') {
+            Some((name, value)) => Ok((name.trim(), value.trim())),
+            None => Err(ComposeErr::NotAField { line: line.to_string() })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((fields, body.trim().to_string()))
+}
+
+// Draft with error on top, replacing the error shown before.
+pub fn with_error(text: &str, err: &ComposeErr) -> String {
+    let draft: String = text.lines()
+        .skip_while(|line| line.starts_with(ERROR_PREFIX))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    format!("{} {}\n{}", ERROR_PREFIX, err, draft)
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Edit draft until parse takes it, editing it again with the error on top while it fails.
+// * None if the draft is cancelled, as parse tells.
+// -----------------------------------------------------------------------------------------------------------
+pub fn compose<T>(mut draft: String, parse: impl Fn(&str) -> Result<Option<T>, ComposeErr>,
+        mut edit: impl FnMut(&str) -> Result<String>) -> Result<Option<T>> {
+    loop {
+        draft = edit(&draft)?;
+        match parse(&draft) {
+            Ok(parsed) => { return Ok(parsed); },
+            Err(e) => { draft = with_error(&draft, &e); }
+        }
+    }
+}
+
+// New draft file of kind, e.g. reply, in the drafts directory of the state directory.
+pub fn create_draft(kind: &str) -> Result<PathBuf> {
+    let dir = state_dir().ok_or_else(|| anyhow!("No state directory found, set $XDG_STATE_HOME or $HOME"))?;
+    create_draft_in(&dir.join(DRAFTS_DIR_NAME), kind)
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Create an empty draft file of kind in dir, readable only by the user.
+// * Each draft gets a file of its own, so a draft kept is never written over by a later one.
+// * Created exclusively, an existing file or symlink of the same name is never opened.
+// -----------------------------------------------------------------------------------------------------------
+fn create_draft_in(dir: &Path, kind: &str) -> Result<PathBuf> {
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    for n in 0.. {
+        let path = dir.join(format!("{}-{}-{}.md", kind, process::id(), n));
+        match OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path) {
+            Ok(_) => { return Ok(path); },
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {},
+            Err(e) => { return Err(e.into()); }
+        }
+    }
+    unreachable!()
+}
+
+// Write text to draft at path and edit it in $VISUAL or $EDITOR, returning the text saved.
+pub fn edit_in_editor(path: &Path, text: &str) -> Result<String> {
+    // The draft was created by create_draft, a symlink put in its place is not followed.
+    OpenOptions::new().write(true).truncate(true).custom_flags(OFlag::O_NOFOLLOW.bits()).open(path)?
+        .write_all(text.as_bytes())?;
+    let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or_else(|| anyhow!("No editor set in $VISUAL or $EDITOR."))?;
+    let status = Command::new(program).args(words).arg(path).status()
+        .map_err(|e| anyhow!("Unable to run editor {}: {}", editor, e))?;
+    if !status.success() {
+        bail!("Editor {} exited with {}, draft kept in {}", editor, status, path.display());
+    }
+    Ok(fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::model::{ Comment, Item, Message, NewMessage, Post, Submission };
+    use std::{ env, fs, os::unix::fs::{ symlink, PermissionsExt } };
+
+    use super::{
+        compose, create_draft_in, edit_in_editor, edit_template, message_template, parse_message, parse_reply, parse_submit, reply_template, submit_template,
+        ComposeErr
+    };
+
+    // Test if templates left as they are cancel, and comment lines are left out of what is written or edited.
+    #[test]
+    fn test_parse_templates() {
+        let post = Item::Post(Post { author: "ferris".to_string(), title: "Crab".to_string(), ..Post::default() });
+        let template = reply_template(&post);
+        assert!(template.contains("#: > Crab"));
+        assert_eq!(parse_reply(&template), Ok(None));
+        assert_eq!(parse_reply(&format!("{}Nice\n#: left out\n\n# Heading\n", template)), Ok(Some("Nice\n\n# Heading".to_string())));
+
+        let comment = Item::Comment(Comment { body: "Typo\n\n> quoted\n".to_string(), ..Comment::default() });
+        assert_eq!(parse_reply(&edit_template(&comment)), Ok(Some("Typo\n\n> quoted".to_string())));
+
+        let template = submit_template("rust");
+        assert_eq!(parse_submit("rust", &template), Ok(None));
+        let draft = template
+            .replace("title: ", "title: Ravana")
+            .replace("spoiler: no", "spoiler: yes")
+            .replace("flair: ", "flair: Tool")
+            + "Body\n--- of post\n";
+        assert_eq!(parse_submit("rust", &draft), Ok(Some(Submission {
+            subreddit: "rust".to_string(),
+            title: "Ravana".to_string(),
+            url: None,
+            body: "Body\n--- of post".to_string(),
+            flair: Some("Tool".to_string()),
+            nsfw: false,
+            spoiler: true
+        })));
+    }
+
+    // Test if bad drafts are refused with the reason.
+    #[test]
+    fn test_parse_submit_errors() {
+        assert_eq!(parse_submit("rust", "url: https://a.b\n"), Err(ComposeErr::MissingTitle));
+        assert_eq!(parse_submit("rust", &format!("title: {}", "t".repeat(301))),
+            Err(ComposeErr::TitleTooLong { len: 301, max: 300 }));
+        assert_eq!(parse_submit("rust", "title: t\nurl: https://a.b\n---\nbody"), Err(ComposeErr::UrlAndBody));
+        assert_eq!(parse_submit("rust", "title t\n"), Err(ComposeErr::NotAField { line: "title t".to_string() }));
+        assert_eq!(parse_submit("rust", "tags: t\n"), Err(ComposeErr::UnknownField { name: "tags".to_string(), expected: "title, url, flair, nsfw, spoiler" }));
+        assert_eq!(parse_submit("rust", "title: t\nnsfw: maybe"),
+            Err(ComposeErr::NotAFlag { name: "nsfw".to_string(), value: "maybe".to_string() }));
+    }
+
+    // Test if messages need a subject and a body, and replies to them quote the conversation's subject.
+    #[test]
+    fn test_parse_message() {
+        let template = message_template("ferris");
+        assert_eq!(parse_message("ferris", &template), Ok(None));
+        assert_eq!(parse_message("ferris", &template.replace("subject: ", "subject: Hi")), Err(ComposeErr::MissingBody));
+        assert_eq!(parse_message("ferris", &format!("{}Hello\n", template)), Err(ComposeErr::MissingSubject));
+        assert_eq!(parse_message("ferris", &format!("{}Hello\n", template.replace("subject: ", "subject: Hi"))),
+            Ok(Some(NewMessage { to: "ferris".to_string(), subject: "Hi".to_string(), body: "Hello".to_string() })));
+        assert_eq!(parse_message("ferris", "title: Hi\n"),
+            Err(ComposeErr::UnknownField { name: "title".to_string(), expected: "subject" }));
+
+        let message = Item::Message(Message { author: "crab".to_string(), subject: "Hi".to_string(), ..Message::default() });
+        assert!(reply_template(&message).starts_with("#: Reply to u/crab on \"Hi\":"));
+    }
+
+    // Test if editor reopens with the last error on top until the draft parses.
+    #[test]
+    fn test_compose() {
+        let mut drafts = vec!["title: Ravana\nnsfw: maybe\n", "title: \nurl: https://a.b\n", "title: Ravana\n"].into_iter();
+        let mut opened = vec![];
+        let submission = compose(submit_template("rust"), |text| parse_submit("rust", text), |text| {
+            opened.push(text.to_string());
+            let draft = drafts.next().unwrap();
+            // Editing keeps the error header, as a user would.
+            Ok(text.lines().take_while(|line| line.starts_with("#: Error:")).map(|line| format!("{}\n", line)).collect::<String>() + draft)
+        }).unwrap().unwrap();
+        assert_eq!(submission.title, "Ravana");
+
+        assert_eq!(opened.len(), 3);
+        assert!(opened[1].starts_with("#: Error: nsfw takes yes or no, not \"maybe\"\ntitle: Ravana"));
+        assert!(opened[2].starts_with("#: Error: title is missing\ntitle: \n"));
+    }
+
+    // Test if each draft gets a new file only the user can read, and symlinks are neither reused nor followed.
+    #[test]
+    fn test_create_draft() {
+        let dir = env::temp_dir().join(format!("ravana-test-drafts-{}", std::process::id()));
+        let target = env::temp_dir().join(format!("ravana-test-drafts-target-{}", std::process::id()));
+        fs::write(&target, "secret").unwrap();
+
+        let first = create_draft_in(&dir, "reply").unwrap();
+        fs::remove_file(&first).unwrap();
+        symlink(&target, &first).unwrap();
+        let second = create_draft_in(&dir, "reply").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(fs::metadata(&second).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+
+        assert!(edit_in_editor(&first, "draft").is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "secret");
+
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_file(&target).unwrap();
+    }
+}
@@ -1,40 +1,461 @@
-use anyhow::Result;
-use log::{ error, info, warn };
+use anyhow::{ bail, Result };
+use log::{ debug, error, info, warn, LevelFilter };
 use std::{
-    fs::File,
-    io::prelude::*,
-    path::Path
+    collections::BTreeMap,
+    env,
+    fs,
+    path::{ Path, PathBuf }
 };
+use toml::{ map::Map, Value };
 
-use crate::def::app::CONFIG_DIR_PATHS;
-use super::util::config::Config;
+use crate::{
+    def::app::{
+        CONFIG_INCLUDE_KEY,
+        DEFAULT_THEME,
+        MAX_CONFIG_INCLUDE_DEPTH,
+        STARTER_CONFIG,
+        THEME_PRESETS,
+        THEMES_DIR_NAME
+    },
+    input::DEFAULT_KEY_BINDINGS,
+    tui::{ val_color_fmt, COLOR_FMT_HINT }
+};
+use super::{
+    diagnostics::{ fmt_key_path, locate_key, suggest, Diagnostic },
+    util::{ config::Config, dirs::ConfigRoots }
+};
+
+// Config along with problems found loading it.
+pub struct LoadedConfig {
+    pub config: Config,
+    pub diagnostics: Vec<Diagnostic>,
+    // Files merged into config, lowest layer first.
+    pub files: Vec<LoadedFile>,
+    // --config path it was loaded with, to reload it the same way.
+    pub cli_path: Option<PathBuf>
+}
+
+// Config file merged into config.
+pub struct LoadedFile {
+    pub path: PathBuf,
+    pub contents: String,
+    // Valid values set by the file itself, without its includes.
+    pub value: Value
+}
+
+impl LoadedConfig {
+    // Point diagnostic at the last file setting its key.
+    pub fn locate(&self, diagnostic: &mut Diagnostic) {
+        if let Some(file) = self.files.iter().rev()
+            .find(|file| locate_key(&file.contents, &diagnostic.key_path).is_some()) {
+            diagnostic.locate(&file.path, &file.contents);
+        }
+    }
+
+    // Last file setting key path, None if it has the default value.
+    pub fn origin(&self, key_path: &[String]) -> Option<&Path> {
+        self.files.iter().rev()
+            .find(|file| {
+                key_path.iter().try_fold(&file.value, |value, key| value.get(key.as_str())).is_some()
+            })
+            .map(|file| file.path.as_path())
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Layers, each merged over the previous: built-in defaults, theme, system config, user config.
+// * User config is the first readable and valid Config.toml among candidates, see config_candidates.
+// * A --config or $RAVANA_CONFIG file that is missing or unreadable is an error, not skipped.
+// * A file's includes are merged under it, so it overrides what it includes.
+// * Invalid values are reported and dropped, keeping the value of the layer below.
+// * Log which file won and why the others were skipped.
+// -----------------------------------------------------------------------------------------------------------
+pub fn load_config(cli_path: Option<&Path>) -> Result<LoadedConfig> {
+    load_config_from(&ConfigRoots::from_env(cli_path), cli_path)
+}
+
+pub(crate) fn load_config_from(roots: &ConfigRoots, cli_path: Option<&Path>) -> Result<LoadedConfig> {
+    let mut loaded = LoadedConfig {
+        config: Config::default(),
+        diagnostics: Vec::new(),
+        files: Vec::new(),
+        cli_path: cli_path.map(|path| path.to_path_buf())
+    };
+    let mut base = Value::Table(Map::new());
+
+    let system_path = roots.system.as_path();
+    if system_path.exists() {
+        match load_layer(system_path, &mut Vec::new(), &mut loaded.files, &mut loaded.diagnostics) {
+            Ok(layer) => {
+                debug!("Using system config at: {}", system_path.display());
+                base = layer;
+            },
+            Err(e) => {
+                warn!("Skipping system config: {}", e);
+                loaded.diagnostics.push(e);
+            }
+        }
+    }
+
+    let mut skipped: Vec<String> = Vec::new();
+    for (path, source) in roots.candidates.iter() {
+        if !path.exists() {
+            if source.is_explicit() {
+                bail!("Config {} from {} not found", path.display(), source);
+            }
+            skipped.push(format!("{} ( from {} ): not found", path.display(), source));
+            continue;
+        }
+
+        let mut files = Vec::new();
+        let mut diagnostics = Vec::new();
+        match load_layer(path, &mut Vec::new(), &mut files, &mut diagnostics) {
+            Ok(layer) => {
+                info!("Using config at: {}", path.display());
+                debug!("Config {} won ( from {} ), skipped: [{}]", path.display(), source, skipped.join("; "));
+                merge_toml(&mut base, layer);
+                loaded.files.extend(files);
+                loaded.diagnostics.extend(diagnostics);
+                break;
+            },
+            Err(e) if source.is_explicit() => bail!("Unable to use config from {}: {}", source, e),
+            Err(e) => {
+                warn!("Skipping config {} from {}: {}", path.display(), source, e);
+                skipped.push(format!("{} ( from {} ): {}", path.display(), source, e));
+                loaded.diagnostics.push(e);
+            }
+        }
+    }
+    if loaded.files.is_empty() {
+        debug!("No config found, skipped: [{}]", skipped.join("; "));
+    }
+
+    let theme = base.get("theme").and_then(|theme| theme.as_str()).unwrap_or(DEFAULT_THEME).to_string();
+    let mut layered = load_theme(&theme, &mut loaded);
+    merge_toml(&mut layered, base);
+
+    // Layers are validated, so this only fails on bugs in validation.
+    match deserialize_toml(layered) {
+        Ok(config) => { loaded.config = config; },
+        Err(e) => {
+            loaded.diagnostics.push(Diagnostic::error(&[], format!("unable to use config, using defaults: {}", e)));
+        }
+    }
+    Ok(loaded)
+}
 
 // -----------------------------------------------------------------------------------------------------------
-// * Open up Config.toml for reading.
-// * Deserialize to Config struct.
+// * Read file as TOML table, validating it and dropping invalid values.
+// * Merge files listed in its include = [...] in order, then the file itself over them.
+// * Include paths are relative to the including file, ~/ is the home directory.
+// * chain holds files being loaded, to catch include cycles.
+// * Files read are added to files, problems in them to diagnostics.
+// * Returns error if the file itself or an include cannot be used.
 // -----------------------------------------------------------------------------------------------------------
-pub fn load_config() -> Config {
-    for path in CONFIG_DIR_PATHS {
-        match File::open(Path::new(&format!("{}{}", path, "/Config.toml"))) {
-            Ok(mut file) => {
-                let mut contents = String::new();
-                if file.read_to_string(&mut contents).is_ok() {
-                    if let Ok(config) = deserialize_toml(&contents) {
-                        info!("Using config at: {}", path);
-                        return config
-                    }
+fn load_layer(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+    files: &mut Vec<LoadedFile>,
+    diagnostics: &mut Vec<Diagnostic>) -> Result<Value, Diagnostic> {
+
+    let layer_err = |message: String| {
+        let mut e = Diagnostic::error(&[], message);
+        e.file = Some(path.to_path_buf());
+        e
+    };
+
+    if chain.iter().any(|p| p == path) {
+        return Err(layer_err("include cycle".to_string()));
+    }
+    if chain.len() >= MAX_CONFIG_INCLUDE_DEPTH {
+        return Err(layer_err(format!("includes nested deeper than {}", MAX_CONFIG_INCLUDE_DEPTH)));
+    }
+
+    let contents = fs::read_to_string(path).map_err(|e| layer_err(format!("unable to read: {}", e)))?;
+    let mut value: Value = toml::from_str(&contents).map_err(|e| toml_err(path, e))?;
+
+    let include_key = vec![CONFIG_INCLUDE_KEY.to_string()];
+    let includes: Vec<String> = match value.as_table_mut().and_then(|table| table.remove(CONFIG_INCLUDE_KEY)) {
+        Some(includes) => includes.try_into().map_err(|_| {
+            let mut e = Diagnostic::error(&include_key, "must be a list of paths".to_string());
+            e.locate(path, &contents);
+            e
+        })?,
+        None => Vec::new()
+    };
+
+    let mut layer = Value::Table(Map::new());
+    chain.push(path.to_path_buf());
+    for include in includes.iter() {
+        let include_path = resolve_include(path, include);
+        let include_layer = load_layer(&include_path, chain, files, diagnostics).map_err(|e| {
+            let mut e_diag = Diagnostic::error(&include_key, format!("cannot include {}: {}", include, e));
+            e_diag.locate(path, &contents);
+            e_diag
+        })?;
+        merge_toml(&mut layer, include_layer);
+    }
+    chain.pop();
+
+    let mut layer_diagnostics = Vec::new();
+    val_layer(&mut value, &schema(), &mut Vec::new(), &mut layer_diagnostics);
+    for mut diagnostic in layer_diagnostics {
+        diagnostic.locate(path, &contents);
+        diagnostics.push(diagnostic);
+    }
+    files.push(LoadedFile { path: path.to_path_buf(), contents, value: value.clone() });
+
+    merge_toml(&mut layer, value);
+    Ok(layer)
+}
+
+// Syntax error at its position in file.
+fn toml_err(path: &Path, e: toml::de::Error) -> Diagnostic {
+    let message = e.to_string();
+    let mut e_diag = Diagnostic::error(&[], format!("invalid TOML: {}", message.split(" at line ").next().unwrap_or(&message)));
+    e_diag.file = Some(path.to_path_buf());
+    e_diag.line_col = e.line_col().map(|(line, col)| (line + 1, col + 1));
+    e_diag
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Theme colors as TOML under tui.theme, for config layers to be merged over.
+// * Theme is themes/<name>.toml next to the config files loaded, last loaded first, else a shipped preset.
+// * Theme files hold tui.theme entries at top level, validated like them.
+// * Unknown or unusable themes are reported, leaving default colors.
+// -----------------------------------------------------------------------------------------------------------
+fn load_theme(name: &str, loaded: &mut LoadedConfig) -> Value {
+    let theme_key = vec!["theme".to_string()];
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for file in loaded.files.iter().rev() {
+        if let Some(dir) = file.path.parent().map(|dir| dir.join(THEMES_DIR_NAME)) {
+            if !dirs.contains(&dir) { dirs.push(dir); }
+        }
+    }
+
+    if name.is_empty() || name.starts_with('.') || name.contains(|c: char| c == '/' || c == '\\') {
+        let mut e = Diagnostic::error(&theme_key, format!("invalid theme name \"{}\", expected a name, not a path", name));
+        loaded.locate(&mut e);
+        loaded.diagnostics.push(e);
+        return Value::Table(Map::new());
+    }
+
+    let file_name = format!("{}.toml", name);
+    let (path, contents) = match dirs.iter().map(|dir| dir.join(&file_name)).find(|path| path.is_file()) {
+        Some(path) => match fs::read_to_string(&path) {
+            Ok(contents) => (path, contents),
+            Err(e) => {
+                let mut e_diag = Diagnostic::error(&[], format!("unable to read theme: {}", e));
+                e_diag.file = Some(path);
+                loaded.diagnostics.push(e_diag);
+                return Value::Table(Map::new());
+            }
+        },
+        None => match THEME_PRESETS.iter().find(|(preset, _)| *preset == name) {
+            Some((_, contents)) => (Path::new(THEMES_DIR_NAME).join(&file_name), contents.to_string()),
+            None => {
+                let mut names: Vec<String> = THEME_PRESETS.iter().map(|(preset, _)| preset.to_string()).collect();
+                for entries in dirs.iter().filter_map(|dir| fs::read_dir(dir).ok()) {
+                    names.extend(entries.filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        if path.extension()? != "toml" { return None; }
+                        Some(path.file_stem()?.to_string_lossy().to_string())
+                    }));
                 }
-                else { warn!("Unable to open file: {}", path); }
+                let mut e = Diagnostic::error(&theme_key, format!("unknown theme \"{}\"", name));
+                e.suggestion = suggest(name, names.iter().map(|name| name.as_str()));
+                loaded.locate(&mut e);
+                loaded.diagnostics.push(e);
+                return Value::Table(Map::new());
             }
-            Err(e) => { warn!("Skipping opening file: {}, due to: {:?}", path, e); }
         }
+    };
+    debug!("Using theme {} at: {}", name, path.display());
+
+    let mut value: Value = match toml::from_str(&contents) {
+        Ok(value) => value,
+        Err(e) => {
+            loaded.diagnostics.push(toml_err(&path, e));
+            return Value::Table(Map::new());
+        }
+    };
+    let theme_schema = schema().get("tui").and_then(|tui| tui.get("theme")).cloned().unwrap_or(Value::Table(Map::new()));
+    let mut theme_diagnostics = Vec::new();
+    val_layer(&mut value, &theme_schema, &mut vec!["tui".to_string(), "theme".to_string()], &mut theme_diagnostics);
+    for mut diagnostic in theme_diagnostics {
+        diagnostic.line_col = locate_key(&contents, &diagnostic.key_path[2..]);
+        diagnostic.locate(&path, &contents);
+        loaded.diagnostics.push(diagnostic);
     }
-    error!("Unable to open config file. Returning default config.");
-    Config::default()
+
+    let mut tui = Map::new();
+    tui.insert("theme".to_string(), value);
+    let mut layer = Map::new();
+    layer.insert("tui".to_string(), Value::Table(tui));
+    let layer = Value::Table(layer);
+    // Lowest layer above defaults.
+    loaded.files.insert(0, LoadedFile { path, contents, value: layer.clone() });
+    layer
 }
 
-fn deserialize_toml(s: &str) -> Result<Config, toml::de::Error> {
-    match toml::from_str(s) {
+// Defaults as TOML, giving the known keys and their types.
+fn schema() -> Value {
+    Value::try_from(Config::default()).unwrap_or(Value::Table(Map::new()))
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Check table against schema, reporting unknown keys, values of wrong type and invalid colors.
+// * Entries reported are removed.
+// -----------------------------------------------------------------------------------------------------------
+fn val_layer(value: &mut Value, schema: &Value, key_path: &mut Vec<String>, diagnostics: &mut Vec<Diagnostic>) {
+    let (table, schema_table) = match (value.as_table_mut(), schema.as_table()) {
+        (Some(table), Some(schema_table)) => (table, schema_table),
+        _ => { return; }
+    };
+
+    let keys: Vec<String> = table.keys().cloned().collect();
+    for key in keys {
+        key_path.push(key.clone());
+        let valid = match (table.get_mut(&key), schema_table.get(&key)) {
+            // Key-bindings take any command line as key.
+            (Some(value), _) if key_path.len() == 2 && key_path[0] == "key-bindings" => {
+                val_key_bindings_entry(value, key_path, diagnostics)
+            },
+            (Some(value), Some(schema_value)) => val_entry(value, schema_value, key_path, diagnostics),
+            _ => {
+                let mut e = Diagnostic::warning(key_path, "unknown key".to_string());
+                e.suggestion = suggest(&key, schema_table.keys().map(|k| k.as_str()));
+                diagnostics.push(e);
+                false
+            }
+        };
+        if !valid { table.remove(&key); }
+        key_path.pop();
+    }
+}
+
+fn val_entry(value: &mut Value, schema: &Value, key_path: &mut Vec<String>, diagnostics: &mut Vec<Diagnostic>) -> bool {
+    if !value.same_type(schema) {
+        diagnostics.push(Diagnostic::error(key_path,
+            format!("expected {}, found {}", schema.type_str(), value.type_str())));
+        return false;
+    }
+    match value {
+        Value::Table(_) => { val_layer(value, schema, key_path, diagnostics); },
+        Value::String(level) if key_path.len() == 1 && key_path[0] == "log-level" => {
+            if level.parse::<LevelFilter>().is_err() {
+                diagnostics.push(Diagnostic::error(key_path,
+                    format!("invalid log level \"{}\", expected off, error, warn, info, debug or trace", level)));
+                return false;
+            }
+        },
+        Value::String(color) if key_path.len() == 3 && key_path[0] == "tui" && key_path[1] == "theme" => {
+            if !val_color_fmt(color) {
+                diagnostics.push(Diagnostic::error(key_path,
+                    format!("invalid color \"{}\", {}", color, COLOR_FMT_HINT)));
+                return false;
+            }
+        },
+        _ => {  }
+    }
+    true
+}
+
+// Key-binding is a string or list of strings.
+fn val_key_bindings_entry(value: &Value, key_path: &[String], diagnostics: &mut Vec<Diagnostic>) -> bool {
+    let valid = match value {
+        Value::String(_) => true,
+        Value::Array(key_strs) => key_strs.iter().all(|key_str| key_str.is_str()),
+        _ => false
+    };
+    if !valid {
+        diagnostics.push(Diagnostic::error(key_path,
+            format!("expected key-binding string or list of them, found {}", value.type_str())));
+    }
+    valid
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Resolved config as TOML, with key-bindings merged over defaults.
+// * Each value is marked with the file it came from, or as default.
+// -----------------------------------------------------------------------------------------------------------
+pub fn dump_config(loaded: &LoadedConfig) -> String {
+    let mut value = Value::try_from(&loaded.config).unwrap_or(Value::Table(Map::new()));
+
+    let mut key_bindings: BTreeMap<String, Vec<String>> = DEFAULT_KEY_BINDINGS.entries()
+        .map(|(&cmd, &key_strs)| (cmd.to_string(), key_strs.iter().map(|&key_str| key_str.to_string()).collect()))
+        .collect();
+    for (cmd, key_strs) in loaded.config.key_bindings.iter() {
+        key_bindings.insert(cmd.clone(), key_strs.key_strs().iter().map(|&key_str| key_str.to_string()).collect());
+    }
+    if let Some(table) = value.as_table_mut() {
+        table.insert("key-bindings".to_string(), Value::try_from(key_bindings).unwrap_or(Value::Table(Map::new())));
+    }
+
+    let mut dump = String::from("# Resolved config, each value marked with where it came from.\n");
+    dump_table(loaded, &value, &mut Vec::new(), &mut dump);
+    dump
+}
+
+// Values of table first, then its sub-tables under headers.
+fn dump_table(loaded: &LoadedConfig, value: &Value, key_path: &mut Vec<String>, dump: &mut String) {
+    let table = match value.as_table() { Some(table) => table, None => { return; } };
+    if !key_path.is_empty() {
+        dump.push_str(&format!("\n[{}]\n", fmt_key_path(key_path)));
+    }
+    for (key, value) in table.iter().filter(|(_, value)| !value.is_table()) {
+        key_path.push(key.clone());
+        let origin = match loaded.origin(key_path) {
+            Some(path) => format!("from {}", path.display()),
+            None => "default".to_string()
+        };
+        dump.push_str(&format!("{} = {} # {}\n", fmt_key_path(&key_path[key_path.len() - 1..]), value, origin));
+        key_path.pop();
+    }
+    for (key, value) in table.iter().filter(|(_, value)| value.is_table()) {
+        key_path.push(key.clone());
+        dump_table(loaded, value, key_path, dump);
+        key_path.pop();
+    }
+}
+
+// Write commented config with all defaults, refusing to overwrite a file.
+pub fn write_starter_config(path: &Path) -> Result<()> {
+    if path.exists() {
+        bail!("{} already exists, not overwriting", path.display());
+    }
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+    fs::write(path, STARTER_CONFIG)?;
+    Ok(())
+}
+
+fn resolve_include(including: &Path, include: &str) -> PathBuf {
+    if let (Some(rest), Some(home)) = (include.strip_prefix("~/"), env::var_os("HOME")) {
+        return PathBuf::from(home).join(rest);
+    }
+    match including.parent() {
+        Some(dir) => dir.join(include),
+        None => PathBuf::from(include)
+    }
+}
+
+// Merge tables key by key, anything else in over replaces base.
+fn merge_toml(base: &mut Value, over: Value) {
+    match (base, over) {
+        (Value::Table(base), Value::Table(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_toml(base_value, value),
+                    None => { base.insert(key, value); }
+                }
+            }
+        },
+        (base, over) => { *base = over; }
+    }
+}
+
+fn deserialize_toml(value: Value) -> Result<Config, toml::de::Error> {
+    match value.try_into() {
         Ok(toml) => { Ok(toml) }
         Err(e) => { 
             error!("Error deserializing: {:?}", e); 
@@ -45,17 +466,90 @@ fn deserialize_toml(s: &str) -> Result<Config, toml::de::Error> {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{ collections::HashMap, env, ffi::OsString, fs, path::{ Path, PathBuf } };
+
+    use super::{ Config, deserialize_toml, dump_config, load_config_from, load_layer, LoadedConfig, THEME_PRESETS };
+    use crate::jobs::{
+        util::dirs::{ config_candidates_from, ConfigRoots, ConfigSource },
+        InterfaceDes,
+        KeyBindingsDes,
+        ThemeDes,
+        TuiPrefsDes
+    };
 
-    use super::{ Config, deserialize_toml };
-    use crate::jobs::{ InterfaceDes, ThemeDes, TuiPrefsDes };
+    // Load config given with --config, without reading the host's system or user config.
+    fn load(file: &Path) -> LoadedConfig {
+        let roots = ConfigRoots {
+            system: file.with_file_name("System.toml"),
+            candidates: vec![(file.to_path_buf(), ConfigSource::CliFlag)]
+        };
+        load_config_from(&roots, Some(file)).unwrap()
+    }
+
+    // Test if config candidates follow --config, $RAVANA_CONFIG, $XDG_CONFIG_HOME, $HOME order.
+    #[test]
+    fn test_config_candidates() {
+        let vars = |var: &str| match var {
+            "RAVANA_CONFIG" => Some(OsString::from("/env/Config.toml")),
+            "XDG_CONFIG_HOME" => Some(OsString::from("/xdg")),
+            "HOME" => Some(OsString::from("/home/user")),
+            _ => None
+        };
+        let candidates = config_candidates_from(Some(Path::new("/cli/Config.toml")), vars);
+        let exp = vec![
+            (PathBuf::from("/cli/Config.toml"), ConfigSource::CliFlag),
+            (PathBuf::from("/env/Config.toml"), ConfigSource::EnvVar),
+            (PathBuf::from("/xdg/ravana/Config.toml"), ConfigSource::XdgConfigHome),
+            (PathBuf::from("/home/user/.config/ravana/Config.toml"), ConfigSource::HomeConfig),
+        ];
+        assert_eq!(candidates, exp);
+
+        let candidates = config_candidates_from(Some(Path::new("docs/.ravana")), |_| None);
+        assert_eq!(candidates[0], (PathBuf::from("docs/.ravana/Config.toml"), ConfigSource::CliFlag));
+    }
+
+    // Test if a missing or unreadable explicit config is an error, while others fall through.
+    #[test]
+    fn test_explicit_config() {
+        let dir = env::temp_dir().join(format!("ravana-test-explicit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let broken = dir.join("Broken.toml");
+        let user = dir.join("Config.toml");
+        fs::write(&broken, "leader = \"<C-w>\n").unwrap();
+        fs::write(&user, "leader = \"<C-x>\"\n").unwrap();
+        fs::write(dir.join("System.toml"), "persist-macros = true\n").unwrap();
+
+        let roots = |candidates: Vec<(PathBuf, ConfigSource)>| ConfigRoots { system: dir.join("System.toml"), candidates };
+        for source in [ConfigSource::CliFlag, ConfigSource::EnvVar].iter().copied() {
+            let missing = roots(vec![(dir.join("Missing.toml"), source), (user.clone(), ConfigSource::HomeConfig)]);
+            let e = load_config_from(&missing, None).err().unwrap();
+            assert_eq!(e.to_string(), format!("Config {} from {} not found", dir.join("Missing.toml").display(), source));
+
+            let invalid = roots(vec![(broken.clone(), source), (user.clone(), ConfigSource::HomeConfig)]);
+            assert!(load_config_from(&invalid, None).is_err());
+        }
+
+        let fallback = roots(vec![(broken.clone(), ConfigSource::XdgConfigHome), (user.clone(), ConfigSource::HomeConfig)]);
+        let loaded = load_config_from(&fallback, None).unwrap();
+        assert_eq!(loaded.config.leader, "<C-x>");
+        assert!(loaded.config.persist_macros);
+        assert_eq!(loaded.diagnostics.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
     // Test if deserialize_toml deserializes toml proper.
     #[test]
     fn test_deserialize_toml() {
-        let res_config: Config = deserialize_toml(r##"
+        let res_config: Config = deserialize_toml(toml::from_str(r##"
+            leader = "<C-w>"
+            key-timeout = 500
+            persist-macros = true
+            log-level = "debug"
+
             [key-bindings]
             app_quit = "abcdefghi"
+            "switch_page 2" = ["<C-b>", "gb"]
 
             [tui]
             interface.mouse-events-enable = false
@@ -69,18 +563,26 @@ mod tests {
             theme.post-header-bg = "#555555"
             theme.post-upvoted-fg = "#666666"
             theme.post-upvoted-bg = "#777777"
+            theme.post-downvoted-fg = "#777777"
+            theme.post-downvoted-bg = "#666666"
             theme.post-heading-fg = "#888888"
             theme.post-heading-bg = "#999999"
             theme.post-body-fg = "#000000"
             theme.post-body-bg = "#111111"
             theme.cmd-plt-fg = "#222222"
             theme.cmd-plt-bg = "#333333"
-        "##).unwrap();
+        "##).unwrap()).unwrap();
         // let mut exp_config = Config::default();
         // exp_config.key_bindings.app_quit = "ABCDEFGHIJ".to_owned();
         assert_eq!(res_config, Config {
+            theme: "default".to_owned(),
+            leader: "<C-w>".to_owned(),
+            key_timeout: 500,
+            persist_macros: true,
+            log_level: "debug".to_owned(),
             key_bindings: HashMap::from([
-                ("app_quit".to_owned(), "abcdefghi".to_owned())
+                ("app_quit".to_owned(), KeyBindingsDes::Single("abcdefghi".to_owned())),
+                ("switch_page 2".to_owned(), KeyBindingsDes::Multiple(vec!["<C-b>".to_owned(), "gb".to_owned()]))
             ]),
             tui: TuiPrefsDes {
                 interface: InterfaceDes {
@@ -96,6 +598,8 @@ mod tests {
                     post_header_bg: "#555555".to_string(),
                     post_upvoted_fg: "#666666".to_string(),
                     post_upvoted_bg: "#777777".to_string(),
+                    post_downvoted_fg: "#777777".to_string(),
+                    post_downvoted_bg: "#666666".to_string(),
                     post_heading_fg: "#888888".to_string(),
                     post_heading_bg: "#999999".to_string(),
                     post_body_fg: "#000000".to_string(),
@@ -106,4 +610,183 @@ mod tests {
             }
         });
     }
+
+    // * Test if a config setting a single field gets defaults for the rest.
+    // * Test if built-in defaults match the documented Config.toml.
+    #[test]
+    fn test_partial_config() {
+        let config = deserialize_toml(toml::from_str(r##"
+            tui.theme.highlight-fg = "#222222"
+        "##).unwrap()).unwrap();
+        let mut exp_config = Config::default();
+        exp_config.tui.theme.highlight_fg = "#222222".to_string();
+        assert_eq!(config, exp_config);
+
+        let docs_config = deserialize_toml(
+            toml::from_str(&fs::read_to_string("docs/.ravana/Config.toml").unwrap()).unwrap()
+        ).unwrap();
+        assert_eq!(docs_config.tui, Config::default().tui);
+        assert_eq!(docs_config.leader, Config::default().leader);
+        assert_eq!(docs_config.key_timeout, Config::default().key_timeout);
+        assert_eq!(docs_config.persist_macros, Config::default().persist_macros);
+        assert_eq!(docs_config.log_level, Config::default().log_level);
+    }
+
+    // * Test if theme files next to config are used over presets, with config colors over theme colors.
+    // * Test if presets are valid and the default one matches built-in defaults.
+    // * Test if unknown themes and bad theme colors are reported.
+    #[test]
+    fn test_load_theme() {
+        let dir = env::temp_dir().join(format!("ravana-test-theme-{}", std::process::id()));
+        fs::create_dir_all(dir.join("themes")).unwrap();
+        let file = dir.join("Config.toml");
+        fs::write(dir.join("themes/mine.toml"), r##"
+            highlight-fg = "red"
+            highlight-bg = "ansi:17"
+            post-body-fg = "#zzzzzz"
+        "##).unwrap();
+
+        fs::write(&file, r##"
+            theme = "mine"
+            tui.theme.highlight-bg = "#222222"
+        "##).unwrap();
+        let loaded = load(&file);
+        assert_eq!(loaded.config.tui.theme.highlight_fg, "red");
+        assert_eq!(loaded.config.tui.theme.highlight_bg, "#222222");
+        assert_eq!(loaded.config.tui.theme.post_body_fg, Config::default().tui.theme.post_body_fg);
+        let messages: Vec<String> = loaded.diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(messages, vec![format!(
+            "{}:4:13: error: tui.theme.post-body-fg: invalid color \"#zzzzzz\", expected #rrggbb, a color name or ansi:0 to ansi:255",
+            dir.join("themes/mine.toml").display())]);
+
+        for (name, _) in THEME_PRESETS.iter() {
+            fs::write(&file, format!("theme = \"{}\"", name)).unwrap();
+            let loaded = load(&file);
+            assert!(loaded.diagnostics.is_empty(), "preset {}: {}", name, loaded.diagnostics[0]);
+            if *name == "default" { assert_eq!(loaded.config, Config::default()); }
+        }
+
+        fs::write(&file, "theme = \"high-contrats\"\n").unwrap();
+        let loaded = load(&file);
+        assert_eq!(loaded.config.tui, Config::default().tui);
+        assert_eq!(loaded.diagnostics[0].to_string(), format!(
+            "{}:1:1: error: theme: unknown theme \"high-contrats\" ( did you mean high-contrast? )", file.display()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // * Test if includes are merged under the including file, in order.
+    // * Test if include cycles and bad includes are errors.
+    #[test]
+    fn test_load_layer() {
+        let dir = env::temp_dir().join(format!("ravana-test-layers-{}", std::process::id()));
+        fs::create_dir_all(dir.join("team")).unwrap();
+        fs::write(dir.join("team/base.toml"), r##"
+            leader = "<C-w>"
+            [tui.theme]
+            highlight-fg = "#111111"
+            highlight-bg = "#111111"
+        "##).unwrap();
+        fs::write(dir.join("colors.toml"), r##"
+            tui.theme.highlight-bg = "#222222"
+        "##).unwrap();
+        fs::write(dir.join("Config.toml"), r##"
+            include = ["team/base.toml", "colors.toml"]
+            persist-macros = true
+            tui.theme.highlight-fg = "#333333"
+        "##).unwrap();
+        fs::write(dir.join("cycle.toml"), r##"include = ["cycle.toml"]"##).unwrap();
+        fs::write(dir.join("bad.toml"), r##"include = "colors.toml""##).unwrap();
+
+        let config = deserialize_toml(load_layer(&dir.join("Config.toml"), &mut Vec::new(), &mut Vec::new(), &mut Vec::new()).unwrap()).unwrap();
+        let mut exp_config = Config::default();
+        exp_config.leader = "<C-w>".to_string();
+        exp_config.persist_macros = true;
+        exp_config.tui.theme.highlight_fg = "#333333".to_string();
+        exp_config.tui.theme.highlight_bg = "#222222".to_string();
+        assert_eq!(config, exp_config);
+
+        assert!(load_layer(&dir.join("cycle.toml"), &mut Vec::new(), &mut Vec::new(), &mut Vec::new()).is_err());
+        assert!(load_layer(&dir.join("bad.toml"), &mut Vec::new(), &mut Vec::new(), &mut Vec::new()).is_err());
+        assert!(load_layer(&dir.join("missing.toml"), &mut Vec::new(), &mut Vec::new(), &mut Vec::new()).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // * Test if unknown keys, wrong types and invalid colors are reported at their line and dropped.
+    // * Test if valid values in the same file are kept.
+    #[test]
+    fn test_layer_diagnostics() {
+        let dir = env::temp_dir().join(format!("ravana-test-diagnostics-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("Config.toml");
+        fs::write(&file, r##"leader = "<C-w>"
+persist-macros = "yes"
+log-level = "loud"
+[key-bindings]
+app_quit = 1
+[tui.theme]
+hilight-fg = "#222222"
+highlight-bg = "#22222"
+cmd-plt-fg = "#333333"
+"##).unwrap();
+
+        let mut files = Vec::new();
+        let mut diagnostics = Vec::new();
+        let config = deserialize_toml(load_layer(&file, &mut Vec::new(), &mut files, &mut diagnostics).unwrap()).unwrap();
+        let mut exp_config = Config::default();
+        exp_config.leader = "<C-w>".to_string();
+        exp_config.key_bindings = HashMap::new();
+        exp_config.tui.theme.cmd_plt_fg = "#333333".to_string();
+        assert_eq!(config, exp_config);
+        assert_eq!(files.len(), 1);
+
+        let messages: Vec<String> = diagnostics.iter()
+            .map(|d| d.to_string().replace(&file.display().to_string(), "Config.toml"))
+            .collect();
+        assert_eq!(messages, vec![
+            "Config.toml:5:1: error: key-bindings.app_quit: expected key-binding string or list of them, found integer",
+            "Config.toml:3:1: error: log-level: invalid log level \"loud\", expected off, error, warn, info, debug or trace",
+            "Config.toml:2:1: error: persist-macros: expected boolean, found string",
+            "Config.toml:8:1: error: tui.theme.highlight-bg: invalid color \"#22222\", expected #rrggbb, a color name or ansi:0 to ansi:255",
+            "Config.toml:7:1: warning: tui.theme.hilight-fg: unknown key ( did you mean highlight-fg? )",
+        ]);
+
+        fs::write(&file, "leader = \"<C-w>\"\npersist-macros = \n").unwrap();
+        let e = load_layer(&file, &mut Vec::new(), &mut Vec::new(), &mut Vec::new()).unwrap_err();
+        assert_eq!(e.line_col, Some((2, 18)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Test if dump has resolved values with their origin, and key-bindings merged over defaults.
+    #[test]
+    fn test_dump_config() {
+        let dir = env::temp_dir().join(format!("ravana-test-dump-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("Config.toml");
+        fs::write(&file, r##"
+            leader = "<C-w>"
+            [key-bindings]
+            app_quit = "ZZ"
+            "switch_page 2" = ["<C-b>", "gb"]
+        "##).unwrap();
+
+        let dump = dump_config(&load(&file));
+        let origin = format!("# from {}", file.display());
+        for exp_line in [
+            format!("leader = \"<C-w>\" {}", origin),
+            "persist-macros = false # default".to_string(),
+            "[key-bindings]".to_string(),
+            format!("app_quit = [\"ZZ\"] {}", origin),
+            format!("\"switch_page 2\" = [\"<C-b>\", \"gb\"] {}", origin),
+            "scroll_down = [\"j\", \"<Down>\"] # default".to_string(),
+            "[tui.interface]".to_string(),
+            "mouse-events-enable = true # default".to_string(),
+        ] {
+            assert!(dump.lines().any(|line| line == exp_line), "{} not in dump:\n{}", exp_line, dump);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use log::debug;
+use nix::{
+    sys::inotify::{ AddWatchFlags, InitFlags, Inotify, WatchDescriptor },
+    unistd::close
+};
+use std::{
+    os::unix::io::{ AsRawFd, RawFd },
+    path::{ Path, PathBuf }
+};
+
+// -----------------------------------------------------------------------------------------------------------
+// Watch config files for changes with inotify.
+// * Directories holding the files are watched, editors often save by replacing the file, which would end a
+//   watch on the file itself.
+// * Files need not exist yet, creating one in a watched directory is a change too.
+// * Non-blocking, poll fd() for readability, then check changed().
+// -----------------------------------------------------------------------------------------------------------
+pub struct ConfigWatcher {
+    inotify: Inotify,
+    dirs: Vec<(WatchDescriptor, PathBuf)>,
+    files: Vec<PathBuf>
+}
+
+impl ConfigWatcher {
+    // Watch files, skipping ones whose directory is not on disk.
+    pub fn new<I: IntoIterator<Item = PathBuf>>(files: I) -> Result<ConfigWatcher> {
+        let mut watcher = ConfigWatcher {
+            inotify: Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?,
+            dirs: Vec::new(),
+            files: Vec::new()
+        };
+
+        for file in files {
+            // Empty for files in the working directory.
+            let dir = file.parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+            let watch_dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir.as_path() };
+            if !watch_dir.is_dir() { continue; }
+            if !watcher.dirs.iter().any(|(_, watched)| *watched == dir) {
+                let wd = watcher.inotify.add_watch(watch_dir, AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO)?;
+                debug!("Watching {} for config changes.", watch_dir.display());
+                watcher.dirs.push((wd, dir));
+            }
+            if !watcher.watches(&file) { watcher.files.push(file); }
+        }
+        Ok(watcher)
+    }
+
+    pub fn fd(&self) -> RawFd {
+        self.inotify.as_raw_fd()
+    }
+
+    // Read pending events, true if any of them wrote a watched file.
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        while let Ok(events) = self.inotify.read_events() {
+            if events.is_empty() { break; }
+            for event in events {
+                let dir = self.dirs.iter().find(|(wd, _)| *wd == event.wd).map(|(_, dir)| dir);
+                if let (Some(dir), Some(name)) = (dir, event.name) {
+                    changed = changed || self.watches(&dir.join(name));
+                }
+            }
+        }
+        changed
+    }
+
+    fn watches(&self, path: &Path) -> bool {
+        self.files.iter().any(|file| file == path)
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        let _ = close(self.inotify.as_raw_fd());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ env, fs };
+
+    use super::ConfigWatcher;
+
+    // Test if writing, replacing or creating a watched file is a change, writing other files in its directory
+    // is not.
+    #[test]
+    fn test_config_watcher() {
+        let dir = env::temp_dir().join(format!("ravana-test-watcher-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("Config.toml");
+        fs::write(&file, "leader = \"<Space>\"").unwrap();
+
+        let watcher = ConfigWatcher::new(vec![file.clone(), dir.join("missing.toml"), dir.join("none/Config.toml")])
+            .unwrap();
+        assert!(!watcher.changed());
+
+        fs::write(dir.join("other.toml"), "").unwrap();
+        assert!(!watcher.changed());
+
+        fs::write(&file, "leader = \"<C-w>\"").unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        fs::write(dir.join("Config.toml.new"), "leader = \"<C-a>\"").unwrap();
+        fs::rename(dir.join("Config.toml.new"), &file).unwrap();
+        assert!(watcher.changed());
+
+        fs::write(dir.join("missing.toml"), "").unwrap();
+        assert!(watcher.changed());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
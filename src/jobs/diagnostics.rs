@@ -0,0 +1,240 @@
+use std::{ fmt, path::{ Path, PathBuf } };
+
+// -----------------------------------------------------------------------------------------------------------
+// Config diagnostics
+// * A problem found in config, pointing at the TOML key path and, when known, the file, line and column.
+// * Errors make the value fall back to the layer below it, warnings are informational.
+// -----------------------------------------------------------------------------------------------------------
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Option<PathBuf>,
+    // 1-based line and column.
+    pub line_col: Option<(usize, usize)>,
+    pub key_path: Vec<String>,
+    pub message: String,
+    pub suggestion: Option<String>
+}
+
+impl Diagnostic {
+    pub fn error(key_path: &[String], message: String) -> Diagnostic {
+        Diagnostic::new(Severity::Error, key_path, message)
+    }
+
+    pub fn warning(key_path: &[String], message: String) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, key_path, message)
+    }
+
+    fn new(severity: Severity, key_path: &[String], message: String) -> Diagnostic {
+        Diagnostic {
+            severity,
+            file: None,
+            line_col: None,
+            key_path: key_path.to_vec(),
+            message,
+            suggestion: None
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    // Point diagnostic at its key in file contents.
+    pub fn locate(&mut self, file: &Path, contents: &str) {
+        self.file = Some(file.to_path_buf());
+        if self.line_col.is_none() {
+            self.line_col = locate_key(contents, &self.key_path);
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    // e.g. /home/user/.config/ravana/Config.toml:12:1: warning: tui.theme.hilight-fg: unknown key ( did you mean highlight-fg? )
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+            if let Some((line, col)) = self.line_col {
+                write!(f, "{}:{}:", line, col)?;
+            }
+            write!(f, " ")?;
+        }
+        match self.severity {
+            Severity::Error => write!(f, "error: ")?,
+            Severity::Warning => write!(f, "warning: ")?
+        }
+        if !self.key_path.is_empty() {
+            write!(f, "{}: ", fmt_key_path(&self.key_path))?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " ( did you mean {}? )", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+// Key path as written in TOML, quoting keys that are not bare, e.g. key-bindings."switch_page 2".
+pub fn fmt_key_path(key_path: &[String]) -> String {
+    key_path.iter()
+        .map(|key| {
+            if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                key.clone()
+            } else {
+                format!("\"{}\"", key)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+// Closest candidate to a mistyped word, if close enough to be a likely typo.
+pub fn suggest<'a, I: IntoIterator<Item = &'a str>>(word: &str, candidates: I) -> Option<String> {
+    let scored: Vec<(usize, &str)> = candidates.into_iter()
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .collect();
+    if scored.iter().any(|(distance, _)| *distance == 0) { return None; }
+    scored.into_iter()
+        .filter(|(distance, candidate)| *distance <= 2.max(candidate.len() / 3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            cur.push((prev[j] + if ca == *cb { 0 } else { 1 }).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Find line and column of key path in TOML contents.
+// * Follows [table] headers and dotted keys, keys in inline tables are not found.
+// -----------------------------------------------------------------------------------------------------------
+pub fn locate_key(contents: &str, key_path: &[String]) -> Option<(usize, usize)> {
+    let mut table: Vec<String> = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let col = line.len() - trimmed.len() + 1;
+        if trimmed.starts_with('#') || trimmed.is_empty() { continue; }
+
+        if trimmed.starts_with('[') {
+            let header = trimmed.trim_start_matches('[');
+            if let Some(end) = find_unquoted(header, ']') {
+                table = split_dotted(&header[..end]);
+                if table == key_path { return Some((i + 1, col)); }
+            }
+            continue;
+        }
+
+        if let Some(eq) = find_unquoted(trimmed, '=') {
+            let mut full = table.clone();
+            full.extend(split_dotted(&trimmed[..eq]));
+            if full == key_path { return Some((i + 1, col)); }
+        }
+    }
+    None
+}
+
+fn find_unquoted(s: &str, target: char) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => { quote = None; },
+            Some(_) => {  },
+            None if c == '"' || c == '\'' => { quote = Some(c); },
+            None if c == target => { return Some(i); },
+            None => {  }
+        }
+    }
+    None
+}
+
+fn split_dotted(s: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut rest = s;
+    loop {
+        match find_unquoted(rest, '.') {
+            Some(dot) => {
+                keys.push(unquote(&rest[..dot]));
+                rest = &rest[dot + 1..];
+            },
+            None => {
+                keys.push(unquote(rest));
+                break;
+            }
+        }
+    }
+    keys
+}
+
+fn unquote(key: &str) -> String {
+    key.trim().trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{ locate_key, suggest, Diagnostic };
+
+    fn path(s: &[&str]) -> Vec<String> {
+        s.iter().map(|key| key.to_string()).collect()
+    }
+
+    // Test if keys are found under table headers, as dotted keys and as quoted keys.
+    #[test]
+    fn test_locate_key() {
+        let contents = r##"
+leader = "<C-w>"
+[key-bindings]
+  "switch_page 2" = "gb"
+[tui]
+theme.highlight-fg = "#222222" # theme.page-bar-fg = 1
+[tui.interface]
+mouse-events-enable = true
+        "##;
+        assert_eq!(locate_key(contents, &path(&["leader"])), Some((2, 1)));
+        assert_eq!(locate_key(contents, &path(&["key-bindings", "switch_page 2"])), Some((4, 3)));
+        assert_eq!(locate_key(contents, &path(&["tui", "theme", "highlight-fg"])), Some((6, 1)));
+        assert_eq!(locate_key(contents, &path(&["tui", "interface"])), Some((7, 1)));
+        assert_eq!(locate_key(contents, &path(&["tui", "interface", "mouse-events-enable"])), Some((8, 1)));
+        assert_eq!(locate_key(contents, &path(&["tui", "theme", "page-bar-fg"])), None);
+    }
+
+    // Test if typos get the closest candidate and unrelated words get none.
+    #[test]
+    fn test_suggest() {
+        let candidates = ["highlight-fg", "highlight-bg", "page-bar-fg"];
+        assert_eq!(suggest("hilight-fg", candidates), Some("highlight-fg".to_string()));
+        assert_eq!(suggest("page_bar_fg", candidates), Some("page-bar-fg".to_string()));
+        assert_eq!(suggest("cursor", candidates), None);
+        assert_eq!(suggest("highlight-fg", candidates), None);
+    }
+
+    // Test if diagnostics show file, position, key path and suggestion.
+    #[test]
+    fn test_diagnostic_display() {
+        let mut diagnostic = Diagnostic::warning(&path(&["key-bindings", "switch_page 2"]), "unknown command".to_string());
+        assert_eq!(diagnostic.to_string(), "warning: key-bindings.\"switch_page 2\": unknown command");
+
+        diagnostic.locate(Path::new("Config.toml"), "[key-bindings]\n\"switch_page 2\" = \"gb\"");
+        diagnostic.suggestion = Some("switch_page".to_string());
+        assert_eq!(diagnostic.to_string(),
+            "Config.toml:2:1: warning: key-bindings.\"switch_page 2\": unknown command ( did you mean switch_page? )");
+    }
+}
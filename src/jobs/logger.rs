@@ -0,0 +1,266 @@
+use anyhow::{ anyhow, Result };
+use log::{ Level, LevelFilter, Log, Metadata, Record };
+use std::{
+    collections::VecDeque,
+    env,
+    fmt,
+    fs::{ self, File, OpenOptions },
+    io::{ self, Write },
+    path::{ Path, PathBuf },
+    sync::{ atomic::{ AtomicBool, Ordering }, Mutex },
+    time::{ SystemTime, UNIX_EPOCH }
+};
+
+use crate::def::app::{ LOG_FILES_KEPT, MAX_LOG_FILE_SIZE, MAX_RECENT_LOG_RECORDS };
+
+// Log record kept in memory, for :messages.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogRecord {
+    pub time: SystemTime,
+    pub level: Level,
+    pub target: String,
+    pub message: String
+}
+
+// e.g. 2026-10-19 12:00:00 WARN  ravana::jobs::config: Skipping system config
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:<5} {}: {}", fmt_time(self.time), self.level, self.target, self.message)
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Logger for the TUI, which owns the terminal so logs cannot go to stderr.
+// * Records are appended to a log file, rotated when it grows past MAX_LOG_FILE_SIZE.
+// * Recent records are kept in memory.
+// -----------------------------------------------------------------------------------------------------------
+struct FileLogger {
+    file: Mutex<Option<LogFile>>
+}
+
+static LOGGER: FileLogger = FileLogger { file: Mutex::new(None) };
+static RECENT: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+// Whether level was set by RUST_LOG, which overrides log-level in config.
+static ENV_LEVEL: AtomicBool = AtomicBool::new(false);
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) { return; }
+        let record = LogRecord {
+            time: SystemTime::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string()
+        };
+
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                // Nowhere left to report failing to log.
+                let _ = file.write_line(&record.to_string());
+            }
+        }
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() >= MAX_RECENT_LOG_RECORDS { recent.pop_front(); }
+            recent.push_back(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() { let _ = file.file.flush(); }
+        }
+    }
+}
+
+// Log file rotated as path, path.1, ... path.LOG_FILES_KEPT, newest first.
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64
+}
+
+impl LogFile {
+    fn open(path: &Path, max_size: u64) -> io::Result<LogFile> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(LogFile { path: path.to_path_buf(), file, size, max_size })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    // Shift rotated files up by one, dropping the oldest, and start a new file.
+    fn rotate(&mut self) -> io::Result<()> {
+        for i in (1..LOG_FILES_KEPT).rev() {
+            let from = rotated_path(&self.path, i);
+            if from.exists() { fs::rename(from, rotated_path(&self.path, i + 1))?; }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        *self = LogFile::open(&self.path.clone(), self.max_size)?;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, i: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", i));
+    PathBuf::from(rotated)
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Log to file at path, keeping only recent records in memory if None.
+// * Level is RUST_LOG if it holds a level ( e.g. debug ), else info until set_log_level.
+// -----------------------------------------------------------------------------------------------------------
+pub fn init_file_logger(path: Option<&Path>) -> Result<()> {
+    let file = match path {
+        Some(path) => Some(LogFile::open(path, MAX_LOG_FILE_SIZE)?),
+        None => None
+    };
+    *LOGGER.file.lock().map_err(|_| anyhow!("Unable to lock log file."))? = file;
+
+    log::set_logger(&LOGGER).map_err(|e| anyhow!("{}", e))?;
+    match env::var("RUST_LOG").ok().and_then(|level| level.parse::<LevelFilter>().ok()) {
+        Some(level) => {
+            ENV_LEVEL.store(true, Ordering::Relaxed);
+            log::set_max_level(level);
+        },
+        None => { log::set_max_level(LevelFilter::Info); }
+    }
+    Ok(())
+}
+
+// Set level from config, unless RUST_LOG set it.
+pub fn set_log_level(level: LevelFilter) {
+    if !ENV_LEVEL.load(Ordering::Relaxed) {
+        log::set_max_level(level);
+    }
+}
+
+// File logged to, None if logging to memory only.
+pub fn log_file_path() -> Option<PathBuf> {
+    LOGGER.file.lock().ok()?.as_ref().map(|file| file.path.clone())
+}
+
+// Records kept in memory, oldest first.
+pub fn recent_records() -> Vec<LogRecord> {
+    RECENT.lock().map(|recent| recent.iter().cloned().collect()).unwrap_or_default()
+}
+
+// Records at level or more severe, containing search ( ignoring case ) if given.
+pub fn filter_records<'r>(records: &'r [LogRecord], level: LevelFilter, search: Option<&str>) -> Vec<&'r LogRecord> {
+    let search = search.map(|search| search.to_lowercase());
+    records.iter()
+        .filter(|record| record.level <= level)
+        .filter(|record| match &search {
+            Some(search) => record.message.to_lowercase().contains(search) || record.target.to_lowercase().contains(search),
+            None => true
+        })
+        .collect()
+}
+
+// Text of :messages page, newest record first.
+pub fn messages_text(level: LevelFilter, search: Option<&str>) -> String {
+    let records = recent_records();
+    let filtered = filter_records(&records, level, search);
+
+    let mut text = format!("# {} log message(s) at {} and above", filtered.len(), level);
+    if let Some(search) = search { text.push_str(&format!(" containing \"{}\"", search)); }
+    text.push_str(", newest first.\n");
+    if let Some(log_file) = log_file_path() { text.push_str(&format!("# Full log in {}\n", log_file.display())); }
+    for record in filtered.iter().rev() {
+        text.push_str(&format!("{}\n", record));
+    }
+    text
+}
+
+// Time as YYYY-MM-DD HH:MM:SS in UTC.
+pub fn fmt_time(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, secs) = ((secs / 86400) as i64, secs % 86400);
+
+    // Civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use log::{ Level, LevelFilter };
+    use std::{ env, fs, time::{ Duration, UNIX_EPOCH } };
+
+    use super::{ filter_records, fmt_time, rotated_path, LogFile, LogRecord };
+
+    // Test if records are filtered by level and case-insensitive search in message or target.
+    #[test]
+    fn test_filter_records() {
+        let record = |level: Level, target: &str, message: &str| LogRecord {
+            time: UNIX_EPOCH,
+            level,
+            target: target.to_string(),
+            message: message.to_string()
+        };
+        let records = vec![
+            record(Level::Error, "ravana::api", "Request timed out"),
+            record(Level::Warn, "ravana::jobs::config", "Skipping system config"),
+            record(Level::Info, "ravana::api", "Using account default."),
+            record(Level::Debug, "ravana::input", "Key pressed"),
+        ];
+
+        assert_eq!(filter_records(&records, LevelFilter::Trace, None).len(), 4);
+        assert_eq!(filter_records(&records, LevelFilter::Warn, None), vec![&records[0], &records[1]]);
+        assert_eq!(filter_records(&records, LevelFilter::Trace, Some("TIMED")), vec![&records[0]]);
+        assert_eq!(filter_records(&records, LevelFilter::Info, Some("api")), vec![&records[0], &records[2]]);
+        assert!(filter_records(&records, LevelFilter::Off, None).is_empty());
+
+        assert_eq!(records[1].to_string(), "1970-01-01 00:00:00 WARN  ravana::jobs::config: Skipping system config");
+    }
+
+    #[test]
+    fn test_fmt_time() {
+        assert_eq!(fmt_time(UNIX_EPOCH), "1970-01-01 00:00:00");
+        assert_eq!(fmt_time(UNIX_EPOCH + Duration::from_secs(951827696)), "2000-02-29 12:34:56");
+        assert_eq!(fmt_time(UNIX_EPOCH + Duration::from_secs(1792368000)), "2026-10-19 00:00:00");
+    }
+
+    // Test if log file rotates past its max size, keeping LOG_FILES_KEPT rotated files.
+    #[test]
+    fn test_log_file_rotation() {
+        let dir = env::temp_dir().join(format!("ravana-test-log-{}", std::process::id()));
+        let path = dir.join("ravana.log");
+        let mut log_file = LogFile::open(&path, 10).unwrap();
+        for line in ["line 1", "line 2", "line 3", "line 4", "line 5"] {
+            log_file.write_line(line).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 5\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "line 4\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 3)).unwrap(), "line 2\n");
+        assert!(!rotated_path(&path, 4).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
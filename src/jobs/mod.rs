@@ -1,4 +1,11 @@
 mod util;
+pub mod compose;
 pub mod config;
+pub mod config_watcher;
+pub mod diagnostics;
+pub mod logger;
 
-pub use util::config::{ Config, InterfaceDes, ThemeDes , TuiPrefsDes };
+pub use config_watcher::ConfigWatcher;
+pub use diagnostics::Diagnostic;
+pub use util::dirs::{ state_dir, user_config_path, ConfigRoots };
+pub use util::config::{ Config, InterfaceDes, KeyBindingsDes, ThemeDes , TuiPrefsDes };
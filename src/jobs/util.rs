@@ -1,12 +1,17 @@
 pub mod config {
-    use serde::Deserialize;
+    use serde::{ Deserialize, Serialize };
     use std::collections::HashMap;
 
-    use crate::input::DEFAULT_KEY_BINDINGS;
+    use crate::{ def::app::{ DEFAULT_KEY_TIMEOUT, DEFAULT_LEADER, DEFAULT_THEME }, input::DEFAULT_KEY_BINDINGS };
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Every field is optional, missing fields take values from Default.
+    // * Defaults match docs/.ravana/Config.toml.
+    // -------------------------------------------------------------------------------------------------------
 
     // Theme deserialized.
-    #[derive(Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename_all(deserialize = "kebab-case"))]
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+    #[serde(default, rename_all = "kebab-case")]
     pub struct ThemeDes {
         pub highlight_fg: String,
         pub highlight_bg: String,
@@ -17,6 +22,8 @@ pub mod config {
         pub post_header_bg: String,
         pub post_upvoted_fg: String,
         pub post_upvoted_bg: String,
+        pub post_downvoted_fg: String,
+        pub post_downvoted_bg: String,
         pub post_heading_fg: String,
         pub post_heading_bg: String,
         pub post_body_fg: String,
@@ -26,63 +33,233 @@ pub mod config {
     }
 
     // Interface deserialized.
-    #[derive(Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename_all(deserialize = "kebab-case"))]
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+    #[serde(default, rename_all = "kebab-case")]
     pub struct InterfaceDes {
         pub mouse_events_enable: bool
     }
 
     // TUI Prefs deserialized.
-    #[derive(Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename_all(deserialize = "kebab-case"))]
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Default)]
+    #[serde(default, rename_all = "kebab-case")]
     pub struct TuiPrefsDes {
         pub interface: InterfaceDes,
         pub theme: ThemeDes
     }
 
+    // Key-binding(s) of a command line deserialized, either a single key-binding string or a list of them.
+    // Empty string or empty list unbinds the command.
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+    #[serde(untagged)]
+    pub enum KeyBindingsDes {
+        Single(String),
+        Multiple(Vec<String>)
+    }
+
+    impl ThemeDes {
+        // Colors by key in config, e.g. post-body-fg.
+        pub fn colors(&self) -> [(&'static str, &String); 17] {
+            [
+                ("highlight-fg", &self.highlight_fg),
+                ("highlight-bg", &self.highlight_bg),
+                ("page-bar-fg", &self.page_bar_fg),
+                ("page-bar-bg", &self.page_bar_bg),
+                ("page-bar-current-bg", &self.page_bar_current_bg),
+                ("post-header-fg", &self.post_header_fg),
+                ("post-header-bg", &self.post_header_bg),
+                ("post-upvoted-fg", &self.post_upvoted_fg),
+                ("post-upvoted-bg", &self.post_upvoted_bg),
+                ("post-downvoted-fg", &self.post_downvoted_fg),
+                ("post-downvoted-bg", &self.post_downvoted_bg),
+                ("post-heading-fg", &self.post_heading_fg),
+                ("post-heading-bg", &self.post_heading_bg),
+                ("post-body-fg", &self.post_body_fg),
+                ("post-body-bg", &self.post_body_bg),
+                ("cmd-plt-fg", &self.cmd_plt_fg),
+                ("cmd-plt-bg", &self.cmd_plt_bg)
+            ]
+        }
+    }
+
+    impl KeyBindingsDes {
+        // Key-binding strings, skipping empty ones.
+        pub fn key_strs(&self) -> Vec<&str> {
+            match self {
+                KeyBindingsDes::Single(s) => vec![s.as_str()],
+                KeyBindingsDes::Multiple(v) => v.iter().map(|s| s.as_str()).collect()
+            }.into_iter().filter(|s| !s.is_empty()).collect()
+        }
+    }
+
     // Config deserialized.
-    #[derive(Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename_all(deserialize = "kebab-case"))]
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+    #[serde(default, rename_all = "kebab-case")]
     pub struct Config {
-        pub key_bindings: HashMap<String, String>,
+        // Theme name, see THEME_PRESETS. Colors under tui.theme override it.
+        pub theme: String,
+        // Key combination replacing <leader> in key-bindings.
+        pub leader: String,
+        // Milliseconds to wait for the rest of a key-binding, 0 waits forever.
+        pub key_timeout: u64,
+        // Keep recorded macros across sessions.
+        pub persist_macros: bool,
+        // Level of TUI log: off, error, warn, info, debug or trace.
+        pub log_level: String,
+        // Command line ( command name with optional arguments ) -> key-binding(s).
+        // Merged over DEFAULT_KEY_BINDINGS per command line.
+        pub key_bindings: HashMap<String, KeyBindingsDes>,
         pub tui: TuiPrefsDes 
     }
     
-    // Config.toml is not found
     impl Default for Config {
         fn default() -> Config {
-            let mut default_key_bindings: HashMap<String, String> = HashMap::new();
+            let mut default_key_bindings: HashMap<String, KeyBindingsDes> = HashMap::new();
 
             // Load default key bindings specified in /input.
-            for (&key, &value) in DEFAULT_KEY_BINDINGS.entries() {
-                default_key_bindings.insert(key.to_owned(), value.to_owned());
+            for (&key, &values) in DEFAULT_KEY_BINDINGS.entries() {
+                default_key_bindings.insert(
+                    key.to_owned(),
+                    KeyBindingsDes::Multiple(values.iter().map(|&v| v.to_owned()).collect())
+                );
             }
 
             Config {
+                theme: DEFAULT_THEME.to_string(),
+                leader: DEFAULT_LEADER.to_string(),
+                key_timeout: DEFAULT_KEY_TIMEOUT,
+                persist_macros: false,
+                log_level: "info".to_string(),
                 key_bindings: default_key_bindings,
-                tui: TuiPrefsDes {
-                    interface: InterfaceDes {
-                        mouse_events_enable: true
-                    },
-                    theme: ThemeDes {
-                        highlight_fg: "#111111".to_string(),
-                        highlight_bg: "#111111".to_string(),
-                        page_bar_fg: "#111111".to_string(),
-                        page_bar_bg: "#111111".to_string(),
-                        page_bar_current_bg: "#111111".to_string(),
-                        post_header_fg: "#111111".to_string(), 
-                        post_header_bg: "#111111".to_string(),
-                        post_upvoted_fg: "#111111".to_string(),
-                        post_upvoted_bg: "#111111".to_string(),
-                        post_heading_fg: "#111111".to_string(),
-                        post_heading_bg: "#111111".to_string(),
-                        post_body_fg: "#111111".to_string(),
-                        post_body_bg: "#111111".to_string(),
-                        cmd_plt_fg: "#111111".to_string(),
-                        cmd_plt_bg: "#111111".to_string(),
-                    }
-                }
+                tui: TuiPrefsDes::default()
+            }
+        }
+    }
+
+    impl Default for InterfaceDes {
+        fn default() -> InterfaceDes {
+            InterfaceDes {
+                mouse_events_enable: true
             }
         }
     }
+
+    impl Default for ThemeDes {
+        fn default() -> ThemeDes {
+            ThemeDes {
+                highlight_fg: "#030F04".to_string(),
+                highlight_bg: "#0df93f".to_string(),
+                page_bar_fg: "#3922e4".to_string(),
+                page_bar_bg: "#a0e422".to_string(),
+                page_bar_current_bg: "#ffffff".to_string(),
+                post_header_fg: "#030F04".to_string(), 
+                post_header_bg: "#0df93f".to_string(),
+                post_upvoted_fg: "#fafafa".to_string(),
+                post_upvoted_bg: "#060303".to_string(),
+                post_downvoted_fg: "#fafafa".to_string(),
+                post_downvoted_bg: "#1f3fbf".to_string(),
+                post_heading_fg: "#030F04".to_string(),
+                post_heading_bg: "#0df93f".to_string(),
+                post_body_fg: "#000000".to_string(),
+                post_body_bg: "#f000ff".to_string(),
+                cmd_plt_fg: "#9fe2bf".to_string(),
+                cmd_plt_bg: "#000000".to_string(),
+            }
+        }
+    }
+}
+
+pub mod dirs {
+    use std::{ env, ffi::OsString, fmt, path::{ Path, PathBuf } };
+
+    use crate::def::app::{ CONFIG_DIR_NAME, CONFIG_ENV_VAR, CONFIG_FILE_NAME, STATE_DIR_NAME, SYSTEM_CONFIG_PATH };
+
+    // Where a config file candidate came from.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum ConfigSource {
+        CliFlag,
+        EnvVar,
+        XdgConfigHome,
+        HomeConfig
+    }
+
+    impl ConfigSource {
+        // Paths asked for by the user, which must load rather than fall back.
+        pub fn is_explicit(self) -> bool {
+            matches!(self, ConfigSource::CliFlag | ConfigSource::EnvVar)
+        }
+    }
+
+    impl fmt::Display for ConfigSource {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(match self {
+                ConfigSource::CliFlag => "--config",
+                ConfigSource::EnvVar => "$RAVANA_CONFIG",
+                ConfigSource::XdgConfigHome => "$XDG_CONFIG_HOME",
+                ConfigSource::HomeConfig => "$HOME/.config"
+            })
+        }
+    }
+
+    // Files config is looked up in: system config, then user config candidates.
+    pub struct ConfigRoots {
+        pub system: PathBuf,
+        pub candidates: Vec<(PathBuf, ConfigSource)>
+    }
+
+    impl ConfigRoots {
+        // Roots from SYSTEM_CONFIG_PATH and the environment.
+        pub fn from_env(cli_path: Option<&Path>) -> ConfigRoots {
+            ConfigRoots {
+                system: PathBuf::from(SYSTEM_CONFIG_PATH),
+                candidates: config_candidates(cli_path)
+            }
+        }
+    }
+
+    // Config file candidates in order of precedence, each with where it came from.
+    pub fn config_candidates(cli_path: Option<&Path>) -> Vec<(PathBuf, ConfigSource)> {
+        config_candidates_from(cli_path, |var| env::var_os(var).filter(|val| !val.is_empty()))
+    }
+
+    pub(crate) fn config_candidates_from<F>(cli_path: Option<&Path>, var: F) -> Vec<(PathBuf, ConfigSource)>
+        where F: Fn(&str) -> Option<OsString> {
+
+        // Explicit paths can be the file itself or the directory holding it.
+        let file_or_dir = |path: PathBuf| {
+            if path.is_dir() { path.join(CONFIG_FILE_NAME) } else { path }
+        };
+
+        let mut candidates = Vec::new();
+        if let Some(path) = cli_path {
+            candidates.push((file_or_dir(path.to_path_buf()), ConfigSource::CliFlag));
+        }
+        if let Some(path) = var(CONFIG_ENV_VAR) {
+            candidates.push((file_or_dir(PathBuf::from(path)), ConfigSource::EnvVar));
+        }
+        if let Some(dir) = var("XDG_CONFIG_HOME") {
+            candidates.push((PathBuf::from(dir).join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME), ConfigSource::XdgConfigHome));
+        }
+        if let Some(dir) = var("HOME") {
+            candidates.push((PathBuf::from(dir).join(".config").join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME), ConfigSource::HomeConfig));
+        }
+        candidates
+    }
+
+    // Config file written by ravana config init: $XDG_CONFIG_HOME/ravana, falling back to ~/.config/ravana.
+    pub fn user_config_path() -> Option<PathBuf> {
+        config_candidates_from(None, |var| env::var_os(var).filter(|val| !val.is_empty()))
+            .into_iter()
+            .find(|(_, source)| matches!(source, ConfigSource::XdgConfigHome | ConfigSource::HomeConfig))
+            .map(|(path, _)| path)
+    }
+
+    // State directory: $XDG_STATE_HOME/ravana, falling back to ~/.local/state/ravana.
+    pub fn state_dir() -> Option<PathBuf> {
+        match env::var_os("XDG_STATE_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => Some(PathBuf::from(dir).join(STATE_DIR_NAME)),
+            None => env::var_os("HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| PathBuf::from(dir).join(".local/state").join(STATE_DIR_NAME))
+        }
+    }
 }
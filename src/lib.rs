@@ -1,3 +1,5 @@
+pub mod api;
+pub mod cli;
 pub mod def;
 pub mod events;
 pub mod input;
@@ -1,6 +1,8 @@
 use env_logger;
+use std::{ fs::File, process };
 
-//mod api;
+mod api;
+mod cli;
 mod def;
 mod tui;
 mod events;
@@ -8,7 +10,82 @@ mod jobs;
 mod input;
 mod tools;
 
+use cli::{ parse_args, Cli, Cmd, USAGE };
+use def::app::LOG_FILE_NAME;
+use jobs::{ logger::init_file_logger, state_dir };
+
 fn main(){
-    env_logger::init();
-    events::lifecycle::ravana().expect("Main loop error.");
+    // Parse arguments before anything else, errors print usage without starting TUI.
+    let cli = match parse_args(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    // TUI owns the terminal, it logs to file. Other commands log to stderr unless --log-file is given.
+    if let Cmd::Tui(_) = cli.cmd {
+        let log_file = cli.log_file.clone().or_else(|| state_dir().map(|dir| dir.join(LOG_FILE_NAME)));
+        if log_file.is_none() {
+            eprintln!("warning: no state directory found, set $XDG_STATE_HOME or $HOME to keep logs");
+        }
+        if let Err(e) = init_file_logger(log_file.as_deref()) {
+            eprintln!("error: unable to open log file: {}", e);
+            process::exit(1);
+        }
+    } else {
+        init_stderr_logger(&cli);
+    }
+
+    let config_path = cli.config.as_deref();
+    match &cli.cmd {
+        Cmd::Help => { println!("{}", USAGE); },
+        Cmd::Version => { println!("ravana {}", env!("CARGO_PKG_VERSION")); },
+        Cmd::CheckConfig => {
+            process::exit(if events::lifecycle::check_config(config_path) { 0 } else { 1 });
+        },
+        Cmd::ConfigDump => {
+            if let Err(e) = events::lifecycle::print_config(config_path) {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+        },
+        Cmd::ConfigInit(path) => {
+            if let Err(e) = events::lifecycle::init_config(path.as_deref()) {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+        },
+        Cmd::Login | Cmd::Logout => {
+            let res = if cli.cmd == Cmd::Login { events::lifecycle::login(&cli.account) }
+                else { events::lifecycle::logout(&cli.account) };
+            if let Err(e) = res {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+        },
+        Cmd::Tui(target) => {
+            // TUI is stopped by now, error can go to stderr.
+            if let Err(e) = events::lifecycle::ravana(config_path, target.as_ref(), &cli.account) {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+        }
+    }
 }   
+
+// Log to stderr, or to --log-file if given.
+fn init_stderr_logger(cli: &Cli) {
+    let mut logger = env_logger::Builder::from_default_env();
+    if let Some(log_file) = &cli.log_file {
+        match File::create(log_file) {
+            Ok(file) => { logger.target(env_logger::Target::Pipe(Box::new(file))); },
+            Err(e) => {
+                eprintln!("error: unable to open log file {}: {}", log_file.display(), e);
+                process::exit(1);
+            }
+        }
+    }
+    logger.init();
+}
@@ -1,52 +1,100 @@
-use anyhow::{ anyhow,  Context, Result };
+use anyhow::{ anyhow, bail, Context, Result };
 use libnotcurses_sys::{
-    c_api::ncreader_offer_input,
     Nc,
-    NcInput,
     NcMiceEvents,
     NcPlane,
     NcPlaneOptions
 };
-use log::{ error, info };
-use std::sync::{ Arc, Mutex };
+use log::{ debug, error, info, warn };
+use std::{ fs, os::unix::io::RawFd, path::{ Path, PathBuf }, sync::{ Arc, Mutex }, thread, time::Duration };
 
 use crate::{ 
-        input::command_to_event,
-        tools::{ log_err_desc_ret, log_err_ret }, 
+        api::{ client::Client, model::{ InboxTab, Item, Listing, Post, Vote } },
+        cli::Target,
+        def::app::{ DEFAULT_KEY_TIMEOUT, UNDO_HIDE_WINDOW, UNREAD_REFRESH_INTERVAL },
+        events::{ bus::EventSender, event::{ Event, TimerId }, timers::Timers },
+        jobs::{
+            compose::{
+                compose, create_draft, edit_in_editor, edit_template, message_template, parse_message, parse_reply, parse_submit,
+                reply_template, submit_template, ComposeErr
+            },
+            config::{ dump_config, LoadedConfig },
+            ConfigRoots,
+            ConfigWatcher
+        },
+        events::crash::unregister_nc,
+        tools::{ log_err_desc, log_err_desc_ret, log_err_ret }, 
         tui::{ AppRes, TuiPrefs }
 };
-use super::subreddit_listing_page::SubListPage;
+use super::{ inbox_page::InboxPage, saved_page::SavedPage, subreddit_listing_page::SubListPage, terminal, text_page::TextPage };
 use super::{ 
-        command_palette::CmdPalette,
-        page::{ Page, PageBar, PageType },
+        model::{ AppModel, Confirm, Mode, PaletteKey },
+        page::PageType,
         util::new_child_plane,
-        util::Widget,
-        util::PostData
+        surface::CellBuffer,
+        view::{ draw_view, BARS_HEIGHT }
 };
 
 // -----------------------------------------------------------------------------------------------------------
 // * Primary base App.
 // * All widgets are subordinate to this.
 // * All widget planes are derived from this plane or its children.
-// * It represents the current state of TUI and the application.
+// * It renders the state of the application, held in AppModel, onto the terminal, or into a cell buffer
+//   when headless.
 // -----------------------------------------------------------------------------------------------------------
 pub struct App<'a> {
-        // Nc instance.
-        nc: Arc<Mutex<&'a mut Nc>>,
-        plane: &'a mut NcPlane,
+        screen: Screen<'a>,
         tui_prefs: TuiPrefs,
 
-        // Pages currently in the application.
-        foc_page: usize,
-        pub pages: Vec<Box<dyn Page + 'a>>,
-        pub page_bar: PageBar<'a>,
+        // Pages, focus, mode, palette and status.
+        model: AppModel,
+
+        // Config in use, shown by :config.
+        loaded_config: Option<LoadedConfig>,
+        // Watches files of config in use, to reload it on change.
+        config_watcher: Option<ConfigWatcher>,
+
+        // Sends events to the main loop, handed to background jobs.
+        event_sender: Option<EventSender>,
+        // Reddit API client of account logged in, None browsing anonymously.
+        client: Option<Client>,
+        // Name of account logged in, None until fetched.
+        username: Option<String>,
+        // Timers polled by the main loop.
+        pub timers: Timers,
+        // Posts hidden, kept until Reddit answered and their undo window ended, hidden last at the end.
+        hidden_posts: Vec<HiddenPost>,
+        // Timer refreshing the unread count, None browsing anonymously.
+        unread_timer: Option<TimerId>,
+        // Index of the inbox page, None until it is opened.
+        inbox_page: Option<usize>,
+
+        // Whether terminal cursor is shown, in command palette.
+        cursor_enabled: bool
+}
+
+// Post hidden, kept on pages to put back until it is hidden for good.
+struct HiddenPost {
+    fullname: String,
+    // Timer ending its undo window, None once it ended.
+    undo_timer: Option<TimerId>,
+    // Whether Reddit answered the request hiding it.
+    answered: bool
+}
 
-        // Command palette widget.
-        pub cmd_plt: CmdPalette<'a>
+// Where App renders.
+enum Screen<'a> {
+    // Terminal, through Nc instance.
+    Nc {
+        nc: Arc<Mutex<&'a mut Nc>>,
+        plane: &'a mut NcPlane
+    },
+    // Cell buffer in memory, for tests.
+    Buffer(CellBuffer)
 }
 
 impl<'a> App<'a> {
-    pub fn new<'b>(nc: Arc<Mutex<&'b mut Nc>>, tui_prefs: TuiPrefs) 
+    pub fn new<'b>(nc: Arc<Mutex<&'b mut Nc>>, mut tui_prefs: TuiPrefs) 
             -> Result<App<'b>> {
         let mut nc_lock = nc.lock().unwrap();
         let stdplane = unsafe { nc_lock.stdplane() }; 
@@ -56,165 +104,863 @@ impl<'a> App<'a> {
             info!("Enabling mice events.");
             log_err_desc_ret!(nc_lock.mice_enable(NcMiceEvents::All), "Failed to enable mice events")?;
         }
+        if !nc_lock.cantruecolor() {
+            // Palette size fails without color support, 8 colors are the least to hope for.
+            let palette_size = nc_lock.palette_size().unwrap_or(8);
+            info!("Terminal has no RGB colors, limiting theme to {} palette colors.", palette_size);
+            tui_prefs.limit_colors(palette_size);
+        }
         drop(nc_lock);
 
         let app_plane = new_child_plane!(stdplane, 0, 0, dim_x, dim_y);
 
-        let cmd_plt = log_err_ret!(
-            CmdPalette::new(&tui_prefs,
-                              app_plane,
-                              0,
-                              (app_plane.dim_y() - 1) as i32,
-                              app_plane.dim_x(),
-                              1
-                              )
-        )?;
-
-        let page_bar = PageBar::new(&tui_prefs,
-                                       app_plane,
-                                       0,
-                                       0,
-                                       app_plane.dim_x(),
-                                       1)?;
-
         Ok(
             App {
-                nc,
-                plane: app_plane,
+                screen: Screen::Nc { nc, plane: app_plane },
                 tui_prefs,
 
-                foc_page: 0,
-                pages: Vec::new(),
-                page_bar,
+                model: AppModel::new(dim_x, dim_y - BARS_HEIGHT),
+
+                loaded_config: None,
+                config_watcher: None,
 
-                cmd_plt
+                event_sender: None,
+                client: None,
+                username: None,
+                timers: Timers::new(),
+                hidden_posts: Vec::new(),
+                unread_timer: None,
+                inbox_page: None,
+
+                cursor_enabled: false
             }
         )
     }
 
-    // Add a new page.
+    // App rendering into a cell buffer of dim_x x dim_y instead of the terminal, e.g. for tests.
+    pub fn headless(tui_prefs: TuiPrefs, dim_x: u32, dim_y: u32) -> App<'static> {
+        App {
+            screen: Screen::Buffer(CellBuffer::new(dim_x, dim_y)),
+            tui_prefs,
+
+            model: AppModel::new(dim_x, dim_y.saturating_sub(BARS_HEIGHT)),
+
+            loaded_config: None,
+            config_watcher: None,
+
+            event_sender: None,
+            client: None,
+            username: None,
+            timers: Timers::new(),
+            hidden_posts: Vec::new(),
+            unread_timer: None,
+            inbox_page: None,
+
+            cursor_enabled: false
+        }
+    }
+
+    // Screen rendered last, None if rendering onto the terminal.
+    pub fn buffer(&self) -> Option<&CellBuffer> {
+        match &self.screen {
+            Screen::Buffer(buffer) => Some(buffer),
+            Screen::Nc { .. } => None
+        }
+    }
+
+    pub fn model(&self) -> &AppModel {
+        &self.model
+    }
+
+    pub fn model_mut(&mut self) -> &mut AppModel {
+        &mut self.model
+    }
+
+    // Add a new page, focusing it.
     pub fn add_page(&mut self, page_type: PageType) -> Result<()> {
         info!("Adding new page of type {:?}.", page_type);
         match page_type {
-            PageType::SubredditListing => {
-                let sub_list_page = log_err_ret!(SubListPage::new(&self.tui_prefs,
-                                                            self.plane,
-                                                            0,
-                                                            1,
-                                                            self.plane.dim_x(),
-                                                            self.plane.dim_y() - 1,
-                                                            ))?;
-                self.pages.push(Box::new(sub_list_page));
-                self.foc_page = self.pages.len() - 1;
+            PageType::SubredditListing { subreddit } => {
+                self.model.add_page(Box::new(SubListPage::new(&subreddit)));
+            },
+            PageType::Saved => {
+                self.model.add_page(Box::new(SavedPage::new()));
+            },
+            PageType::Inbox { tab } => {
+                self.inbox_page = Some(self.model.add_page(Box::new(InboxPage::new(tab))));
             }
         }
         Ok(())
     }
 
     pub fn dummy_render(&mut self) -> Result<()> {
-        self.add_page(PageType::SubredditListing)?;
-        let sub_list_page = &mut self.pages[0];       
+        self.add_page(PageType::SubredditListing { subreddit: "rust".to_string() })?;
+        let sub_list_page = self.model.page_mut(0).ok_or_else(|| anyhow!("No page to add posts to."))?;
         for x in 0..13 {
-            (*sub_list_page).add_post(&self.tui_prefs, PostData {
-                heading: "hadfafda",
-                content: "fahfaljdf",
-                upvotes: x,
-                username: "afhaldjf",
-                subreddit_name: "rust",
-                comments: 78,
-                body: "jfkladjfl ajdfla jdflkj"
+            sub_list_page.add_post(Post {
+                id: format!("rust{}", x),
+                title: "hadfafda".to_string(),
+                score: x,
+                author: "afhaldjf".to_string(),
+                subreddit: "rust".to_string(),
+                num_comments: 78,
+                selftext: "jfkladjfl ajdfla jdflkj".to_string(),
+                ..Post::default()
             }).context("Failed to create new page of type SubredditListing.")?;
         }
 
-        self.add_page(PageType::SubredditListing)?;
-        let sub_list_page2 = &mut self.pages[1]; 
+        self.add_page(PageType::SubredditListing { subreddit: "hhhhhhhh".to_string() })?;
+        let sub_list_page2 = self.model.page_mut(1).ok_or_else(|| anyhow!("No page to add posts to."))?;
         
         for x in 0..13 {
-            (*sub_list_page2).add_post(&self.tui_prefs, PostData {
-                heading: "ffffff",
-                content: "hhhhhhhh",
-                upvotes: x,
-                username: "bbbbbbbbb",
-                subreddit_name: "hhhhhhhh",
-                comments: 78,
-                body: "ooooooooooooooooooo"
+            sub_list_page2.add_post(Post {
+                id: format!("hhhhhhhh{}", x),
+                title: "ffffff".to_string(),
+                score: x,
+                author: "bbbbbbbbb".to_string(),
+                subreddit: "hhhhhhhh".to_string(),
+                num_comments: 78,
+                selftext: "ooooooooooooooooooo".to_string(),
+                ..Post::default()
             }).context("Failed to create new page of type SubredditListing.")?;
         }
 
-        self.foc_page = 1;
+        self.model.set_foc_page(1)
+    }
+
+    // Open page of target given at startup, focusing it. Post and user targets are rejected by parse_args.
+    pub fn open_target(&mut self, target: &Target) -> Result<()> {
+        let subreddit = match target {
+            Target::Subreddit(sub) => {
+                info!("Opening r/{}.", sub);
+                sub.clone()
+            },
+            Target::Post { id, .. } => { bail!("Post pages are not supported yet, not opening post {}", id); },
+            Target::User(user) => { bail!("User pages are not supported yet, not opening u/{}", user); }
+        };
+        self.add_page(PageType::SubredditListing { subreddit })?;
+        self.model.foc_page_mut()?.fetch()
+    }
+
+    // Set config in use, watching its files and the places a config file could be created in.
+    pub fn set_loaded_config(&mut self, loaded_config: LoadedConfig) {
+        let roots = ConfigRoots::from_env(loaded_config.cli_path.as_deref());
+        let files = loaded_config.files.iter().map(|file| file.path.clone())
+            .chain(Some(roots.system))
+            .chain(roots.candidates.into_iter().map(|(path, _)| path));
+        self.config_watcher = match ConfigWatcher::new(files) {
+            Ok(config_watcher) => Some(config_watcher),
+            Err(e) => {
+                warn!("Unable to watch config files, they will not reload on change: {}", e);
+                None
+            }
+        };
+        self.loaded_config = Some(loaded_config);
+    }
+
+    pub fn set_event_sender(&mut self, event_sender: EventSender) {
+        self.event_sender = Some(event_sender);
+    }
+
+    // Sender for background jobs to report back through, None if the main loop is not listening.
+    pub fn event_sender(&self) -> Option<EventSender> {
+        self.event_sender.clone()
+    }
+
+    pub fn set_client(&mut self, client: Client) {
+        self.client = Some(client);
+    }
+
+    // Fetch name of account logged in, in background. Own items are told apart by it.
+    pub fn fetch_username(&mut self) -> Result<()> {
+        let client = match self.client.clone() {
+            Some(client) => client,
+            None => { return Ok(()); }
+        };
+        self.spawn_job(move || Event::LoggedIn { username: client.username().map_err(|e| e.to_string()) })
+    }
+
+    pub fn logged_in(&mut self, username: Result<String, String>) {
+        match username {
+            Ok(username) => {
+                info!("Logged in as u/{}.", username);
+                self.username = Some(username);
+            },
+            Err(e) => { warn!("Unable to fetch account name, own items cannot be edited or deleted: {}", e); }
+        }
+    }
+
+    // Count unread messages in background now and every UNREAD_REFRESH_INTERVAL, shown in the page bar.
+    pub fn start_unread_refresh(&mut self) -> Result<()> {
+        if self.client.is_none() { return Ok(()); }
+        self.unread_timer = Some(self.timers.every(Duration::from_millis(UNREAD_REFRESH_INTERVAL)));
+        self.refresh_unread()
+    }
 
-        self.cmd_plt.plane.move_top();
-        self.page_bar.plane.move_top();
+    fn refresh_unread(&self) -> Result<()> {
+        let client = self.client("count unread messages")?;
+        self.spawn_job(move || Event::Unread { count: client.unread_count().map_err(|e| e.to_string()) })
+    }
 
+    // Show unread count in the page bar. Failing to count is only logged, it is retried on the next refresh.
+    pub fn unread_counted(&mut self, count: Result<u32, String>) -> Result<()> {
+        match count {
+            Ok(count) if Some(count) != self.model.unread() => {
+                self.model.set_unread(count);
+                self.render()
+            },
+            Ok(_) => Ok(()),
+            Err(e) => {
+                warn!("Unable to count unread messages: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    // Client of account logged in, failing with a login hint to do what needs one.
+    fn client(&self, action: &str) -> Result<Client> {
+        self.client.clone().ok_or_else(|| anyhow!("Not logged in, run ravana login to {}.", action))
+    }
+
+    // Run job in background, sending the event it returns to the main loop.
+    fn spawn_job(&self, job: impl FnOnce() -> Event + Send + 'static) -> Result<()> {
+        let event_sender = self.event_sender().ok_or_else(|| anyhow!("Main loop is not listening to background jobs."))?;
+        thread::spawn(move || log_err_desc!(event_sender.send(job()), "Unable to report background job"));
         Ok(())
     }
 
-    pub fn input_cmd_plt(&mut self, ncin: NcInput) -> Result<AppRes> {
-        let res = log_err_ret!(self.cmd_plt.input(ncin))?;
+    pub fn loaded_config(&self) -> Option<&LoadedConfig> {
+        self.loaded_config.as_ref()
+    }
+
+    // Time to wait for the rest of a key-binding, None to wait forever.
+    pub fn key_timeout(&self) -> Option<Duration> {
+        let key_timeout = self.loaded_config.as_ref().map_or(DEFAULT_KEY_TIMEOUT, |loaded| loaded.config.key_timeout);
+        Some(Duration::from_millis(key_timeout)).filter(|_| key_timeout > 0)
+    }
+
+    // Fd to poll for config file changes.
+    pub fn config_watcher_fd(&self) -> Option<RawFd> {
+        self.config_watcher.as_ref().map(|config_watcher| config_watcher.fd())
+    }
+
+    // Whether config files changed since last checked.
+    pub fn config_changed(&self) -> bool {
+        self.config_watcher.as_ref().map_or(false, |config_watcher| config_watcher.changed())
+    }
+
+    // Open page with resolved config.
+    pub fn show_config(&mut self) -> Result<()> {
+        let dump = match &self.loaded_config {
+            Some(loaded_config) => dump_config(loaded_config),
+            None => bail!("No config loaded.")
+        };
+        self.add_text_page("config", &dump)
+    }
+
+    // Add a page showing text, focusing it.
+    pub fn add_text_page(&mut self, name: &str, text: &str) -> Result<()> {
+        self.model.add_page(Box::new(TextPage::new(name, text)));
+        Ok(())
+    }
+
+    // Edit command palette, AppRes tells whether command mode continues.
+    pub fn input_cmd_plt(&mut self, key: PaletteKey) -> Result<AppRes> {
+        let res = match self.model.palette_input(key) {
+            Mode::Cmd => AppRes::CmdModeCont,
+            Mode::Normal | Mode::Confirm => AppRes::CmdModeQuit
+        };
         self.render()?;
         Ok(res)
     }
 
+    // Show message in command palette until command mode is entered.
+    pub fn show_status(&mut self, msg: &str) -> Result<()> {
+        self.model.show_status(msg);
+        self.render()
+    }
+
     pub fn enter_cmd(&mut self) -> Result<()> {
-        // Put : in CmdPalette
-        unsafe { ncreader_offer_input(self.cmd_plt.reader, &NcInput::new(':')) };
+        self.model.enter_cmd();
         self.render()
     }
 
     pub fn exit_cmd(&mut self) -> Result<()> {
-        self.cmd_plt.clear_contents();
+        self.model.exit_cmd();
+        self.render()
+    }
+
+    // Take command typed in command palette, clearing it.
+    pub fn take_cmd(&mut self) -> Result<String> {
+        let cmd = self.model.take_cmd();
+        self.render()?;
+        Ok(cmd)
+    }
+
+    // Add fetched posts to page they were fetched for, or show why fetching failed.
+    pub fn add_fetched(&mut self, page: usize, posts: Result<Vec<Post>, String>) -> Result<()> {
+        let posts = match posts {
+            Ok(posts) => posts,
+            Err(e) => { return self.show_status(&format!("Unable to fetch posts: {}", e)); }
+        };
+        let page = self.model.page_mut(page).ok_or_else(|| anyhow!("No page {} to add fetched posts to.", page))?;
+        for post in posts {
+            page.add_post(post)?;
+        }
+        self.render()
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Vote on selected item, showing the vote at once.
+    // * Reddit is told in background, Event::Voted rolls the vote back if it refuses.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn vote(&mut self, vote: Vote) -> Result<()> {
+        let client = self.client("vote")?;
+        let (fullname, prev) = self.model.vote(vote)?;
+        self.render()?;
+        let job_fullname = fullname.clone();
+        let res = self.spawn_job(move || {
+            let res = client.vote(&job_fullname, vote).map_err(|e| e.to_string());
+            Event::Voted { fullname: job_fullname, prev, res }
+        });
+        if let Err(e) = res {
+            self.voted(&fullname, prev, Err(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    // Roll vote on item with fullname back to prev if Reddit refused it.
+    pub fn voted(&mut self, fullname: &str, prev: Vote, res: Result<(), String>) -> Result<()> {
+        match res {
+            Ok(()) => {
+                debug!("Voted on {}.", fullname);
+                Ok(())
+            },
+            Err(e) => {
+                self.model.set_vote(fullname, prev);
+                self.show_status(&format!("Unable to vote: {}", e))
+            }
+        }
+    }
+
+    // Save selected item or unsave it, showing it at once. Event::Saved rolls it back if Reddit refuses.
+    pub fn save(&mut self, saved: bool) -> Result<()> {
+        let client = self.client("save")?;
+        let fullname = self.model.save(saved)?;
+        self.render()?;
+        let job_fullname = fullname.clone();
+        let res = self.spawn_job(move || {
+            let res = client.save(&job_fullname, saved).map_err(|e| e.to_string());
+            Event::Saved { fullname: job_fullname, saved, res }
+        });
+        if let Err(e) = res {
+            self.saved(&fullname, saved, Err(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    // Roll saving item with fullname back if Reddit refused it.
+    pub fn saved(&mut self, fullname: &str, saved: bool, res: Result<(), String>) -> Result<()> {
+        match res {
+            Ok(()) => {
+                debug!("{} {}.", if saved { "Saved" } else { "Unsaved" }, fullname);
+                Ok(())
+            },
+            Err(e) => {
+                self.model.set_saved(fullname, !saved);
+                self.show_status(&format!("Unable to {}: {}", if saved { "save" } else { "unsave" }, e))
+            }
+        }
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Hide selected post, taking it off pages at once. Event::Hidden puts it back if Reddit refuses.
+    // * unhide puts the post hidden last back until its undo window ends.
+    // * A post is dropped for good once its undo window ended and Reddit answered.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn hide(&mut self) -> Result<()> {
+        let client = self.client("hide")?;
+        let fullname = self.model.hide()?;
+        let timer = self.timers.once(Duration::from_millis(UNDO_HIDE_WINDOW));
+        self.hidden_posts.push(HiddenPost { fullname: fullname.clone(), undo_timer: Some(timer), answered: false });
+        self.show_status("Post hidden, :unhide to undo.")?;
+        self.spawn_hide(client, fullname, true)
+    }
+
+    // Undo hiding the post hidden last, within its undo window.
+    pub fn unhide(&mut self) -> Result<()> {
+        let client = self.client("unhide")?;
+        let i = self.hidden_posts.iter().rposition(|hidden| hidden.undo_timer.is_some())
+            .ok_or_else(|| anyhow!("Nothing hidden to undo."))?;
+        let hidden = self.hidden_posts.remove(i);
+        if let Some(timer) = hidden.undo_timer { self.timers.cancel(timer); }
+        self.model.unhide(&hidden.fullname);
+        self.render()?;
+        self.spawn_hide(client, hidden.fullname, false)
+    }
+
+    // Tell Reddit post with fullname is hidden or unhidden, in background.
+    fn spawn_hide(&mut self, client: Client, fullname: String, hidden: bool) -> Result<()> {
+        let job_fullname = fullname.clone();
+        let res = self.spawn_job(move || {
+            let res = client.hide(&job_fullname, hidden).map_err(|e| e.to_string());
+            Event::Hidden { fullname: job_fullname, hidden, res }
+        });
+        if let Err(e) = res {
+            self.hidden(&fullname, hidden, Err(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    // Put post back if Reddit refused hiding it, or take it off again if it refused unhiding it.
+    pub fn hidden(&mut self, fullname: &str, hidden: bool, res: Result<(), String>) -> Result<()> {
+        if !hidden {
+            return match res {
+                Ok(()) => {
+                    debug!("Unhid {}.", fullname);
+                    Ok(())
+                },
+                Err(e) => {
+                    self.model.hide_item(fullname);
+                    self.model.forget_hidden(fullname);
+                    self.show_status(&format!("Unable to unhide: {}", e))
+                }
+            };
+        }
+
+        // Unhidden meanwhile, the post is back already.
+        let i = match self.hidden_posts.iter().position(|hidden| hidden.fullname == fullname) {
+            Some(i) => i,
+            None => { return Ok(()); }
+        };
+        match res {
+            Ok(()) => {
+                debug!("Hid {}.", fullname);
+                self.hidden_posts[i].answered = true;
+                if self.hidden_posts[i].undo_timer.is_none() { self.forget_hidden(i); }
+                Ok(())
+            },
+            Err(e) => {
+                let hidden = self.hidden_posts.remove(i);
+                if let Some(timer) = hidden.undo_timer { self.timers.cancel(timer); }
+                self.model.unhide(fullname);
+                self.show_status(&format!("Unable to hide: {}", e))
+            }
+        }
+    }
+
+    // Drop post hidden for good, it can no longer be put back.
+    fn forget_hidden(&mut self, i: usize) {
+        let hidden = self.hidden_posts.remove(i);
+        self.model.forget_hidden(&hidden.fullname);
+    }
+
+    // End undo window of timer or refresh unread count, returning whether the timer was App's.
+    pub fn timer_expired(&mut self, timer: TimerId) -> bool {
+        if self.unread_timer == Some(timer) {
+            log_err_desc!(self.refresh_unread(), "Unable to refresh unread count");
+            return true;
+        }
+        let i = match self.hidden_posts.iter().position(|hidden| hidden.undo_timer == Some(timer)) {
+            Some(i) => i,
+            None => { return false; }
+        };
+        self.hidden_posts[i].undo_timer = None;
+        if self.hidden_posts[i].answered { self.forget_hidden(i); }
+        true
+    }
+
+    // Open page of saved posts and comments, fetching their first page.
+    pub fn open_saved(&mut self) -> Result<()> {
+        self.client("list saved items")?;
+        self.add_page(PageType::Saved)?;
+        self.fetch_more(self.model.foc_page())
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Open inbox listing tab, focusing it and fetching the tab's first page.
+    // * Once open, the inbox page switches to tab instead, or is only focused if no tab is given.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn open_inbox(&mut self, tab: Option<InboxTab>) -> Result<()> {
+        self.client("read the inbox")?;
+        match (self.inbox_page, tab) {
+            (Some(page), None) => {
+                self.model.set_foc_page(page)?;
+                return self.render();
+            },
+            (Some(page), Some(tab)) => {
+                self.model.set_foc_page(page)?;
+                self.model.foc_page_mut()?.set_tab(tab)?;
+            },
+            (None, tab) => { self.add_page(PageType::Inbox { tab: tab.unwrap_or_default() })?; }
+        }
+        self.fetch_more(self.model.foc_page())
+    }
+
+    // Fetch next page of the listing on page in background, added on Event::FetchedListing.
+    pub fn fetch_more(&mut self, page: usize) -> Result<()> {
+        let client = self.client("fetch items")?;
+        let page_ref = self.model.page_mut(page).ok_or_else(|| anyhow!("No page {} to fetch for.", page))?;
+        let src = page_ref.listing_src().ok_or_else(|| anyhow!("Nothing more to fetch on this page."))?;
+        let after = page_ref.fetch_more()?;
+        self.render()?;
+        let res = self.spawn_job(move || {
+            let listing = client.listing(src, after.as_deref()).map_err(|e| e.to_string());
+            Event::FetchedListing { page, listing }
+        });
+        if let Err(e) = res {
+            self.add_fetched_listing(page, Err(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    // Add page of listing fetched to page it was fetched for, or show why fetching failed.
+    pub fn add_fetched_listing(&mut self, page: usize, listing: Result<Listing, String>) -> Result<()> {
+        let page = self.model.page_mut(page).ok_or_else(|| anyhow!("No page {} to add fetched items to.", page))?;
+        match listing {
+            Ok(listing) => { page.add_listing(listing)?; },
+            Err(e) => {
+                page.fetch_failed();
+                return self.show_status(&format!("Unable to fetch items: {}", e));
+            }
+        }
         self.render()
     }
 
-    // Execute command typed in command palette.
-    pub fn exec_cmd(&mut self) -> Result<Option<AppRes>> {
-        let cmd = log_err_ret!(self.cmd_plt.contents())?;
-        self.cmd_plt.clear_contents();
+    // -------------------------------------------------------------------------------------------------------
+    // * Edit draft in $EDITOR at path, with the terminal released, until parse takes it.
+    // * None if the draft is cancelled, its file is removed then.
+    // -------------------------------------------------------------------------------------------------------
+    fn compose<T>(&mut self, path: &Path, template: String,
+            parse: impl Fn(&str) -> Result<Option<T>, ComposeErr>) -> Result<Option<T>> {
+        let parsed = self.with_terminal_released(|| compose(template, parse, |text| edit_in_editor(path, text)))??;
+        if parsed.is_none() {
+            log_err_desc!(fs::remove_file(path), "Unable to remove draft");
+        }
+        Ok(parsed)
+    }
+
+    // Send draft of what in background with job, reporting on Event::Sent.
+    fn spawn_send(&mut self, what: &str, draft: PathBuf, job: impl FnOnce() -> Result<()> + Send + 'static) -> Result<()> {
+        let job_what = what.to_string();
+        let job_draft = draft.clone();
+        let res = self.spawn_job(move || Event::Sent { what: job_what, draft: job_draft, res: job().map_err(|e| e.to_string()) });
+        match res {
+            Ok(()) => self.show_status(&format!("Sending {}...", what)),
+            Err(e) => self.sent(what, &draft, Err(e.to_string()))
+        }
+    }
+
+    // Reply to selected post or comment, composed in $EDITOR.
+    pub fn reply(&mut self) -> Result<()> {
+        let client = self.client("reply")?;
+        let item = self.model.foc_page_ref()
+            .and_then(|page| page.selected_item())
+            .ok_or_else(|| anyhow!("Nothing to reply to selected."))?;
+        let draft = create_draft("reply")?;
+        let text = match self.compose(&draft, reply_template(&item), parse_reply)? {
+            Some(text) => text,
+            None => { return self.show_status("Reply cancelled."); }
+        };
+        let parent = item.fullname();
+        self.spawn_send("reply", draft, move || client.comment(&parent, &text))
+    }
+
+    // Submit post to subreddit of focused page, composed in $EDITOR.
+    pub fn submit(&mut self) -> Result<()> {
+        let client = self.client("submit")?;
+        let subreddit = self.model.foc_page_ref()
+            .and_then(|page| page.subreddit())
+            .map(|subreddit| subreddit.to_string())
+            .ok_or_else(|| anyhow!("Focused page is not a subreddit to submit to."))?;
+        let draft = create_draft("submit")?;
+        let submission = match self.compose(&draft, submit_template(&subreddit), |text| parse_submit(&subreddit, text))? {
+            Some(submission) => submission,
+            None => { return self.show_status("Submission cancelled."); }
+        };
+        self.spawn_send("post", draft, move || client.submit(&submission))
+    }
+
+    // Send private message to user, composed in $EDITOR.
+    pub fn compose_message(&mut self, to: &str) -> Result<()> {
+        let client = self.client("send messages")?;
+        let to = to.trim_start_matches('/').trim_start_matches("u/").to_string();
+        let draft = create_draft("message")?;
+        let message = match self.compose(&draft, message_template(&to), |text| parse_message(&to, text))? {
+            Some(message) => message,
+            None => { return self.show_status("Message cancelled."); }
+        };
+        self.spawn_send("message", draft, move || client.send_message(&message))
+    }
+
+    // Mark selected message read or unread, showing it at once. Event::MarkedRead rolls it back if Reddit refuses.
+    pub fn mark_read(&mut self, read: bool) -> Result<()> {
+        let client = self.client("mark messages read")?;
+        let fullname = self.model.mark_read(read)?;
         self.render()?;
-        command_to_event::exec_cmd(self, &cmd[1..cmd.len()]) // Ignore first char which is ':'
+        let job_fullname = fullname.clone();
+        let res = self.spawn_job(move || {
+            let res = client.mark_read(&job_fullname, read).map_err(|e| e.to_string());
+            Event::MarkedRead { fullname: job_fullname, read, res }
+        });
+        if let Err(e) = res {
+            self.marked_read(&fullname, read, Err(e.to_string()))?;
+        }
+        Ok(())
     }
 
-    pub fn switch_next_page(&mut self) {
-        self.set_foc_page((self.foc_page + 1) % self.pages.len());
+    // Roll marking message with fullname back if Reddit refused it.
+    pub fn marked_read(&mut self, fullname: &str, read: bool, res: Result<(), String>) -> Result<()> {
+        match res {
+            Ok(()) => {
+                debug!("Marked {} {}.", fullname, if read { "read" } else { "unread" });
+                Ok(())
+            },
+            Err(e) => {
+                self.model.set_read(fullname, !read);
+                self.show_status(&format!("Unable to mark {}: {}", if read { "read" } else { "unread" }, e))
+            }
+        }
+    }
+
+    // Selected item, failing unless it is authored by the account logged in.
+    fn own_selected_item(&self, action: &str) -> Result<Item> {
+        let item = self.model.foc_page_ref()
+            .and_then(|page| page.selected_item())
+            .ok_or_else(|| anyhow!("Nothing to {} selected.", action))?;
+        let username = self.username.as_deref()
+            .ok_or_else(|| anyhow!("Account name is not fetched, unable to tell if the {} is yours.", item.kind()))?;
+        if item.author() != username {
+            bail!("Unable to {} the {} of u/{}, only yours.", action, item.kind(), item.author());
+        }
+        Ok(item)
+    }
+
+    // Edit markdown of selected own post or comment in $EDITOR, updating it in place once Reddit takes it.
+    pub fn edit(&mut self) -> Result<()> {
+        let client = self.client("edit")?;
+        let item = self.own_selected_item("edit")?;
+        if let Item::Post(Post { is_self: false, .. }) = item {
+            bail!("Unable to edit a link post, only text posts have markdown to edit.");
+        }
+        let draft = create_draft("edit")?;
+        let body = match self.compose(&draft, edit_template(&item), parse_reply)? {
+            Some(body) => body,
+            None => { return self.show_status("Edit cancelled."); }
+        };
+        let fullname = item.fullname();
+        let (job_fullname, job_draft) = (fullname.clone(), draft.clone());
+        let res = self.spawn_job(move || {
+            let res = client.edit(&job_fullname, &body).map_err(|e| e.to_string());
+            Event::Edited { fullname: job_fullname, body, draft: job_draft, res }
+        });
+        match res {
+            Ok(()) => self.show_status("Sending edit..."),
+            Err(e) => self.edited(&fullname, "", &draft, Err(e.to_string()))
+        }
+    }
+
+    // Show body of item edited in place, or keep the draft and tell where if Reddit refused it.
+    pub fn edited(&mut self, fullname: &str, body: &str, draft: &Path, res: Result<(), String>) -> Result<()> {
+        match res {
+            Ok(()) => {
+                log_err_desc!(fs::remove_file(draft), "Unable to remove draft");
+                self.model.set_body(fullname, body);
+                self.show_status("Edited.")
+            },
+            Err(e) => self.show_status(&format!("Unable to edit, draft kept in {}: {}", draft.display(), e))
+        }
+    }
+
+    // Ask to confirm deleting selected own post or comment.
+    pub fn delete(&mut self) -> Result<()> {
+        self.client("delete")?;
+        let item = self.own_selected_item("delete")?;
+        if let Item::Message(_) = item {
+            bail!("Unable to delete a message, only posts and comments.");
+        }
+        self.model.ask_confirm(&format!("Delete this {}? y/n", item.kind()), Confirm::Delete { fullname: item.fullname() });
+        self.render()
+    }
+
+    // Act on answer in confirmation modal.
+    pub fn answer_confirm(&mut self, confirmed: bool) -> Result<()> {
+        match self.model.answer_confirm(confirmed) {
+            Some(Confirm::Delete { fullname }) => self.delete_confirmed(fullname),
+            None => self.render()
+        }
     }
 
-    pub fn switch_prev_page(&mut self) {
-        if self.foc_page == 0 {
-            self.set_foc_page(self.pages.len() - 1);
+    fn delete_confirmed(&mut self, fullname: String) -> Result<()> {
+        let client = self.client("delete")?;
+        let job_fullname = fullname.clone();
+        let res = self.spawn_job(move || {
+            let res = client.delete(&job_fullname).map_err(|e| e.to_string());
+            Event::Deleted { fullname: job_fullname, res }
+        });
+        match res {
+            Ok(()) => self.show_status("Deleting..."),
+            Err(e) => self.deleted(&fullname, Err(e.to_string()))
+        }
+    }
+
+    // Show item as deleted in place once Reddit deleted it.
+    pub fn deleted(&mut self, fullname: &str, res: Result<(), String>) -> Result<()> {
+        match res {
+            Ok(()) => {
+                self.model.mark_deleted(fullname);
+                self.show_status("Deleted.")
+            },
+            Err(e) => self.show_status(&format!("Unable to delete: {}", e))
+        }
+    }
+
+    // Remove draft of what once sent, or keep it and tell where if Reddit refused it.
+    pub fn sent(&mut self, what: &str, draft: &Path, res: Result<(), String>) -> Result<()> {
+        match res {
+            Ok(()) => {
+                log_err_desc!(fs::remove_file(draft), "Unable to remove draft");
+                self.show_status(&format!("Sent {}.", what))
+            },
+            Err(e) => self.show_status(&format!("Unable to send {}, draft kept in {}: {}", what, draft.display(), e))
+        }
+    }
+
+    // Hand event to focused page, logging it if unhandled.
+    pub fn route_to_foc_page(&mut self, event: &Event) -> Result<()> {
+        if self.model.foc_page_mut()?.handle_event(event)? {
+            self.render()
         } else {
-            self.set_foc_page(self.foc_page - 1);
+            debug!("Event {:?} unhandled by focused page.", event);
+            Ok(())
         }
     }
 
-    pub fn set_foc_page(&mut self, new_foc_page: usize) {
-        self.foc_page = new_foc_page;
-        self.page_bar.foc_page = new_foc_page as u32;
+    // -------------------------------------------------------------------------------------------------------
+    // * Fit app to terminal size, after it was resized.
+    // * Page bar, page and command palette are laid out anew on rendering.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn resize(&mut self) -> Result<()> {
+        let (nc, plane) = match &mut self.screen {
+            Screen::Nc { nc, plane } => (nc, plane),
+            Screen::Buffer(_) => { return Ok(()); }
+        };
+        let (dim_y, dim_x) = nc.lock().map_err(|_| anyhow!("Unable to lock Nc."))?.term_dim_yx();
+        if (dim_y, dim_x) == (plane.dim_y(), plane.dim_x()) { return Ok(()); }
+        info!("Resizing to {}x{}.", dim_x, dim_y);
+
+        plane.resize_simple(dim_y, dim_x)?;
+        self.model.resize(dim_x, dim_y - BARS_HEIGHT);
+        self.render()
     }
 
-    pub fn scroll_up(&mut self) { 
-        if let Err(e) = (*self.pages[self.foc_page]).scroll_up() {
-            error!("{}", e);
+    // Set TUI preference, shown from next render.
+    pub fn set_pref(&mut self, option: &str, value: &str) -> Result<()> {
+        let mouse_events_enable = self.tui_prefs.interface.mouse_events_enable;
+        self.tui_prefs.set(option, value)?;
+
+        if mouse_events_enable != self.tui_prefs.interface.mouse_events_enable {
+            self.apply_mouse_events()?;
         }
+        Ok(())
     }
 
-    pub fn scroll_down(&mut self) { 
-        if let Err(e) = (*self.pages[self.foc_page]).scroll_down() {
-            error!("{}", e)
+    // Replace TUI prefs, e.g. on config reload, shown from next render. Colors stay limited to terminal's palette.
+    pub fn set_tui_prefs(&mut self, mut tui_prefs: TuiPrefs) -> Result<()> {
+        if let Some(palette_size) = self.tui_prefs.palette_size {
+            tui_prefs.limit_colors(palette_size);
         }
+        let mouse_events_enable = self.tui_prefs.interface.mouse_events_enable;
+        self.tui_prefs = tui_prefs;
+
+        if mouse_events_enable != self.tui_prefs.interface.mouse_events_enable {
+            self.apply_mouse_events()?;
+        }
+        Ok(())
     }
 
-    // Render TUI.
-    pub fn render(&mut self) -> Result<()> {
-        self.page_bar.draw(&self.tui_prefs)?;
+    // -------------------------------------------------------------------------------------------------------
+    // * Hand the terminal to f, e.g. the shell on suspend or an editor, then take it back and redraw.
+    // * Nc keeps running so planes, pages and their scroll state survive. It only leaves the alternate
+    //   screen, and the terminal mode from before Nc started is restored meanwhile.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn with_terminal_released<R>(&mut self, f: impl FnOnce() -> R) -> Result<R> {
+        let nc = match &self.screen {
+            Screen::Nc { nc, .. } => nc.clone(),
+            // Headless, there is no terminal to hand over.
+            Screen::Buffer(_) => { return Ok(f()); }
+        };
+        let lock = || nc.lock().map_err(|_| anyhow!("Unable to lock Nc."));
 
-        for page in self.pages.iter_mut() {
-            log_err_desc_ret!(page.draw(&self.tui_prefs), "Failed to render page")?;
-            page.set_visibility(false)?;
+        // Undone on early return too, e.g. if leaving the alternate screen fails after mice were disabled.
+        let mut released = terminal::Released::new();
+        if self.tui_prefs.interface.mouse_events_enable {
+            released.step(|| Ok(log_err_desc_ret!(lock()?.mice_disable(), "Failed to disable mice events")?),
+                |_| Ok(log_err_desc_ret!(lock()?.mice_enable(NcMiceEvents::All), "Failed to enable mice events")?))?;
         }
+        released.step(|| Ok(log_err_desc_ret!(lock()?.leave_alternate_screen(), "Failed to leave alternate screen")?),
+            |_| Ok(log_err_desc_ret!(lock()?.enter_alternate_screen(), "Failed to enter alternate screen")?))?;
+        released.step(terminal::enter_cooked_mode, |raw| terminal::enter_raw_mode(&raw))?;
 
-        self.pages[self.foc_page].set_visibility(true)?;
+        let res = f();
 
-        if let Ok(mut nc_lock) = self.nc.lock() {
+        released.restore()?;
+        // Screen holds whatever ran meanwhile, redraw all of it.
+        log_err_desc_ret!(lock()?.refresh(), "Failed to refresh screen")?;
+        self.render()?;
+        Ok(res)
+    }
+
+    // Drop to the shell, resuming when continued, e.g. with fg.
+    pub fn suspend(&mut self) -> Result<()> {
+        if let Screen::Buffer(_) = self.screen { bail!("Headless, there is no terminal to suspend."); }
+        info!("Suspending.");
+        self.with_terminal_released(terminal::stop_self)??;
+        info!("Resumed.");
+        Ok(())
+    }
+
+    // Enable or disable mice events as set in TUI prefs.
+    fn apply_mouse_events(&mut self) -> Result<()> {
+        let nc = match &self.screen {
+            Screen::Nc { nc, .. } => nc,
+            Screen::Buffer(_) => { return Ok(()); }
+        };
+        let mut nc_lock = nc.lock().map_err(|_| anyhow!("Unable to lock Nc."))?;
+        if self.tui_prefs.interface.mouse_events_enable {
+            info!("Enabling mice events.");
+            log_err_desc_ret!(nc_lock.mice_enable(NcMiceEvents::All), "Failed to enable mice events")?;
+        } else {
+            info!("Disabling mice events.");
+            log_err_desc_ret!(nc_lock.mice_disable(), "Failed to disable mice events")?;
+        }
+        Ok(())
+    }
+
+    // Render TUI.
+    pub fn render(&mut self) -> Result<()> {
+        let (nc, plane) = match &mut self.screen {
+            Screen::Nc { nc, plane } => (nc, plane),
+            Screen::Buffer(buffer) => {
+                draw_view(&self.model, &self.tui_prefs, buffer)?;
+                return Ok(())
+            }
+        };
+        let cursor_x = draw_view(&self.model, &self.tui_prefs, *plane)?;
+
+        if let Ok(mut nc_lock) = nc.lock() {
+            match cursor_x {
+                Some(x) => {
+                    log_err_desc_ret!(nc_lock.cursor_enable(plane.dim_y() - 1, x), "Failed to show cursor")?;
+                    self.cursor_enabled = true;
+                },
+                // Disabling a disabled cursor fails.
+                None if self.cursor_enabled => {
+                    log_err_desc_ret!(nc_lock.cursor_disable(), "Failed to hide cursor")?;
+                    self.cursor_enabled = false;
+                },
+                None => {  }
+            }
             log_err_desc_ret!(nc_lock.render(), "Failed to render app")?;
             return Ok(())
         } else {
@@ -230,16 +976,100 @@ impl<'a> App<'a> {
 // -----------------------------------------------------------------------------------------------------------
 impl<'a> Drop for App<'a> {
     fn drop(&mut self) {
+        let (nc, plane) = match &mut self.screen {
+            Screen::Nc { nc, plane } => (nc, plane),
+            Screen::Buffer(_) => { return; }
+        };
+        // Nc and the planes in it are gone if crash handling stopped it.
+        if !unregister_nc() { return; }
 
-        // Destroy ncreader before destroying base plane or Nc instance.
-        self.cmd_plt.destroy_reader();
+        // Panicking here, possibly while unwinding, would abort with the terminal unrestored.
+        log_err_desc!(plane.destroy(), "Failed to destroy app plane");
 
-        log_err_desc_ret!(self.plane.destroy(), "Failed to destroy app plane").unwrap();
-
-        if let Ok(mut nc_lock) = self.nc.lock() {
+        if let Ok(mut nc_lock) = nc.lock() {
             unsafe { 
-                log_err_desc_ret!(nc_lock.stop(), "Failed to destroy Nc instance").unwrap()
+                log_err_desc!(nc_lock.stop(), "Failed to destroy Nc instance");
             }
         } else { error!("Error locking Nc instance while dropping App."); }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{ accounts::Token, client::Client, model::{ InboxTab, Item, Listing, Message, Post } },
+        events::bus::event_bus,
+        jobs::Config,
+        tui::TuiPrefs
+    };
+    use super::{ App, InboxPage, Mode, SubListPage };
+    use super::super::page::Page;
+
+    // Headless app with a listing page of 3 posts, its requests refused without leaving the machine.
+    fn app() -> App<'static> {
+        let mut app = App::headless(TuiPrefs::gen_tui_prefs(&Config::default().tui).unwrap(), 80, 20);
+        app.set_event_sender(event_bus().unwrap().0);
+        app.set_client(Client::with_base_url(&Token::Access("token".to_string()), "http://127.0.0.1:0").unwrap());
+        app.dummy_render().unwrap();
+        let mut page = SubListPage::new("rust");
+        for i in 0..3 {
+            page.add_post(Post { id: i.to_string(), ..Post::default() }).unwrap();
+        }
+        app.model_mut().add_page(Box::new(page));
+        app
+    }
+
+    fn selected(app: &App) -> Option<String> {
+        app.model().foc_page_ref().and_then(|page| page.selected_saved()).map(|(fullname, _)| fullname)
+    }
+
+    // Test if posts hidden in a row can each be put back until Reddit answered and their undo window ended.
+    #[test]
+    fn test_hide_in_flight() {
+        let mut app = app();
+        app.hide().unwrap();
+        app.hide().unwrap();
+        let timers: Vec<_> = app.hidden_posts.iter().map(|hidden| hidden.undo_timer.unwrap()).collect();
+
+        // Refused after another post was hidden.
+        app.hidden("t3_0", true, Err("refused".to_string())).unwrap();
+        assert_eq!(selected(&app), Some("t3_0".to_string()));
+        assert!(!app.timers.cancel(timers[0]));
+
+        // Window ends before the answer, the post is kept until it arrives.
+        assert!(app.timer_expired(timers[1]));
+        assert_eq!(app.hidden_posts.len(), 1);
+        app.hidden("t3_1", true, Ok(())).unwrap();
+        assert!(app.hidden_posts.is_empty());
+        assert!(!app.model_mut().unhide("t3_1"));
+
+        // Answered before the window ends, it can be unhidden until then.
+        app.hide().unwrap();
+        app.hidden("t3_0", true, Ok(())).unwrap();
+        app.unhide().unwrap();
+        assert_eq!(selected(&app), Some("t3_0".to_string()));
+        assert!(app.hidden_posts.is_empty());
+        assert!(app.unhide().is_err());
+    }
+
+    // Test if link posts are not opened for editing and messages are not asked to be deleted.
+    #[test]
+    fn test_edit_delete_kinds() {
+        let mut app = app();
+        app.username = Some("me".to_string());
+        let mut page = SubListPage::new("rust");
+        page.add_post(Post { id: "a".to_string(), author: "me".to_string(), ..Post::default() }).unwrap();
+        app.model_mut().add_page(Box::new(page));
+        assert_eq!(app.edit().unwrap_err().to_string(), "Unable to edit a link post, only text posts have markdown to edit.");
+        app.delete().unwrap();
+        assert_eq!(app.model().mode(), Mode::Confirm);
+        app.answer_confirm(false).unwrap();
+
+        let mut page = InboxPage::new(InboxTab::Messages);
+        let message = Message { id: "b".to_string(), author: "me".to_string(), ..Message::default() };
+        page.add_listing(Listing { items: vec![Item::Message(message)], after: None }).unwrap();
+        app.model_mut().add_page(Box::new(page));
+        assert_eq!(app.delete().unwrap_err().to_string(), "Unable to delete a message, only posts and comments.");
+        assert_eq!(app.model().mode(), Mode::Normal);
+    }
+}
@@ -6,4 +6,7 @@ pub enum AppRes {
     AppQuit,
     CmdModeCont,
     CmdModeQuit,
+    MapKeys(String, String), // Key-combination, command line.
+    UnmapKeys(String), // Key-combination.
+    ReloadConfig,
 }
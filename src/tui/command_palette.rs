@@ -1,118 +1,64 @@
-use anyhow::{ anyhow, Result };
-use log::error;
+use anyhow::Result;
 use libnotcurses_sys::{
-    c_api::{ ncreader, ncreader_contents, ncreader_clear, ncreader_destroy, ncreader_offer_input },
-    NcChannels,
     NcInput,
     NcKey,
-    NcPlane,
-    NcPlaneOptions,
     NcReceived,
-    widgets::NcReaderOptions
+    NcStyle
 };
-use std::ffi::CStr;
 
-use super::{ TuiPrefs, util::new_child_plane, util::Widget };
-use crate::tui::AppRes;
-use crate::tools::log_err_ret;
+use super::{ model::PaletteKey, surface::Surface, TuiPrefs, util::{ channels, Widget } };
 
 // -----------------------------------------------------------------------------------------------------------
 // Command palette widget.
+// * Shows command typed in command mode, else status.
+// * Scrolls horizontally, keeping cursor in view.
 // -----------------------------------------------------------------------------------------------------------
-pub struct CmdPalette<'a> {
-    pub plane: &'a mut NcPlane,
-    pub reader: &'a mut ncreader,
+pub struct CmdPalette {
+    contents: String,
+    cursor: Option<usize>
 }
 
-impl<'a> CmdPalette<'a> {
-    // Add input.
-    pub fn input(&mut self, ncin: NcInput) -> Result<AppRes> {
-        if unsafe { ncreader_offer_input(self.reader, &ncin) } {
-            match self.contents() {
-                Ok(s) => {
-                    if s.len() == 1 && (0, 0) == self.plane.cursor_yx() {
-                        return Ok(AppRes::CmdModeQuit);
-                    }    
-                },
-                Err(e) => {
-                    error!("Failed to get command palette contents: {}", e);
-                    return Err(e);
-                }
-            }
-            return Ok(AppRes::CmdModeCont)
+impl CmdPalette {
+    // Palette showing contents, with cursor at char index.
+    pub fn new(contents: &str, cursor: Option<usize>) -> CmdPalette {
+        CmdPalette {
+            contents: contents.to_string(),
+            cursor
         }
-        return log_err_ret!(Err(anyhow!("Unable to input to command palette: {:?}", ncin)))
     }
 
-    // Get contents of command palette.
-    pub fn contents(&mut self) -> Result<String> {
-        Ok((unsafe { CStr::from_ptr(ncreader_contents(self.reader)) }).to_str()?.to_string())
+    // Column of cursor once drawn dim_x wide, None if there is no cursor.
+    pub fn cursor_x(&self, dim_x: u32) -> Option<u32> {
+        self.cursor.map(|cursor| (cursor - self.scrolled(dim_x)) as u32)
     }
 
-    pub fn clear_contents(&mut self) {
-        unsafe { ncreader_clear(self.reader) };
+    // Chars scrolled off left, keeping cursor in view.
+    fn scrolled(&self, dim_x: u32) -> usize {
+        self.cursor.map_or(0, |cursor| (cursor + 1).saturating_sub(dim_x as usize))
     }
-
-    // Destroy reader nc widget. Required for graceful termination of application.
-    pub fn destroy_reader(&mut self) {
-        unsafe { ncreader_destroy(self.reader, std::ptr::null::<*mut *mut i8>() as *mut *mut i8) }
-    }
-
 }
 
-// Validate input for command palette.
-pub fn cmd_plt_val_input(ncr: &NcReceived) -> bool {
-    match ncr {
-        NcReceived::Char(_) => true,
-        NcReceived::Event(NcKey::Left) => true,
-        NcReceived::Event(NcKey::Right) => true,
-        NcReceived::Event(NcKey::Enter) => true,
-        NcReceived::Event(NcKey::Backspace) => true,
-        _ => false
+impl Widget for CmdPalette {
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()> {
+        let cmd_plt_chnls = channels(&tui_prefs.theme.cmd_plt_fg, &tui_prefs.theme.cmd_plt_bg);
+        let shown: String = self.contents.chars().skip(self.scrolled(surface.dim_x())).collect();
+        surface.fill(cmd_plt_chnls)?;
+        surface.put_str(0, 0, &shown, cmd_plt_chnls, NcStyle::None)
     }
 }
 
-impl<'a> Widget for CmdPalette<'a> {
-    fn new(tui_prefs: &TuiPrefs,
-            parent_plane: &mut NcPlane,
-            x: i32,
-            y: i32,
-            dim_x: u32,
-            dim_y: u32
-          ) -> Result<Self> {
-
-        let plane = new_child_plane!(parent_plane, x, y, dim_x, dim_y);
-        plane.set_channels(NcChannels::from_rgb(
-                tui_prefs.theme.cmd_plt_fg.to_nc_rgb(),
-                tui_prefs.theme.cmd_plt_bg.to_nc_rgb()
-        ));
-        plane.set_base(
-            " ",
-            0,
-            NcChannels::from_rgb(
-                tui_prefs.theme.cmd_plt_fg.to_nc_rgb(),
-                tui_prefs.theme.cmd_plt_bg.to_nc_rgb(),
-            ))?;
-
-        let reader = ncreader::with_options(
-                plane,
-                &NcReaderOptions {
-                    tchannels: NcChannels::from_rgb(
-                        tui_prefs.theme.cmd_plt_fg.to_nc_rgb(),
-                        tui_prefs.theme.cmd_plt_bg.to_nc_rgb(),
-                    ).0,
-                    tattrword: 0,
-                    flags: (NcReaderOptions::CURSOR | NcReaderOptions::HORSCROLL) as u64
-                }
-            )?; 
-
-        Ok(Self {
-            plane,
-            reader
-        })
-    }
-
-    fn draw(&mut self, _tui_prefs: &TuiPrefs) -> Result<()> {
-        Ok(())
+// Palette key for input, None if it does not edit the palette.
+pub fn palette_key(ncr: &NcReceived, id: &NcInput) -> Option<PaletteKey> {
+    if id.evtype == 3 { return None; } // Ignore Kitty release events.
+    match ncr {
+        NcReceived::Char(c) => Some(PaletteKey::Char(*c)),
+        NcReceived::Event(NcKey::Space) => Some(PaletteKey::Char(' ')),
+        NcReceived::Event(NcKey::Backspace) => Some(PaletteKey::Backspace),
+        NcReceived::Event(NcKey::Del) => Some(PaletteKey::Del),
+        NcReceived::Event(NcKey::Left) => Some(PaletteKey::Left),
+        NcReceived::Event(NcKey::Right) => Some(PaletteKey::Right),
+        NcReceived::Event(NcKey::Home) => Some(PaletteKey::Home),
+        NcReceived::Event(NcKey::End) => Some(PaletteKey::End),
+        _ => None
     }
 }
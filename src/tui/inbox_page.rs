@@ -0,0 +1,368 @@
+use anyhow::{ bail, Result };
+use libnotcurses_sys::NcStyle;
+
+use crate::api::model::{ InboxTab, Item, Listing, ListingSrc, Message, Post };
+use super::{ page::Page, surface::Surface, TuiPrefs, util::{ channels, put_cut, wrap } };
+
+// Line on top listing the tabs, above the messages.
+const TAB_BAR_HEIGHT: u32 = 1;
+// Lines taken by a message: header, subject and body.
+const ITEM_HEIGHT: u32 = 4;
+const BODY_LINES: usize = 2;
+// Line below the messages telling whether more are fetched.
+const FOOTER_HEIGHT: u32 = 1;
+// Columns replies are indented by, under the message they reply to.
+const INDENT: u32 = 4;
+// Lines scrolled at a time.
+const SCROLL_STEP: u32 = 2;
+
+// Message listed, with replies of its conversation listed below it.
+struct Row {
+    depth: u32, // 0 for messages listed, 1 for their replies.
+    message: Message
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Page for displaying the inbox of the account logged in, a tab at a time.
+// * Private messages are listed as conversations, replies indented below the message they reply to.
+// * Messages are fetched a page at a time, like saved items, and stay unread until marked read.
+// -----------------------------------------------------------------------------------------------------------
+pub struct InboxPage {
+    tab: InboxTab,
+    rows: Vec<Row>,
+
+    // Cursor of the next page of messages, None before the first page is fetched and after the last one.
+    after: Option<String>,
+    fetched: bool, // Whether a page was fetched.
+    fetching: bool, // Whether a fetch is under way.
+
+    scrolled: u32, // Lines scrolled down, 0 initially.
+    selected: usize, // Index of selected row.
+
+    dim_x: u32,
+    dim_y: u32
+}
+
+impl InboxPage {
+    pub fn new(tab: InboxTab) -> InboxPage {
+        InboxPage {
+            tab,
+            rows: vec![],
+            after: None,
+            fetched: false,
+            fetching: false,
+            scrolled: 0,
+            selected: 0,
+            dim_x: 0,
+            dim_y: 0
+        }
+    }
+
+    fn content_len(&self) -> u32 {
+        self.rows.len() as u32 * ITEM_HEIGHT + FOOTER_HEIGHT
+    }
+
+    // Lines messages are listed in, below the tab bar.
+    fn list_dim_y(&self) -> u32 {
+        self.dim_y.saturating_sub(TAB_BAR_HEIGHT)
+    }
+
+    // Scroll selected row into view.
+    fn scroll_to_selected(&mut self) {
+        let top = self.selected as u32 * ITEM_HEIGHT;
+        if top < self.scrolled {
+            self.scrolled = top;
+        } else if top + ITEM_HEIGHT > self.scrolled + self.list_dim_y() {
+            self.scrolled = (top + ITEM_HEIGHT).saturating_sub(self.list_dim_y());
+        }
+    }
+
+    // Rows with fullname, among messages and replies.
+    fn rows_mut<'a>(&'a mut self, fullname: &'a str) -> impl Iterator<Item = &'a mut Row> + 'a {
+        self.rows.iter_mut().filter(move |row| row.message.fullname() == fullname)
+    }
+
+    fn draw_tab_bar(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()> {
+        let theme = &tui_prefs.theme;
+        let tab_bar_channels = channels(&theme.page_bar_fg, &theme.page_bar_bg);
+        let current_channels = channels(&theme.page_bar_fg, &theme.page_bar_current_bg);
+        put_cut(surface, 0, 0, &" ".repeat(self.dim_x as usize), tab_bar_channels, NcStyle::None)?;
+        let mut pos = 0;
+        for tab in InboxTab::TABS.iter() {
+            let name = format!(" {} ", tab.name());
+            let tab_channels = if *tab == self.tab { current_channels } else { tab_bar_channels };
+            put_cut(surface, 0, pos, &name, tab_channels, NcStyle::None)?;
+            pos += name.len() as u32;
+        }
+        Ok(())
+    }
+
+    // Draw row with its top line at y, lines outside surface are left out.
+    fn draw_row(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs, row: &Row, y: i64, selected: bool) -> Result<()> {
+        let theme = &tui_prefs.theme;
+        let header_channels = channels(&theme.post_header_fg, &theme.post_header_bg);
+        let heading_channels = if selected {
+            channels(&theme.highlight_fg, &theme.highlight_bg)
+        } else {
+            channels(&theme.post_heading_fg, &theme.post_heading_bg)
+        };
+        let body_channels = channels(&theme.post_body_fg, &theme.post_body_bg);
+
+        const MAX_USERNAME_LEN: u32 = 16;
+
+        let message = &row.message;
+        let indent = row.depth * INDENT;
+        let width = self.dim_x.saturating_sub(indent) as usize;
+        let blank = " ".repeat(width);
+
+        // Header
+        put_cut(surface, y, indent, &blank, header_channels, NcStyle::None)?;
+        put_cut(surface, y, indent, &message.author, header_channels, NcStyle::None)?;
+        let pos = indent + MAX_USERNAME_LEN + 1;
+        let (place, kind) = if message.was_comment {
+            (format!("r/{}", message.subreddit), "comment")
+        } else {
+            (format!("to {}", message.dest), "message")
+        };
+        put_cut(surface, y, pos, &place, header_channels, NcStyle::None)?;
+        let kind = if message.new { format!("{}, unread", kind) } else { kind.to_string() };
+        put_cut(surface, y, self.dim_x.saturating_sub(kind.len() as u32 + 1), &kind, header_channels, NcStyle::None)?;
+
+        // Subject, bold while unread.
+        let heading = if message.was_comment { format!("Re: {}", message.link_title) } else { message.subject.clone() };
+        put_cut(surface, y + 1, indent, &blank, heading_channels, NcStyle::None)?;
+        put_cut(surface, y + 1, indent, &heading, heading_channels, if message.new { NcStyle::Bold } else { NcStyle::None })?;
+
+        // Body
+        let body = wrap(&message.body, width);
+        for i in 0..BODY_LINES {
+            let line = body.get(i).map(|line| line.as_str()).unwrap_or("");
+            let line = format!("{:<1$}", line, width);
+            put_cut(surface, y + 2 + i as i64, indent, &line, body_channels, NcStyle::None)?;
+        }
+        Ok(())
+    }
+
+    fn footer(&self) -> String {
+        let tab = self.tab.name();
+        if self.fetching {
+            format!("Fetching {}...", tab)
+        } else if !self.fetched || self.after.is_some() {
+            format!("More in {}, :load_more fetches them.", tab)
+        } else if self.rows.is_empty() {
+            format!("Nothing in {}.", tab)
+        } else {
+            format!("All of {} fetched.", tab)
+        }
+    }
+}
+
+impl Page for InboxPage {
+    fn name(&self) -> &str {
+        "inbox"
+    }
+
+    fn scroll_down(&mut self) -> Result<()> {
+        let max_scrolled = self.content_len().saturating_sub(self.list_dim_y());
+        if self.scrolled >= max_scrolled {
+            bail!("Bottom reached, cannot scroll down more.");
+        }
+        self.scrolled = (self.scrolled + SCROLL_STEP).min(max_scrolled);
+        Ok(())
+    }
+
+    fn scroll_up(&mut self) -> Result<()> {
+        if self.scrolled == 0 {
+            bail!("Top reached, cannot scroll up more.");
+        }
+        self.scrolled = self.scrolled.saturating_sub(SCROLL_STEP);
+        Ok(())
+    }
+
+    fn scrolled(&self) -> u32 {
+        self.scrolled
+    }
+
+    fn select_next(&mut self) -> Result<()> {
+        if self.selected + 1 >= self.rows.len() {
+            bail!("Last message selected.");
+        }
+        self.selected += 1;
+        self.scroll_to_selected();
+        Ok(())
+    }
+
+    fn select_prev(&mut self) -> Result<()> {
+        if self.selected == 0 {
+            bail!("First message selected.");
+        }
+        self.selected -= 1;
+        self.scroll_to_selected();
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(self.selected).filter(|_| !self.rows.is_empty())
+    }
+
+    fn selected_item(&self) -> Option<Item> {
+        self.rows.get(self.selected).map(|row| Item::Message(row.message.clone()))
+    }
+
+    fn selected_read(&self) -> Option<(String, bool)> {
+        self.rows.get(self.selected).map(|row| (row.message.fullname(), !row.message.new))
+    }
+
+    // Messages marked read stay listed in unread until the tab is listed again, so they can be marked back.
+    fn set_read(&mut self, fullname: &str, read: bool) -> bool {
+        let mut changed = false;
+        for row in self.rows_mut(fullname) {
+            changed |= row.message.new == read;
+            row.message.new = !read;
+        }
+        changed
+    }
+
+    fn set_tab(&mut self, tab: InboxTab) -> Result<()> {
+        if self.fetching {
+            bail!("Still fetching {}, switch tabs once fetched.", self.tab.name());
+        }
+        *self = InboxPage { dim_x: self.dim_x, dim_y: self.dim_y, ..InboxPage::new(tab) };
+        Ok(())
+    }
+
+    fn set_body(&mut self, fullname: &str, body: &str) -> bool {
+        let mut found = false;
+        for row in self.rows_mut(fullname) {
+            row.message.body = body.to_string();
+            found = true;
+        }
+        found
+    }
+
+    fn mark_deleted(&mut self, fullname: &str) -> bool {
+        let mut found = false;
+        for row in self.rows_mut(fullname) {
+            row.message.mark_deleted();
+            found = true;
+        }
+        found
+    }
+
+    fn listing_src(&self) -> Option<ListingSrc> {
+        Some(ListingSrc::Inbox(self.tab))
+    }
+
+    fn fetch_more(&mut self) -> Result<Option<String>> {
+        if self.fetching {
+            bail!("Already fetching {}.", self.tab.name());
+        }
+        if self.fetched && self.after.is_none() {
+            bail!("All of {} is fetched.", self.tab.name());
+        }
+        self.fetching = true;
+        Ok(self.after.clone())
+    }
+
+    // Messages are listed with their replies below them, other items are left out.
+    fn add_listing(&mut self, listing: Listing) -> Result<()> {
+        for item in listing.items {
+            if let Item::Message(mut message) = item {
+                let replies = std::mem::take(&mut message.replies);
+                self.rows.push(Row { depth: 0, message });
+                self.rows.extend(replies.into_iter().map(|message| Row { depth: 1, message }));
+            }
+        }
+        self.after = listing.after;
+        self.fetched = true;
+        self.fetching = false;
+        Ok(())
+    }
+
+    fn fetch_failed(&mut self) {
+        self.fetching = false;
+    }
+
+    fn add_post(&mut self, _post: Post) -> Result<()> {
+        bail!("Inbox lists no posts.")
+    }
+
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()> {
+        let body_channels = channels(&tui_prefs.theme.post_body_fg, &tui_prefs.theme.post_body_bg);
+        surface.fill(body_channels)?;
+        let top = TAB_BAR_HEIGHT as i64 - self.scrolled as i64;
+        for (i, row) in self.rows.iter().enumerate() {
+            let y = top + (i as u32 * ITEM_HEIGHT) as i64;
+            if y + ITEM_HEIGHT as i64 <= TAB_BAR_HEIGHT as i64 { continue; }
+            if y >= self.dim_y as i64 { break; }
+            self.draw_row(surface, tui_prefs, row, y, i == self.selected)?;
+        }
+        let footer_y = top + (self.rows.len() as u32 * ITEM_HEIGHT) as i64;
+        if footer_y >= TAB_BAR_HEIGHT as i64 {
+            put_cut(surface, footer_y, 0, &self.footer(), body_channels, NcStyle::None)?;
+        }
+        // Drawn last, over rows scrolled partly out of view.
+        self.draw_tab_bar(surface, tui_prefs)
+    }
+
+    fn fetch(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn resize(&mut self, dim_x: u32, dim_y: u32) {
+        self.dim_x = dim_x;
+        self.dim_y = dim_y;
+        self.scrolled = self.scrolled.min(self.content_len().saturating_sub(self.list_dim_y()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::model::{ InboxTab, Item, Listing, ListingSrc, Message };
+    use super::InboxPage;
+    use super::super::page::Page;
+
+    fn message(id: &str, new: bool, replies: Vec<Message>) -> Message {
+        Message { id: id.to_string(), subject: format!("subject {}", id), new, replies, ..Message::default() }
+    }
+
+    // Test if conversations are listed with their replies below them, and read state is set on the row selected.
+    #[test]
+    fn test_conversations() {
+        let mut page = InboxPage::new(InboxTab::Messages);
+        page.resize(80, 20);
+        assert_eq!(page.listing_src(), Some(ListingSrc::Inbox(InboxTab::Messages)));
+        assert_eq!(page.fetch_more().unwrap(), None);
+        page.add_listing(Listing {
+            items: vec![
+                Item::Message(message("a", false, vec![message("b", true, vec![])])),
+                Item::Message(Message { was_comment: true, ..message("c", true, vec![]) })
+            ],
+            after: None
+        }).unwrap();
+        assert!(page.fetch_more().is_err());
+
+        page.select_next().unwrap();
+        assert_eq!(page.selected_read(), Some(("t4_b".to_string(), false)));
+        assert!(page.set_read("t4_b", true));
+        assert_eq!(page.selected_read(), Some(("t4_b".to_string(), true)));
+        page.select_next().unwrap();
+        assert_eq!(page.selected_read(), Some(("t1_c".to_string(), false)));
+        assert!(page.select_next().is_err());
+    }
+
+    // Test if switching tabs lists the new tab from its first page, but not while a fetch is under way.
+    #[test]
+    fn test_set_tab() {
+        let mut page = InboxPage::new(InboxTab::Unread);
+        page.resize(80, 20);
+        page.fetch_more().unwrap();
+        assert!(page.set_tab(InboxTab::All).is_err());
+        page.add_listing(Listing { items: vec![Item::Message(message("a", true, vec![]))], after: Some("t4_a".to_string()) }).unwrap();
+
+        page.set_tab(InboxTab::Mentions).unwrap();
+        assert_eq!(page.listing_src(), Some(ListingSrc::Inbox(InboxTab::Mentions)));
+        assert_eq!(page.selected(), None);
+        assert_eq!(page.fetch_more().unwrap(), None);
+    }
+}
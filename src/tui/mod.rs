@@ -2,12 +2,23 @@ mod app;
 mod app_response;
 
 pub(super) mod command_palette;
+pub(super) mod inbox_page;
+pub(super) mod modal;
+pub(super) mod model;
 pub(super) mod page;
+pub(super) mod saved_page;
 pub(super) mod subreddit_listing_page;
+pub(super) mod surface;
+pub(super) mod terminal;
+pub(super) mod text_page;
 pub(super) mod util;
+pub(super) mod view;
 
 pub use app::App;
 pub use app_response::AppRes;
 pub use page::PageType;
-pub use command_palette::cmd_plt_val_input;
-pub use util::{ TuiPrefs, val_tui_prefs_des };
+pub use command_palette::palette_key;
+pub use model::{ AppModel, Confirm, Mode, Palette, PaletteKey };
+pub use surface::{ Cell, CellBuffer, Region, Surface };
+pub use util::{ TuiPrefs, val_color_fmt, val_tui_prefs_des, COLOR_FMT_HINT };
+pub use view::draw_view;
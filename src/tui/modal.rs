@@ -0,0 +1,36 @@
+use anyhow::Result;
+use libnotcurses_sys::NcStyle;
+
+use super::{ surface::Surface, TuiPrefs, util::{ channels, Widget } };
+
+// Blank lines and columns around the prompt.
+const PADDING_Y: u32 = 1;
+const PADDING_X: u32 = 2;
+
+// -----------------------------------------------------------------------------------------------------------
+// Modal widget.
+// * Box over the focused page asking to confirm an action, e.g. "Delete this comment? y/n".
+// * Sized to its prompt, cut to fit the area it is centered in.
+// -----------------------------------------------------------------------------------------------------------
+pub struct Modal {
+    prompt: String
+}
+
+impl Modal {
+    pub fn new(prompt: &str) -> Modal {
+        Modal { prompt: prompt.to_string() }
+    }
+
+    // Size of modal, as dim_y, dim_x.
+    pub fn dim_yx(&self) -> (u32, u32) {
+        (1 + 2 * PADDING_Y, self.prompt.chars().count() as u32 + 2 * PADDING_X)
+    }
+}
+
+impl Widget for Modal {
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()> {
+        let modal_chnls = channels(&tui_prefs.theme.highlight_fg, &tui_prefs.theme.highlight_bg);
+        surface.fill(modal_chnls)?;
+        surface.put_str(PADDING_Y, PADDING_X, &self.prompt, modal_chnls, NcStyle::Bold)
+    }
+}
@@ -0,0 +1,655 @@
+use anyhow::{ anyhow, bail, Result };
+
+use super::page::Page;
+use crate::{
+    api::model::{ Item, Vote },
+    def::commands::*,
+    input::{ KeyBindingsTrie, KeyCombination }
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Normal,
+    // Command typed in palette.
+    Cmd,
+    // Action asked to be confirmed in a modal.
+    Confirm
+}
+
+// Action waiting to be confirmed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Confirm {
+    // Delete own item with fullname.
+    Delete { fullname: String }
+}
+
+// Key editing command palette.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteKey {
+    Char(char),
+    Backspace,
+    Del,
+    Left,
+    Right,
+    Home,
+    End
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Text typed in command palette, without the leading :.
+// * Cursor is a char index into text.
+// -----------------------------------------------------------------------------------------------------------
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Palette {
+    text: Vec<char>,
+    cursor: usize
+}
+
+impl Palette {
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    // Edit text, returns false for backspace on empty text, which leaves command mode.
+    pub fn input(&mut self, key: PaletteKey) -> bool {
+        match key {
+            PaletteKey::Char(c) => {
+                self.text.insert(self.cursor, c);
+                self.cursor += 1;
+            },
+            PaletteKey::Backspace => {
+                if self.text.is_empty() { return false; }
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    self.text.remove(self.cursor);
+                }
+            },
+            PaletteKey::Del => {
+                if self.cursor < self.text.len() { self.text.remove(self.cursor); }
+            },
+            PaletteKey::Left => { self.cursor = self.cursor.saturating_sub(1); },
+            PaletteKey::Right => { self.cursor = (self.cursor + 1).min(self.text.len()); },
+            PaletteKey::Home => { self.cursor = 0; },
+            PaletteKey::End => { self.cursor = self.text.len(); }
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * State of the application, apart from the terminal: pages, focus, keys pending, mode, palette and status.
+// * Transitions are driven by commands and keys, so they run and are tested without Nc.
+// * App renders it with widgets.
+// -----------------------------------------------------------------------------------------------------------
+pub struct AppModel {
+    pages: Vec<Box<dyn Page>>,
+    foc_page: usize,
+
+    mode: Mode,
+    palette: Palette,
+    // Message shown in palette outside command mode.
+    status: Option<String>,
+    // Prompt shown in modal and action it confirms, in confirm mode.
+    confirm: Option<(String, Confirm)>,
+    // Unread messages in the inbox, None until counted.
+    unread: Option<u32>,
+
+    // Keys typed of a key-binding not complete yet.
+    pending_keys: KeyCombination,
+
+    // Size of area below page bar pages are shown in.
+    dim_x: u32,
+    dim_y: u32
+}
+
+impl AppModel {
+    pub fn new(dim_x: u32, dim_y: u32) -> AppModel {
+        AppModel {
+            pages: Vec::new(),
+            foc_page: 0,
+            mode: Mode::Normal,
+            palette: Palette::default(),
+            status: None,
+            confirm: None,
+            unread: None,
+            pending_keys: KeyCombination::new(),
+            dim_x,
+            dim_y
+        }
+    }
+
+    // Add page sized to page area, focusing it. Returns its index.
+    pub fn add_page(&mut self, mut page: Box<dyn Page>) -> usize {
+        page.resize(self.dim_x, self.dim_y);
+        self.pages.push(page);
+        self.foc_page = self.pages.len() - 1;
+        self.foc_page
+    }
+
+    pub fn pages(&self) -> &[Box<dyn Page>] {
+        &self.pages
+    }
+
+    pub fn page_mut(&mut self, page: usize) -> Option<&mut Box<dyn Page>> {
+        self.pages.get_mut(page)
+    }
+
+    pub fn foc_page(&self) -> usize {
+        self.foc_page
+    }
+
+    pub fn foc_page_ref(&self) -> Option<&dyn Page> {
+        self.pages.get(self.foc_page).map(|page| page.as_ref())
+    }
+
+    pub fn foc_page_mut(&mut self) -> Result<&mut Box<dyn Page>> {
+        self.pages.get_mut(self.foc_page).ok_or_else(|| anyhow!("No page open."))
+    }
+
+    pub fn set_foc_page(&mut self, page: usize) -> Result<()> {
+        if page >= self.pages.len() {
+            bail!("No page {}, {} page(s) open", page, self.pages.len());
+        }
+        self.foc_page = page;
+        Ok(())
+    }
+
+    pub fn switch_next_page(&mut self) {
+        if !self.pages.is_empty() {
+            self.foc_page = (self.foc_page + 1) % self.pages.len();
+        }
+    }
+
+    pub fn switch_prev_page(&mut self) {
+        if !self.pages.is_empty() {
+            self.foc_page = (self.foc_page + self.pages.len() - 1) % self.pages.len();
+        }
+    }
+
+    // Fit pages to new size of page area.
+    pub fn resize(&mut self, dim_x: u32, dim_y: u32) {
+        self.dim_x = dim_x;
+        self.dim_y = dim_y;
+        for page in self.pages.iter_mut() {
+            page.resize(dim_x, dim_y);
+        }
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Execute command changing only model state, e.g. scrolling or switching pages.
+    // * Returns false for other commands, left to command_to_event::exec_cmd.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn exec(&mut self, cmd: &str) -> Result<bool> {
+        let args: Vec<&str> = cmd.split(' ').collect();
+        match args[0] {
+            SCROLL_DOWN => { self.foc_page_mut()?.scroll_down()?; },
+            SCROLL_UP => { self.foc_page_mut()?.scroll_up()?; },
+            SELECT_NEXT => { self.foc_page_mut()?.select_next()?; },
+            SELECT_PREV => { self.foc_page_mut()?.select_prev()?; },
+            SWITCH_PAGE => {
+                let page = args.get(1).ok_or_else(|| anyhow!("{}: missing page number", SWITCH_PAGE))?.parse::<usize>()?;
+                self.set_foc_page(page).map_err(|e| anyhow!("{}: {}", SWITCH_PAGE, e))?;
+            },
+            NEXT_PAGE => { self.switch_next_page(); },
+            PREV_PAGE => { self.switch_prev_page(); },
+            _ => { return Ok(false); }
+        }
+        Ok(true)
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Vote on selected item of focused page, on every page holding it, so it shows at once.
+    // * Returns fullname of the item and its vote before, to roll back if Reddit refuses the vote.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn vote(&mut self, vote: Vote) -> Result<(String, Vote)> {
+        let (fullname, prev) = self.foc_page_ref()
+            .and_then(|page| page.selected_vote())
+            .ok_or_else(|| anyhow!("Nothing to vote on selected."))?;
+        self.set_vote(&fullname, vote);
+        Ok((fullname, prev))
+    }
+
+    // Set vote of the item with fullname on every page holding it.
+    pub fn set_vote(&mut self, fullname: &str, vote: Vote) {
+        for page in self.pages.iter_mut() {
+            page.set_vote(fullname, vote);
+        }
+    }
+
+    // Save selected item, or unsave it, on every page holding it. Returns its fullname.
+    pub fn save(&mut self, saved: bool) -> Result<String> {
+        let (fullname, was_saved) = self.foc_page_ref()
+            .and_then(|page| page.selected_saved())
+            .ok_or_else(|| anyhow!("Nothing to save selected."))?;
+        if was_saved == saved {
+            bail!("Selected item is {} already.", if saved { "saved" } else { "not saved" });
+        }
+        self.set_saved(&fullname, saved);
+        Ok(fullname)
+    }
+
+    pub fn set_saved(&mut self, fullname: &str, saved: bool) {
+        for page in self.pages.iter_mut() {
+            page.set_saved(fullname, saved);
+        }
+    }
+
+    // Hide selected post from every page listing it. Returns its fullname.
+    pub fn hide(&mut self) -> Result<String> {
+        let fullname = match self.foc_page_ref().and_then(|page| page.selected_item()) {
+            Some(Item::Post(post)) => post.fullname(),
+            Some(item) => bail!("Only posts can be hidden, selected item is a {}.", item.kind()),
+            None => bail!("Nothing to hide selected.")
+        };
+        if !self.hide_item(&fullname) {
+            bail!("Selected item cannot be hidden on this page.");
+        }
+        Ok(fullname)
+    }
+
+    // Hide item with fullname from every page listing it, returning whether any did.
+    pub fn hide_item(&mut self, fullname: &str) -> bool {
+        let mut hidden = false;
+        for page in self.pages.iter_mut() {
+            hidden |= page.hide(fullname);
+        }
+        hidden
+    }
+
+    // Put item hidden back on pages it was hidden from, returning whether any did.
+    pub fn unhide(&mut self, fullname: &str) -> bool {
+        let mut unhidden = false;
+        for page in self.pages.iter_mut() {
+            unhidden |= page.unhide(fullname);
+        }
+        unhidden
+    }
+
+    // Drop item hidden for good, once it can no longer be unhidden.
+    pub fn forget_hidden(&mut self, fullname: &str) {
+        for page in self.pages.iter_mut() {
+            page.forget_hidden(fullname);
+        }
+    }
+
+    // Set body of item with fullname on every page holding it, e.g. once edited.
+    pub fn set_body(&mut self, fullname: &str, body: &str) {
+        for page in self.pages.iter_mut() {
+            page.set_body(fullname, body);
+        }
+    }
+
+    // Show item with fullname as deleted on every page holding it.
+    pub fn mark_deleted(&mut self, fullname: &str) {
+        for page in self.pages.iter_mut() {
+            page.mark_deleted(fullname);
+        }
+    }
+
+    // Mark selected message read or unread on every page holding it. Returns its fullname.
+    pub fn mark_read(&mut self, read: bool) -> Result<String> {
+        let (fullname, was_read) = self.foc_page_ref()
+            .and_then(|page| page.selected_read())
+            .ok_or_else(|| anyhow!("No message selected."))?;
+        if was_read == read {
+            bail!("Selected message is {} already.", if read { "read" } else { "unread" });
+        }
+        self.set_read(&fullname, read);
+        Ok(fullname)
+    }
+
+    // Set read state of message with fullname on every page holding it, counting it in unread if that changed it.
+    pub fn set_read(&mut self, fullname: &str, read: bool) {
+        let mut changed = false;
+        for page in self.pages.iter_mut() {
+            changed |= page.set_read(fullname, read);
+        }
+        if changed {
+            self.unread = self.unread.map(|unread| if read { unread.saturating_sub(1) } else { unread + 1 });
+        }
+    }
+
+    pub fn unread(&self) -> Option<u32> {
+        self.unread
+    }
+
+    pub fn set_unread(&mut self, unread: u32) {
+        self.unread = Some(unread);
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Add key typed in normal mode to keys pending.
+    // * Returns command line once they complete a key-binding. Keys not leading to one are dropped.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn press(&mut self, kbt: &KeyBindingsTrie, mut key: KeyCombination) -> Option<String> {
+        self.pending_keys.append(&mut key);
+        if !kbt.is_prefix(&self.pending_keys) {
+            self.pending_keys.clear();
+            return None;
+        }
+        let cmd = kbt.get(&self.pending_keys).cloned();
+        if cmd.is_some() { self.pending_keys.clear(); }
+        cmd
+    }
+
+    pub fn pending_keys(&self) -> &KeyCombination {
+        &self.pending_keys
+    }
+
+    pub fn clear_pending_keys(&mut self) {
+        self.pending_keys.clear();
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    // Show message in palette until command mode is entered.
+    pub fn show_status(&mut self, msg: &str) {
+        self.status = Some(msg.to_string());
+    }
+
+    // Ask to confirm action in a modal showing prompt, until answered.
+    pub fn ask_confirm(&mut self, prompt: &str, action: Confirm) {
+        self.mode = Mode::Confirm;
+        self.confirm = Some((prompt.to_string(), action));
+        self.status = None;
+        self.pending_keys.clear();
+    }
+
+    pub fn confirm_prompt(&self) -> Option<&str> {
+        self.confirm.as_ref().map(|(prompt, _)| prompt.as_str())
+    }
+
+    // Leave confirm mode, returning the action if confirmed.
+    pub fn answer_confirm(&mut self, confirmed: bool) -> Option<Confirm> {
+        self.mode = Mode::Normal;
+        self.confirm.take().map(|(_, action)| action).filter(|_| confirmed)
+    }
+
+    pub fn enter_cmd(&mut self) {
+        self.mode = Mode::Cmd;
+        self.palette.clear();
+        self.status = None;
+        self.pending_keys.clear();
+    }
+
+    pub fn exit_cmd(&mut self) {
+        self.mode = Mode::Normal;
+        self.palette.clear();
+    }
+
+    // Edit palette, leaving command mode on backspace with nothing typed.
+    pub fn palette_input(&mut self, key: PaletteKey) -> Mode {
+        if !self.palette.input(key) {
+            self.exit_cmd();
+        }
+        self.mode
+    }
+
+    // Take command typed in palette, leaving command mode.
+    pub fn take_cmd(&mut self) -> String {
+        let cmd = self.palette.text();
+        self.exit_cmd();
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::model::{ InboxTab, Item, Listing, Message, Post, Vote },
+        jobs::Config,
+        input::{ create_key_bindings_trie, KeyBindingsTrie }
+    };
+    use super::{ AppModel, Confirm, Mode, PaletteKey };
+    use super::super::{ inbox_page::InboxPage, page::Page, subreddit_listing_page::SubListPage, text_page::TextPage };
+
+    // Model with two listing pages of 13 posts and a text page, 80x20.
+    fn model() -> AppModel {
+        let mut model = AppModel::new(80, 20);
+        for subreddit in ["rust", "linux"] {
+            let mut page = SubListPage::new(subreddit);
+            for i in 0..13 {
+                page.add_post(Post { id: i.to_string(), title: format!("post {}", i), score: i, ..Post::default() }).unwrap();
+            }
+            model.add_page(Box::new(page));
+        }
+        model.add_page(Box::new(TextPage::new("text", "line 1\nline 2")));
+        model
+    }
+
+    fn kbt() -> KeyBindingsTrie {
+        let config = Config::default();
+        create_key_bindings_trie(&config.key_bindings, &config.leader).unwrap()
+    }
+
+    // Feed keys in key-binding notation, executing commands they complete.
+    fn press(model: &mut AppModel, kbt: &KeyBindingsTrie, keys: &str) {
+        let keys = kbt.parse(keys).unwrap();
+        for key in keys {
+            if let Some(cmd) = model.press(kbt, vec![key]) {
+                // Scrolling past the ends fails, leaving state as it was.
+                let _ = model.exec(&cmd);
+            }
+        }
+    }
+
+    // Test if page switching commands and keys change focus, wrapping around, and bad pages are refused.
+    #[test]
+    fn test_switch_page() {
+        let (mut model, kbt) = (model(), kbt());
+        assert_eq!(model.foc_page(), 2);
+
+        assert!(model.exec("switch_page 0").unwrap());
+        assert_eq!(model.foc_page(), 0);
+        assert!(model.exec("switch_page 3").is_err());
+        assert!(model.exec("switch_page").is_err());
+        assert_eq!(model.foc_page(), 0);
+
+        press(&mut model, &kbt, "g<S-t>");
+        assert_eq!(model.foc_page(), 2);
+        press(&mut model, &kbt, "gtgt");
+        assert_eq!(model.foc_page(), 1);
+        assert!(!model.exec("config").unwrap());
+    }
+
+    // Test if keys pending are kept until a key-binding completes and dropped if they lead to none.
+    #[test]
+    fn test_press() {
+        let (mut model, kbt) = (model(), kbt());
+        assert_eq!(model.press(&kbt, kbt.parse("g").unwrap()), None);
+        assert_eq!(model.pending_keys(), &kbt.parse("g").unwrap());
+        assert_eq!(model.press(&kbt, kbt.parse("t").unwrap()), Some("next_page".to_string()));
+        assert!(model.pending_keys().is_empty());
+
+        model.press(&kbt, kbt.parse("g").unwrap());
+        assert_eq!(model.press(&kbt, kbt.parse("x").unwrap()), None);
+        assert!(model.pending_keys().is_empty());
+    }
+
+    // Test if scrolling and selection move within page bounds, selection scrolling into view.
+    #[test]
+    fn test_scroll_and_select() {
+        let (mut model, kbt) = (model(), kbt());
+        model.set_foc_page(0).unwrap();
+        let page = |model: &AppModel| (model.foc_page_ref().unwrap().scrolled(), model.foc_page_ref().unwrap().selected());
+
+        press(&mut model, &kbt, "k");
+        assert_eq!(page(&model), (0, Some(0)));
+        press(&mut model, &kbt, "jjj");
+        assert_eq!(page(&model), (6, Some(0)));
+
+        // 13 posts of 5 lines in 20 lines scroll down to 45 at most.
+        press(&mut model, &kbt, &"j".repeat(30));
+        assert_eq!(page(&model).0, 45);
+        assert!(model.exec("scroll_down").is_err());
+
+        // Selection above view scrolls up to it, below view scrolls down to it.
+        press(&mut model, &kbt, "<S-j>");
+        assert_eq!(page(&model), (5, Some(1)));
+        press(&mut model, &kbt, &"<S-j>".repeat(4));
+        assert_eq!(page(&model), (10, Some(5)));
+        press(&mut model, &kbt, "<S-j>");
+        assert_eq!(page(&model), (15, Some(6)));
+        assert!(model.exec("select_prev").is_ok());
+        assert_eq!(page(&model), (15, Some(5)));
+
+        // Text page has nothing to select.
+        model.set_foc_page(2).unwrap();
+        assert!(model.exec("select_next").is_err());
+        assert_eq!(page(&model), (0, None));
+    }
+
+    // Test if votes apply on every page holding the post and roll back to the vote before.
+    #[test]
+    fn test_vote() {
+        let mut model = model();
+        let vote = |model: &AppModel, page: usize| model.pages()[page].selected_vote().map(|(_, vote)| vote);
+        assert!(model.vote(Vote::Up).is_err());
+
+        model.set_foc_page(0).unwrap();
+        model.exec("select_next").unwrap();
+        assert_eq!(model.vote(Vote::Up).unwrap(), ("t3_1".to_string(), Vote::None));
+        model.set_foc_page(1).unwrap();
+        model.exec("select_next").unwrap();
+        assert_eq!(vote(&model, 1), Some(Vote::Up));
+        assert_eq!(model.vote(Vote::Down).unwrap(), ("t3_1".to_string(), Vote::Up));
+        assert_eq!(vote(&model, 0), Some(Vote::Down));
+
+        model.set_vote("t3_1", Vote::Up);
+        assert_eq!((vote(&model, 0), vote(&model, 1)), (Some(Vote::Up), Some(Vote::Up)));
+    }
+
+    // Test if hidden posts leave every page and come back in place, and saving toggles saved state.
+    #[test]
+    fn test_hide_and_save() {
+        let mut model = model();
+        model.set_foc_page(2).unwrap();
+        assert!(model.hide().is_err());
+
+        model.set_foc_page(0).unwrap();
+        model.exec("select_next").unwrap();
+        assert_eq!(model.hide().unwrap(), "t3_1");
+        let selected = |model: &AppModel, page: usize| model.pages()[page].selected_saved().map(|(fullname, _)| fullname);
+        assert_eq!(selected(&model, 0), Some("t3_2".to_string()));
+        assert_eq!(selected(&model, 1), Some("t3_0".to_string()));
+        model.exec("select_prev").unwrap();
+        model.exec("select_next").unwrap();
+        model.exec("select_next").unwrap();
+        assert_eq!(selected(&model, 0), Some("t3_3".to_string()));
+
+        assert!(model.unhide("t3_1"));
+        assert_eq!(selected(&model, 0), Some("t3_1".to_string()));
+        model.hide().unwrap();
+        model.forget_hidden("t3_1");
+        assert!(!model.unhide("t3_1"));
+
+        assert_eq!(model.save(true).unwrap(), "t3_2");
+        assert_eq!(model.pages()[0].selected_saved(), Some(("t3_2".to_string(), true)));
+        assert!(model.save(true).is_err());
+        assert_eq!(model.save(false).unwrap(), "t3_2");
+    }
+
+    // Test if confirm mode returns the action only if confirmed, and deleted or edited items change on every page.
+    #[test]
+    fn test_confirm() {
+        let mut model = model();
+        let delete = Confirm::Delete { fullname: "t3_0".to_string() };
+        model.ask_confirm("Delete this post? y/n", delete.clone());
+        assert_eq!((model.mode(), model.confirm_prompt()), (Mode::Confirm, Some("Delete this post? y/n")));
+        assert_eq!(model.answer_confirm(false), None);
+        assert_eq!((model.mode(), model.confirm_prompt()), (Mode::Normal, None));
+
+        model.ask_confirm("Delete this post? y/n", delete.clone());
+        assert_eq!(model.answer_confirm(true), Some(delete));
+        model.mark_deleted("t3_0");
+        model.set_foc_page(1).unwrap();
+        assert_eq!(model.foc_page_ref().unwrap().selected_item().unwrap().author(), "[deleted]");
+        model.set_body("t3_0", "Fixed typo");
+        assert_eq!(model.pages()[0].selected_item().unwrap().body(), "Fixed typo");
+    }
+
+    // Test if marking messages read or unread counts them in unread, only for messages listed.
+    #[test]
+    fn test_mark_read() {
+        let mut model = model();
+        assert!(model.mark_read(true).is_err());
+
+        let messages = || Listing {
+            items: ["a", "b"].iter().map(|id| Item::Message(Message { id: id.to_string(), new: true, ..Message::default() })).collect(),
+            after: None
+        };
+        let mut page = InboxPage::new(InboxTab::Unread);
+        page.add_listing(messages()).unwrap();
+        model.add_page(Box::new(page));
+        model.set_unread(2);
+
+        assert_eq!(model.mark_read(true).unwrap(), "t4_a");
+        assert_eq!(model.unread(), Some(1));
+        assert!(model.mark_read(true).is_err());
+        model.set_read("t4_c", true);
+        assert_eq!(model.unread(), Some(1));
+        assert_eq!(model.mark_read(false).unwrap(), "t4_a");
+        assert_eq!(model.unread(), Some(2));
+
+        // Messages listed on two pages count once, only when their state changes.
+        let mut page = InboxPage::new(InboxTab::All);
+        page.add_listing(messages()).unwrap();
+        model.add_page(Box::new(page));
+        model.set_read("t4_b", false);
+        assert_eq!(model.unread(), Some(2));
+        model.set_read("t4_b", true);
+        model.set_read("t4_b", true);
+        assert_eq!(model.unread(), Some(1));
+    }
+
+    // Test if palette edits text at cursor and command mode is left on enter, escape or backspace on empty.
+    #[test]
+    fn test_palette() {
+        let mut model = model();
+        model.show_status("Config reloaded.");
+        model.enter_cmd();
+        assert_eq!((model.mode(), model.status()), (Mode::Cmd, None));
+
+        for c in "switch_pge 1".chars() {
+            model.palette_input(PaletteKey::Char(c));
+        }
+        for key in [PaletteKey::Left, PaletteKey::Left, PaletteKey::Left, PaletteKey::Left] {
+            model.palette_input(key);
+        }
+        model.palette_input(PaletteKey::Char('a'));
+        model.palette_input(PaletteKey::End);
+        assert_eq!(model.palette().text(), "switch_page 1");
+        assert_eq!(model.palette().cursor(), 13);
+        assert_eq!(model.take_cmd(), "switch_page 1");
+        assert_eq!((model.mode(), model.palette().text()), (Mode::Normal, "".to_string()));
+
+        model.enter_cmd();
+        model.palette_input(PaletteKey::Char('x'));
+        assert_eq!(model.palette_input(PaletteKey::Backspace), Mode::Cmd);
+        assert_eq!(model.palette_input(PaletteKey::Backspace), Mode::Normal);
+
+        model.enter_cmd();
+        model.palette_input(PaletteKey::Char('x'));
+        model.exit_cmd();
+        assert_eq!((model.mode(), model.palette().text()), (Mode::Normal, "".to_string()));
+    }
+}
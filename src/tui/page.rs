@@ -1,101 +1,181 @@
-use anyhow::Result;
-use libnotcurses_sys::{ 
-    NcChannels,
-    NcPlane,
-    NcPlaneOptions
-};
+use anyhow::{ bail, Result };
+use libnotcurses_sys::NcStyle;
 
-use super::{ TuiPrefs, util::{ new_child_plane, PostData, Widget} };
+use super::{ surface::Surface, TuiPrefs, util::{ channels, Widget } };
+use crate::{ api::model::{ InboxTab, Item, Listing, ListingSrc, Post, Vote }, events::event::Event };
 
 #[derive(Debug)]
 pub enum PageType {
-    SubredditListing
+    SubredditListing { subreddit: String },
+    // Posts and comments saved by the account logged in.
+    Saved,
+    // Messages, comment replies and mentions of the account logged in, listing tab.
+    Inbox { tab: InboxTab }
 }
 
 // -----------------------------------------------------------------------------------------------------------
 // Page encapsulate functionality required to function as a "page".
+// * Pages hold their state only, the focused one is drawn below the page bar.
 // -----------------------------------------------------------------------------------------------------------
 pub trait Page: Send {
-
-    // -------------------------------------------------------------------------------------------------------
-    // * Set visibility of page.
-    // * If not visible, the page is shifted to right by the length of width, making it invisible
-    //   on render. 
-    // * For making it visible again, the shift is undoed.
-    // -------------------------------------------------------------------------------------------------------
-    fn set_visibility(&mut self, visible: bool) -> Result<()>;
+    // Name shown in page bar.
+    fn name(&self) -> &str;
 
     // Scroll up / down.
     fn scroll_up(&mut self) -> Result<()>;
     fn scroll_down(&mut self) -> Result<()>;
+    // Lines scrolled down, 0 initially.
+    fn scrolled(&self) -> u32;
+
+    // Select next / previous item, scrolling it into view.
+    fn select_next(&mut self) -> Result<()> {
+        bail!("Nothing to select on this page.")
+    }
+    fn select_prev(&mut self) -> Result<()> {
+        bail!("Nothing to select on this page.")
+    }
+    // Index of selected item, None if page has no items to select.
+    fn selected(&self) -> Option<usize> {
+        None
+    }
+
+    // Selected post or comment, None if page lists none.
+    fn selected_item(&self) -> Option<Item> {
+        None
+    }
+    // Subreddit page lists, e.g. to submit to.
+    fn subreddit(&self) -> Option<&str> {
+        None
+    }
+
+    // Fullname and vote of selected item, None if nothing votable is selected.
+    fn selected_vote(&self) -> Option<(String, Vote)> {
+        None
+    }
+    // Set vote of the item with fullname, returning whether page holds it.
+    fn set_vote(&mut self, _fullname: &str, _vote: Vote) -> bool {
+        false
+    }
+    // Fullname and saved state of selected item, None if nothing savable is selected.
+    fn selected_saved(&self) -> Option<(String, bool)> {
+        None
+    }
+    // Set saved state of the item with fullname, returning whether page holds it.
+    fn set_saved(&mut self, _fullname: &str, _saved: bool) -> bool {
+        false
+    }
+
+    // Set markdown body of the item with fullname, returning whether page holds it.
+    // TODO: Update comments in place on a comment thread page too, once there is one. Edits and deletes of
+    // comments only show on listing and saved pages for now.
+    fn set_body(&mut self, _fullname: &str, _body: &str) -> bool {
+        false
+    }
+    // Show the item with fullname as deleted, returning whether page holds it.
+    fn mark_deleted(&mut self, _fullname: &str) -> bool {
+        false
+    }
+
+    // Fullname and read state of selected message, None if no message is selected.
+    fn selected_read(&self) -> Option<(String, bool)> {
+        None
+    }
+    // Set read state of the message with fullname, returning whether that changed any of its rows.
+    fn set_read(&mut self, _fullname: &str, _read: bool) -> bool {
+        false
+    }
+    // List tab of page instead, dropping the items listed so its first page is fetched next.
+    fn set_tab(&mut self, _tab: InboxTab) -> Result<()> {
+        bail!("Page has no tabs.")
+    }
+
+    // Take the item with fullname out of page, keeping it for unhide until forgotten. Returns whether page held it.
+    fn hide(&mut self, _fullname: &str) -> bool {
+        false
+    }
+    // Put hidden item back where it was, returning whether page held it.
+    fn unhide(&mut self, _fullname: &str) -> bool {
+        false
+    }
+    fn forget_hidden(&mut self, _fullname: &str) {  }
+
+    // Listing page fetches a page at a time, None if it fetches none.
+    fn listing_src(&self) -> Option<ListingSrc> {
+        None
+    }
+    // -------------------------------------------------------------------------------------------------------
+    // * Cursor to fetch the next page of items after, None for the first page.
+    // * Marks a fetch under way, fails while one is or once all items are fetched.
+    // -------------------------------------------------------------------------------------------------------
+    fn fetch_more(&mut self) -> Result<Option<String>> {
+        bail!("Nothing more to fetch on this page.")
+    }
+    // Add page of items fetched, ending the fetch under way.
+    fn add_listing(&mut self, _listing: Listing) -> Result<()> {
+        bail!("Page lists no fetched items.")
+    }
+    fn fetch_failed(&mut self) {  }
 
     // Listing functions.
-    fn add_post(&mut self, tui_prefs: &TuiPrefs, data: PostData) -> Result<()>;
+    fn add_post(&mut self, post: Post) -> Result<()>;
 
-    // Draw widgets onto plane.
-    fn draw(&mut self, tui_prefs: &TuiPrefs) -> Result<()>;
+    // Draw page onto surface sized as page.
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()>;
 
     // Fetch data.
     fn fetch(&mut self) -> Result<()>;
+
+    // Fit page to new size of area below page bar.
+    fn resize(&mut self, dim_x: u32, dim_y: u32);
+
+    // Handle event routed to the focused page, returning whether it was handled.
+    fn handle_event(&mut self, _event: &Event) -> Result<bool> {
+        Ok(false)
+    }
 }
 
 // -----------------------------------------------------------------------------------------------------------
 // PageBar widget
 // * Shows the currnet page and list of pages on top.
+// * Unread messages in the inbox are counted at its right end, once there are any.
 // -----------------------------------------------------------------------------------------------------------
-pub struct PageBar<'a> {
-    pub plane: &'a mut NcPlane,
-    pub foc_page: u32,
-    page_names: Vec<&'a str>
+pub struct PageBar {
+    foc_page: u32,
+    page_names: Vec<String>,
+    unread: Option<u32>
 }
 
-impl<'a> Widget for PageBar<'a> {
-    fn new(tui_prefs: &TuiPrefs,
-                    parent_plane: &mut NcPlane,
-                    x: i32,
-                    y: i32,
-                    dim_x: u32,
-                    dim_y: u32
-                   ) -> Result<Self> {
-        let plane = new_child_plane!(parent_plane, x, y, dim_x, dim_y);
-        plane.set_base(
-            " ",
-            0,
-            NcChannels::from_rgb(
-                tui_prefs.theme.page_bar_fg.to_nc_rgb(),
-                tui_prefs.theme.page_bar_bg.to_nc_rgb(),
-            ))?;
-
-        Ok(PageBar {
-            plane,
-            foc_page: 0,
-            page_names: vec!("hello", "yoyou")
-        })
-    }
-
-    fn draw(&mut self, tui_prefs: &TuiPrefs) -> Result<()> {
+impl PageBar {
+    pub fn new(page_names: Vec<String>, foc_page: usize, unread: Option<u32>) -> PageBar {
+        PageBar {
+            foc_page: foc_page as u32,
+            page_names,
+            unread
+        }
+    }
+}
+
+impl Widget for PageBar {
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()> {
         const PAGE_NAME_WIDTH: u32 = 7;
+        let page_bar_chnls = channels(&tui_prefs.theme.page_bar_fg, &tui_prefs.theme.page_bar_bg);
+        surface.fill(page_bar_chnls)?;
         for (pos, e) in self.page_names.iter().enumerate() {
-            if ((pos as u32) + 1) * PAGE_NAME_WIDTH < self.plane.dim_x() {
-                self.plane.putstr_yx(Some(0), Some((pos as u32) * PAGE_NAME_WIDTH),
-                    &format!("{}:{}", pos, &e[0..4])
+            if ((pos as u32) + 1) * PAGE_NAME_WIDTH < surface.dim_x() {
+                surface.put_str(0, (pos as u32) * PAGE_NAME_WIDTH,
+                    &format!("{}:{}", pos, e.chars().take(4).collect::<String>()),
+                    page_bar_chnls,
+                    NcStyle::None
                 )?;
             }
         }
-        let current_page_chnls = NcChannels::from_rgb(
-            tui_prefs.theme.page_bar_fg.to_nc_rgb(),
-            tui_prefs.theme.page_bar_current_bg.to_nc_rgb()
-        );
-        self.plane.stain(
-            Some(0),
-            Some(self.foc_page * PAGE_NAME_WIDTH),
-            Some(1),
-            Some(PAGE_NAME_WIDTH),
-            current_page_chnls,
-            current_page_chnls,
-            current_page_chnls,
-            current_page_chnls,
-        )?;
+        let current_page_chnls = channels(&tui_prefs.theme.page_bar_fg, &tui_prefs.theme.page_bar_current_bg);
+        surface.stain(0, self.foc_page * PAGE_NAME_WIDTH, PAGE_NAME_WIDTH, current_page_chnls)?;
+        if let Some(unread) = self.unread.filter(|unread| *unread > 0) {
+            let badge = format!(" {} unread ", unread);
+            let x = surface.dim_x().saturating_sub(badge.len() as u32);
+            surface.put_str(0, x, &badge, current_page_chnls, NcStyle::Bold)?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,322 @@
+use anyhow::{ bail, Result };
+use libnotcurses_sys::NcStyle;
+
+use crate::api::model::{ Item, Listing, ListingSrc, Post, Vote };
+use super::{ page::Page, surface::Surface, TuiPrefs, util::{ channels, put_cut, wrap } };
+
+// Lines taken by an item: header, heading and body.
+const ITEM_HEIGHT: u32 = 4;
+const BODY_LINES: usize = 2;
+// Line below the items telling whether more are fetched.
+const FOOTER_HEIGHT: u32 = 1;
+// Lines scrolled at a time.
+const SCROLL_STEP: u32 = 2;
+
+// -----------------------------------------------------------------------------------------------------------
+// Page for displaying posts and comments saved by the account logged in.
+// * Items are fetched a page at a time, fetch_more gives the cursor of the next page.
+// -----------------------------------------------------------------------------------------------------------
+pub struct SavedPage {
+    items: Vec<Item>,
+
+    // Cursor of the next page of items, None before the first page is fetched and after the last one.
+    after: Option<String>,
+    fetched: bool, // Whether a page was fetched.
+    fetching: bool, // Whether a fetch is under way.
+
+    scrolled: u32, // Lines scrolled down, 0 initially.
+    selected: usize, // Index of selected item.
+
+    dim_x: u32,
+    dim_y: u32
+}
+
+impl SavedPage {
+    pub fn new() -> SavedPage {
+        SavedPage {
+            items: vec![],
+            after: None,
+            fetched: false,
+            fetching: false,
+            scrolled: 0,
+            selected: 0,
+            dim_x: 0,
+            dim_y: 0
+        }
+    }
+
+    fn content_len(&self) -> u32 {
+        self.items.len() as u32 * ITEM_HEIGHT + FOOTER_HEIGHT
+    }
+
+    // Scroll selected item into view.
+    fn scroll_to_selected(&mut self) {
+        let top = self.selected as u32 * ITEM_HEIGHT;
+        if top < self.scrolled {
+            self.scrolled = top;
+        } else if top + ITEM_HEIGHT > self.scrolled + self.dim_y {
+            self.scrolled = (top + ITEM_HEIGHT).saturating_sub(self.dim_y);
+        }
+    }
+
+    // Draw item with its top line at y, lines outside surface are left out.
+    fn draw_item(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs, item: &Item, y: i64, selected: bool) -> Result<()> {
+        let theme = &tui_prefs.theme;
+        let header_channels = channels(&theme.post_header_fg, &theme.post_header_bg);
+        let heading_channels = if selected {
+            channels(&theme.highlight_fg, &theme.highlight_bg)
+        } else {
+            channels(&theme.post_heading_fg, &theme.post_heading_bg)
+        };
+        let body_channels = channels(&theme.post_body_fg, &theme.post_body_bg);
+
+        const SCORE_WIDTH: u32 = 7;
+        const MAX_USERNAME_LEN: u32 = 16;
+
+        let (score, author, subreddit, kind, heading, body) = match item {
+            Item::Post(Post { score, author, subreddit, title, selftext, .. }) =>
+                (score, author, subreddit, "post", title.clone(), selftext),
+            Item::Comment(comment) =>
+                (&comment.score, &comment.author, &comment.subreddit, "comment", format!("Re: {}", comment.link_title), &comment.body),
+            // Left out by add_listing, skipped should one get here anyway.
+            Item::Message(_) => { return Ok(()); }
+        };
+
+        // Header
+        let blank = " ".repeat(self.dim_x as usize);
+        put_cut(surface, y, 0, &blank, header_channels, NcStyle::None)?;
+        let score_channels = match item.vote() {
+            Vote::Up => channels(&theme.post_upvoted_fg, &theme.post_upvoted_bg),
+            Vote::Down => channels(&theme.post_downvoted_fg, &theme.post_downvoted_bg),
+            Vote::None => header_channels
+        };
+        put_cut(surface, y, 0, &format!("{:<1$}", score, SCORE_WIDTH as usize - 1), score_channels, NcStyle::None)?;
+        let mut pos = SCORE_WIDTH + 1;
+        put_cut(surface, y, pos, author, header_channels, NcStyle::None)?;
+        pos = pos + MAX_USERNAME_LEN + 1;
+        put_cut(surface, y, pos, subreddit, header_channels, NcStyle::None)?;
+        let kind = if item.saved() { kind.to_string() } else { format!("{}, unsaved", kind) };
+        put_cut(surface, y, self.dim_x.saturating_sub(kind.len() as u32 + 1), &kind, header_channels, NcStyle::None)?;
+
+        // Heading
+        put_cut(surface, y + 1, 0, &blank, heading_channels, NcStyle::None)?;
+        put_cut(surface, y + 1, 0, &heading, heading_channels, NcStyle::Bold)?;
+
+        // Body
+        let body = wrap(body, self.dim_x as usize);
+        for i in 0..BODY_LINES {
+            let line = body.get(i).map(|line| line.as_str()).unwrap_or("");
+            let line = format!("{:<1$}", line, self.dim_x as usize);
+            put_cut(surface, y + 2 + i as i64, 0, &line, body_channels, NcStyle::None)?;
+        }
+        Ok(())
+    }
+
+    fn footer(&self) -> &str {
+        if self.fetching {
+            "Fetching saved items..."
+        } else if !self.fetched || self.after.is_some() {
+            "More saved items, :load_more fetches them."
+        } else if self.items.is_empty() {
+            "No saved items."
+        } else {
+            "All saved items fetched."
+        }
+    }
+}
+
+impl Page for SavedPage {
+    fn name(&self) -> &str {
+        "saved"
+    }
+
+    fn scroll_down(&mut self) -> Result<()> {
+        let max_scrolled = self.content_len().saturating_sub(self.dim_y);
+        if self.scrolled >= max_scrolled {
+            bail!("Bottom reached, cannot scroll down more.");
+        }
+        self.scrolled = (self.scrolled + SCROLL_STEP).min(max_scrolled);
+        Ok(())
+    }
+
+    fn scroll_up(&mut self) -> Result<()> {
+        if self.scrolled == 0 {
+            bail!("Top reached, cannot scroll up more.");
+        }
+        self.scrolled = self.scrolled.saturating_sub(SCROLL_STEP);
+        Ok(())
+    }
+
+    fn scrolled(&self) -> u32 {
+        self.scrolled
+    }
+
+    fn select_next(&mut self) -> Result<()> {
+        if self.selected + 1 >= self.items.len() {
+            bail!("Last saved item selected.");
+        }
+        self.selected += 1;
+        self.scroll_to_selected();
+        Ok(())
+    }
+
+    fn select_prev(&mut self) -> Result<()> {
+        if self.selected == 0 {
+            bail!("First saved item selected.");
+        }
+        self.selected -= 1;
+        self.scroll_to_selected();
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<usize> {
+        Some(self.selected).filter(|_| !self.items.is_empty())
+    }
+
+    fn selected_item(&self) -> Option<Item> {
+        self.items.get(self.selected).cloned()
+    }
+
+    fn selected_vote(&self) -> Option<(String, Vote)> {
+        self.items.get(self.selected).map(|item| (item.fullname(), item.vote()))
+    }
+
+    fn set_vote(&mut self, fullname: &str, vote: Vote) -> bool {
+        let mut found = false;
+        for item in self.items.iter_mut().filter(|item| item.fullname() == fullname) {
+            item.set_vote(vote);
+            found = true;
+        }
+        found
+    }
+
+    fn selected_saved(&self) -> Option<(String, bool)> {
+        self.items.get(self.selected).map(|item| (item.fullname(), item.saved()))
+    }
+
+    // Unsaved items stay listed until the page is opened again, so they can be saved back.
+    fn set_saved(&mut self, fullname: &str, saved: bool) -> bool {
+        let mut found = false;
+        for item in self.items.iter_mut().filter(|item| item.fullname() == fullname) {
+            item.set_saved(saved);
+            found = true;
+        }
+        found
+    }
+
+    fn set_body(&mut self, fullname: &str, body: &str) -> bool {
+        let mut found = false;
+        for item in self.items.iter_mut().filter(|item| item.fullname() == fullname) {
+            item.set_body(body);
+            found = true;
+        }
+        found
+    }
+
+    fn mark_deleted(&mut self, fullname: &str) -> bool {
+        let mut found = false;
+        for item in self.items.iter_mut().filter(|item| item.fullname() == fullname) {
+            item.mark_deleted();
+            found = true;
+        }
+        found
+    }
+
+    fn listing_src(&self) -> Option<ListingSrc> {
+        Some(ListingSrc::Saved)
+    }
+
+    fn fetch_more(&mut self) -> Result<Option<String>> {
+        if self.fetching {
+            bail!("Already fetching saved items.");
+        }
+        if self.fetched && self.after.is_none() {
+            bail!("All saved items are fetched.");
+        }
+        self.fetching = true;
+        Ok(self.after.clone())
+    }
+
+    fn add_listing(&mut self, listing: Listing) -> Result<()> {
+        // Only posts and comments can be saved, messages have no score to show either.
+        self.items.extend(listing.items.into_iter().filter(|item| !matches!(item, Item::Message(_))));
+        self.after = listing.after;
+        self.fetched = true;
+        self.fetching = false;
+        Ok(())
+    }
+
+    fn fetch_failed(&mut self) {
+        self.fetching = false;
+    }
+
+    fn add_post(&mut self, post: Post) -> Result<()> {
+        self.items.push(Item::Post(post));
+        Ok(())
+    }
+
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()> {
+        let body_channels = channels(&tui_prefs.theme.post_body_fg, &tui_prefs.theme.post_body_bg);
+        surface.fill(body_channels)?;
+        for (i, item) in self.items.iter().enumerate() {
+            let y = (i as u32 * ITEM_HEIGHT) as i64 - self.scrolled as i64;
+            if y + ITEM_HEIGHT as i64 <= 0 { continue; }
+            if y >= self.dim_y as i64 { break; }
+            self.draw_item(surface, tui_prefs, item, y, i == self.selected)?;
+        }
+        let footer_y = (self.items.len() as u32 * ITEM_HEIGHT) as i64 - self.scrolled as i64;
+        put_cut(surface, footer_y, 0, self.footer(), body_channels, NcStyle::None)?;
+        Ok(())
+    }
+
+    fn fetch(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn resize(&mut self, dim_x: u32, dim_y: u32) {
+        self.dim_x = dim_x;
+        self.dim_y = dim_y;
+        self.scrolled = self.scrolled.min(self.content_len().saturating_sub(dim_y));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::model::{ Comment, Item, Listing, Message, Post };
+    use super::SavedPage;
+    use super::super::page::Page;
+
+    fn listing(ids: &[&str], after: Option<&str>) -> Listing {
+        let items = ids.iter().map(|id| match id.strip_prefix('c') {
+            Some(id) => Item::Comment(Comment { id: id.to_string(), saved: true, ..Comment::default() }),
+            None => Item::Post(Post { id: id.to_string(), saved: true, ..Post::default() })
+        }).collect();
+        Listing { items, after: after.map(|after| after.to_string()) }
+    }
+
+    // Test if pages are fetched after the cursor of the last one, one at a time, until the last one.
+    #[test]
+    fn test_pagination() {
+        let mut page = SavedPage::new();
+        page.resize(80, 20);
+        assert_eq!(page.fetch_more().unwrap(), None);
+        assert!(page.fetch_more().is_err());
+        page.fetch_failed();
+        assert_eq!(page.fetch_more().unwrap(), None);
+
+        page.add_listing(listing(&["a", "cb"], Some("t1_b"))).unwrap();
+        assert_eq!(page.fetch_more().unwrap(), Some("t1_b".to_string()));
+        let mut last = listing(&["c"], None);
+        last.items.push(Item::Message(Message::default()));
+        page.add_listing(last).unwrap();
+        assert!(page.fetch_more().is_err());
+        assert_eq!(page.items.len(), 3);
+
+        page.select_next().unwrap();
+        assert_eq!(page.selected_saved(), Some(("t1_b".to_string(), true)));
+        assert!(page.set_saved("t1_b", false));
+        assert_eq!(page.selected_saved(), Some(("t1_b".to_string(), false)));
+        page.select_next().unwrap();
+        assert!(page.select_next().is_err());
+    }
+}
@@ -1,269 +1,250 @@
 use anyhow::{ bail, Result };
-use libnotcurses_sys::{
-    NcAlign,
-    NcChannel,
-    NcChannels,
-    NcPlane,
-    NcPlaneOptions,
-    NcStyle
-};
-use log::error;
-
-use crate::tools::log_err_desc_ret;
-use super::{ page::Page, TuiPrefs, util::{ Group, new_child_plane, PostData, Widget } };
-
-// Subreddit lisitng post item widget.
-pub struct SubListPost<'a> {
-    plane: &'a mut NcPlane,
-
-    hdr_plane:  &'a mut NcPlane,
-    hdg_plane:  &'a mut NcPlane,
-    body_plane: &'a mut NcPlane,
-
-    upvotes: u32,
-    heading: String,
-    content: String,
-    username: String,
-    subreddit_name: String,
-    comments: u32,
-    body: String
+use libnotcurses_sys::NcStyle;
+
+use crate::api::model::{ Item, Post, Vote };
+use super::{ page::Page, surface::Surface, TuiPrefs, util::{ channels, put_cut, wrap } };
+
+// Lines taken by a post: header, heading and body.
+const POST_HEIGHT: u32 = 5;
+const BODY_LINES: usize = 3;
+// Lines scrolled at a time.
+const SCROLL_STEP: u32 = 2;
+// Shown in header of saved posts, before comment count.
+const SAVED_MARK: &str = "saved";
+
+// -----------------------------------------------------------------------------------------------------------
+// Page for displaying subreddit listing.
+// -----------------------------------------------------------------------------------------------------------
+pub struct SubListPage {
+    subreddit: String,
+    posts: Vec<Post>,
+
+    scrolled: u32, // Lines scrolled down, 0 initially.
+    selected: usize, // Index of selected post.
+    // Posts hidden, with their index, until unhidden or forgotten.
+    hidden: Vec<(usize, Post)>,
+
+    dim_x: u32,
+    dim_y: u32
 }
 
-impl<'a> SubListPost<'a> {
-    fn set_contents(&mut self, data: PostData) {
-        self.upvotes = data.upvotes;
-        self.heading = data.heading.to_string();
-        self.content = data.content.to_string();
-        self.username = data.username.to_string();
-        self.subreddit_name = data.subreddit_name.to_string();
-        self.comments = data.comments;
-        self.body = data.body.to_string();
+impl SubListPage {
+    pub fn new(subreddit: &str) -> SubListPage {
+        SubListPage {
+            subreddit: subreddit.to_string(),
+            posts: vec![],
+            scrolled: 0,
+            selected: 0,
+            hidden: vec![],
+            dim_x: 0,
+            dim_y: 0
+        }
+    }
+
+    fn content_len(&self) -> u32 {
+        self.posts.len() as u32 * POST_HEIGHT
+    }
+
+    // Scroll selected post into view.
+    fn scroll_to_selected(&mut self) {
+        let top = self.selected as u32 * POST_HEIGHT;
+        if top < self.scrolled {
+            self.scrolled = top;
+        } else if top + POST_HEIGHT > self.scrolled + self.dim_y {
+            self.scrolled = (top + POST_HEIGHT).saturating_sub(self.dim_y);
+        }
     }
 
-    fn draw_header(&mut self, tui_prefs: &TuiPrefs) -> Result<()> {
-        let upvoted_channel = NcChannels::from_rgb(
-            tui_prefs.theme.post_upvoted_fg.to_nc_rgb(),
-            tui_prefs.theme.post_upvoted_bg.to_nc_rgb()
-        );
+    // Draw post with its top line at y, lines outside surface are left out.
+    fn draw_post(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs, post: &Post, y: i64, selected: bool) -> Result<()> {
+        let theme = &tui_prefs.theme;
+        let header_channels = channels(&theme.post_header_fg, &theme.post_header_bg);
+        let heading_channels = if selected {
+            channels(&theme.highlight_fg, &theme.highlight_bg)
+        } else {
+            channels(&theme.post_heading_fg, &theme.post_heading_bg)
+        };
+        let body_channels = channels(&theme.post_body_fg, &theme.post_body_bg);
 
         const UPVOTE_COUNT_DECIMAL_PRECISION: u32 = 7;
         const MAX_USERNAME_LEN: u32 = 16;
         const COMMENT_COUNT_DECIMAL_PRECISION: u32 = 8;
 
-        let mut pos = 0;
-        self.hdr_plane.putstr_yx_stained(0, pos, &self.upvotes.to_string())?;
-
-        pos = UPVOTE_COUNT_DECIMAL_PRECISION + 1;
-        self.hdr_plane.putstr_yx(Some(0), Some(pos), &self.username)?;
-
+        // Header
+        let blank = " ".repeat(self.dim_x as usize);
+        put_cut(surface, y, 0, &blank, header_channels, NcStyle::None)?;
+        let score_channels = match post.vote {
+            Vote::Up => channels(&theme.post_upvoted_fg, &theme.post_upvoted_bg),
+            Vote::Down => channels(&theme.post_downvoted_fg, &theme.post_downvoted_bg),
+            Vote::None => header_channels
+        };
+        let score = format!("{:<1$}", post.score, UPVOTE_COUNT_DECIMAL_PRECISION as usize - 1);
+        put_cut(surface, y, 0, &score, score_channels, NcStyle::None)?;
+        let mut pos = UPVOTE_COUNT_DECIMAL_PRECISION + 1;
+        put_cut(surface, y, pos, &post.author, header_channels, NcStyle::None)?;
         pos = pos + MAX_USERNAME_LEN + 1;
-        self.hdr_plane.putstr_yx(Some(0), Some(pos), &self.subreddit_name)?;
-
-        pos = self.plane.dim_x() - COMMENT_COUNT_DECIMAL_PRECISION + 1;
-        self.hdr_plane.putstr_yx(Some(0), Some(pos), &self.comments.to_string())?;
-
-        let upvoted = true;
-        if upvoted {
-            self.hdr_plane.stain(
-                Some(0),
-                Some(0),
-                Some(1),
-                Some(UPVOTE_COUNT_DECIMAL_PRECISION - 1),
-                upvoted_channel,
-                upvoted_channel,
-                upvoted_channel,
-                upvoted_channel,
-            )?;
+        put_cut(surface, y, pos, &post.subreddit, header_channels, NcStyle::None)?;
+        pos = self.dim_x.saturating_sub(COMMENT_COUNT_DECIMAL_PRECISION - 1);
+        put_cut(surface, y, pos, &post.num_comments.to_string(), header_channels, NcStyle::None)?;
+        if post.saved {
+            put_cut(surface, y, pos.saturating_sub(SAVED_MARK.len() as u32 + 1), SAVED_MARK, header_channels, NcStyle::None)?;
         }
 
+        // Heading
+        put_cut(surface, y + 1, 0, &blank, heading_channels, NcStyle::None)?;
+        put_cut(surface, y + 1, 0, &post.title, heading_channels, NcStyle::Bold)?;
+
+        // Body
+        let body = wrap(&post.selftext, self.dim_x as usize);
+        for i in 0..BODY_LINES {
+            let line = body.get(i).map(|line| line.as_str()).unwrap_or("");
+            let line = format!("{:<1$}", line, self.dim_x as usize);
+            put_cut(surface, y + 2 + i as i64, 0, &line, body_channels, NcStyle::None)?;
+        }
         Ok(())
     }
+}
+
+impl Page for SubListPage {
+    fn name(&self) -> &str {
+        &self.subreddit
+    }
 
-    fn draw_heading(&mut self) -> Result<()> {
-        self.hdg_plane.erase();
-        self.hdg_plane.puttext(0, NcAlign::Left, &self.heading)?;
+    fn scroll_down(&mut self) -> Result<()> {
+        let max_scrolled = self.content_len().saturating_sub(self.dim_y);
+        if self.scrolled >= max_scrolled {
+            bail!("Bottom reached, cannot scroll down more.");
+        }
+        self.scrolled = (self.scrolled + SCROLL_STEP).min(max_scrolled);
         Ok(())
     }
 
-    fn draw_body(&mut self) -> Result<()> {
-        self.body_plane.erase();
-        self.body_plane.puttext(0, NcAlign::Left, &self.body)?;
+    fn scroll_up(&mut self) -> Result<()> {
+        if self.scrolled == 0 {
+            bail!("Top reached, cannot scroll up more.");
+        }
+        self.scrolled = self.scrolled.saturating_sub(SCROLL_STEP);
         Ok(())
     }
-}
 
-impl<'a> Widget for SubListPost<'a> {
-    fn new(tui_prefs: &TuiPrefs,
-                    parent_plane: &mut NcPlane,
-                    x: i32,
-                    y: i32,
-                    dim_x: u32,
-                    dim_y: u32
-                   ) -> Result<Self> {
-        let plane = new_child_plane!(parent_plane, x, y, dim_x, dim_y);
-
-        let body_plane = new_child_plane!(plane, 0, 2, dim_x, 3);
-        body_plane.set_base(
-            " ",
-            0,
-            NcChannels::from_rgb(
-                tui_prefs.theme.post_body_fg.to_nc_rgb(),
-                tui_prefs.theme.post_body_bg.to_nc_rgb()
-            ))?;
-        body_plane.set_fg_rgb(tui_prefs.theme.post_body_fg.to_nc_rgb());
-        body_plane.set_bg_rgb(tui_prefs.theme.post_body_bg.to_nc_rgb());
-
-
-        let hdr_plane = new_child_plane!(plane, 0, 0, dim_x, 1);
-        hdr_plane.set_base(
-            " ",
-            0,
-            NcChannels::from_rgb(
-                tui_prefs.theme.post_header_fg.to_nc_rgb(),
-                tui_prefs.theme.post_header_bg.to_nc_rgb()
-            ))?;
-        hdr_plane.set_fg_rgb(tui_prefs.theme.post_header_fg.to_nc_rgb());
-        hdr_plane.set_bg_rgb(tui_prefs.theme.post_header_bg.to_nc_rgb());
-
-        let hdg_plane = new_child_plane!(plane, 0, 1, dim_x, 1);
-        hdg_plane.set_base(
-            " ",
-            0,
-            NcChannels::from_rgb(
-                tui_prefs.theme.post_heading_fg.to_nc_rgb(),
-                tui_prefs.theme.post_heading_bg.to_nc_rgb()
-            )
-        )?;
-        hdg_plane.set_styles(NcStyle::Bold);
-        hdg_plane.set_fg_rgb(tui_prefs.theme.post_heading_fg.to_nc_rgb());
-        hdg_plane.set_bg_rgb(tui_prefs.theme.post_heading_bg.to_nc_rgb());
-
-        Ok(Self {
-                plane,
-
-                hdr_plane,
-                hdg_plane,
-                body_plane,
-
-                heading: "".to_string(),
-                content: "".to_string(),
-                upvotes: 0,
-                username: "".to_string(),
-                subreddit_name: "".to_string(),
-                comments: 0,
-                body: "".to_string()
-
-        })
+    fn scrolled(&self) -> u32 {
+        self.scrolled
     }
 
-    fn draw(&mut self, tui_prefs: &TuiPrefs) -> Result<()> {
-        log_err_desc_ret!(self.draw_header(tui_prefs), "Failed to draw header.")?;
-        log_err_desc_ret!(self.draw_heading(), "Failed to draw heading.")?;
-        log_err_desc_ret!(self.draw_body(), "Failed to draw body.")
+    fn select_next(&mut self) -> Result<()> {
+        if self.selected + 1 >= self.posts.len() {
+            bail!("Last post selected.");
+        }
+        self.selected += 1;
+        self.scroll_to_selected();
+        Ok(())
     }
-}
 
-impl<'a> Group for SubListPost<'a> {
-    fn move_rel_xy(&mut self, x_diff: i32, y_diff: i32) -> Result<()> {
-        self.plane.move_rel(y_diff, x_diff)?;
-        self.hdr_plane.move_rel(y_diff, x_diff)?;
-        self.hdg_plane.move_rel(y_diff, x_diff)?;
-        self.body_plane.move_rel(y_diff, x_diff)?;
+    fn select_prev(&mut self) -> Result<()> {
+        if self.selected == 0 {
+            bail!("First post selected.");
+        }
+        self.selected -= 1;
+        self.scroll_to_selected();
         Ok(())
     }
-}
 
-// -----------------------------------------------------------------------------------------------------------
-// Page for displaying subreddit listing.
-// -----------------------------------------------------------------------------------------------------------
-pub struct SubListPage<'a> {
-    pub plane: &'a mut NcPlane,
-    posts: Vec<SubListPost<'a>>,
+    fn selected(&self) -> Option<usize> {
+        Some(self.selected).filter(|_| !self.posts.is_empty())
+    }
 
-    visible: bool, // Whether the page should be visible. If not, set if off right of visible area.
+    fn selected_item(&self) -> Option<Item> {
+        self.posts.get(self.selected).cloned().map(Item::Post)
+    }
 
-    scrolled: u32, // Lines scrolled down, 0 initially.
-    content_len: u32
-}
+    fn subreddit(&self) -> Option<&str> {
+        Some(&self.subreddit)
+    }
 
-impl<'a> SubListPage<'a> {
-}
+    fn selected_vote(&self) -> Option<(String, Vote)> {
+        self.posts.get(self.selected).map(|post| (post.fullname(), post.vote))
+    }
 
-impl<'a> Widget for SubListPage<'a> {
-    fn new(tui_prefs: &TuiPrefs,
-                   parent_plane: &mut NcPlane,
-                   x: i32,
-                   y: i32,
-                   dim_x: u32,
-                   dim_y: u32,
-                   ) -> Result<Self> {
-        let plane = new_child_plane!(parent_plane, x, y, dim_x, dim_y);
-
-        plane.set_fchannel(NcChannel::from_rgb(tui_prefs.theme.highlight_fg.to_nc_rgb()));
-        plane.set_bchannel(NcChannel::from_rgb(tui_prefs.theme.highlight_bg.to_nc_rgb()));
-
-        let page = Self { 
-            plane,
-            posts: vec![],
-            visible: true,
-            scrolled: 0,
-            content_len: 0
-        };
-        
-        Ok(page)
+    fn set_vote(&mut self, fullname: &str, vote: Vote) -> bool {
+        let mut found = false;
+        for post in self.posts.iter_mut().filter(|post| post.fullname() == fullname) {
+            post.set_vote(vote);
+            found = true;
+        }
+        found
     }
 
-    fn draw(&mut self, _tui_prefs: &TuiPrefs) -> Result<()> {
-        Ok(())
+    fn selected_saved(&self) -> Option<(String, bool)> {
+        self.posts.get(self.selected).map(|post| (post.fullname(), post.saved))
     }
-}
 
-impl<'a> Page for SubListPage<'a> {
-    fn set_visibility(&mut self, visible: bool) -> Result<()> {
-        if visible != self.visible {
-            if visible {
-                self.move_rel_xy(self.plane.dim_x() as i32, 0)?;
-            } else {
-                self.move_rel_xy(- (self.plane.dim_x() as i32), 0)?;
-            }
-            self.visible = visible;
+    fn set_saved(&mut self, fullname: &str, saved: bool) -> bool {
+        let mut found = false;
+        for post in self.posts.iter_mut().filter(|post| post.fullname() == fullname) {
+            post.saved = saved;
+            found = true;
         }
-        Ok(())
+        found
     }
 
-    fn scroll_down(&mut self) -> Result<()> {
-        if self.scrolled + self.plane.dim_y() >= self.content_len - 1 {
-            bail!("Bottom reached, cannot scroll down more.");
+    fn set_body(&mut self, fullname: &str, body: &str) -> bool {
+        let mut found = false;
+        for post in self.posts.iter_mut().filter(|post| post.fullname() == fullname) {
+            post.selftext = body.to_string();
+            found = true;
         }
-        self.scrolled += 2;
-        self.move_rel_xy(0, -2)
+        found
     }
 
-    fn add_post(&mut self, tui_prefs: &TuiPrefs, data: PostData) -> Result<()> {
-        let mut post = SubListPost::new(
-                tui_prefs,
-                self.plane,
-                0,
-                (self.posts.len() * 5) as i32,
-                self.plane.dim_x(),
-                self.plane.dim_y(),
-            )?;
-        post.set_contents(data);
+    fn mark_deleted(&mut self, fullname: &str) -> bool {
+        let mut found = false;
+        for post in self.posts.iter_mut().filter(|post| post.fullname() == fullname) {
+            post.mark_deleted();
+            found = true;
+        }
+        found
+    }
+
+    fn hide(&mut self, fullname: &str) -> bool {
+        let pos = match self.posts.iter().position(|post| post.fullname() == fullname) {
+            Some(pos) => pos,
+            None => { return false; }
+        };
+        self.hidden.push((pos, self.posts.remove(pos)));
+        self.selected = self.selected.min(self.posts.len().saturating_sub(1));
+        self.scrolled = self.scrolled.min(self.content_len().saturating_sub(self.dim_y));
+        true
+    }
+
+    fn unhide(&mut self, fullname: &str) -> bool {
+        let i = match self.hidden.iter().position(|(_, post)| post.fullname() == fullname) {
+            Some(i) => i,
+            None => { return false; }
+        };
+        let (pos, post) = self.hidden.remove(i);
+        let pos = pos.min(self.posts.len());
+        self.posts.insert(pos, post);
+        self.selected = pos;
+        self.scroll_to_selected();
+        true
+    }
+
+    fn forget_hidden(&mut self, fullname: &str) {
+        self.hidden.retain(|(_, post)| post.fullname() != fullname);
+    }
+
+    fn add_post(&mut self, post: Post) -> Result<()> {
         self.posts.push(post);
-        self.content_len += 5;
         Ok(())
     }
-    fn scroll_up(&mut self) -> Result<()> {
-        if self.scrolled == 0 {
-            bail!("Top reached, cannot scroll up more.");
-        }
-        self.scrolled -= 2;
-        self.move_rel_xy(0, 2)
-    }
 
-    fn draw(&mut self, tui_prefs: &TuiPrefs) -> Result<()> {
-        for post in self.posts.iter_mut() {
-            post.draw(tui_prefs)?;
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()> {
+        surface.fill(channels(&tui_prefs.theme.post_body_fg, &tui_prefs.theme.post_body_bg))?;
+        for (i, post) in self.posts.iter().enumerate() {
+            let y = (i as u32 * POST_HEIGHT) as i64 - self.scrolled as i64;
+            if y + POST_HEIGHT as i64 <= 0 { continue; }
+            if y >= self.dim_y as i64 { break; }
+            self.draw_post(surface, tui_prefs, post, y, i == self.selected)?;
         }
         Ok(())
     }
@@ -271,14 +252,10 @@ impl<'a> Page for SubListPage<'a> {
     fn fetch(&mut self) -> Result<()> {
         Ok(())
     }
-}
 
-impl Group for SubListPage<'_> {
-    fn move_rel_xy(&mut self, x_diff: i32, y_diff: i32) -> Result<()> {
-        self.plane.move_rel(y_diff, x_diff)?;
-        for post in self.posts.iter_mut() {
-            post.move_rel_xy(x_diff, y_diff)?;
-        }
-        Ok(())
+    fn resize(&mut self, dim_x: u32, dim_y: u32) {
+        self.dim_x = dim_x;
+        self.dim_y = dim_y;
+        self.scrolled = self.scrolled.min(self.content_len().saturating_sub(dim_y));
     }
 }
@@ -0,0 +1,263 @@
+use anyhow::Result;
+use libnotcurses_sys::{ NcChannels, NcPlane, NcStyle };
+use unicode_width::UnicodeWidthChar;
+
+// -----------------------------------------------------------------------------------------------------------
+// Surface widgets and pages draw onto.
+// * Either an NcPlane on the terminal or a CellBuffer in memory, so drawing is tested without a terminal.
+// * Drawing outside the surface is left out, text is cut at its right edge.
+// -----------------------------------------------------------------------------------------------------------
+pub trait Surface {
+    fn dim_yx(&self) -> (u32, u32);
+
+    fn dim_y(&self) -> u32 {
+        self.dim_yx().0
+    }
+
+    fn dim_x(&self) -> u32 {
+        self.dim_yx().1
+    }
+
+    // Blank all cells, in chnls.
+    fn fill(&mut self, chnls: NcChannels) -> Result<()>;
+
+    // Put text at y, x in chnls and styles.
+    fn put_str(&mut self, y: u32, x: u32, text: &str, chnls: NcChannels, styles: NcStyle) -> Result<()>;
+
+    // Recolor len_x cells from y, x, keeping their text.
+    fn stain(&mut self, y: u32, x: u32, len_x: u32, chnls: NcChannels) -> Result<()>;
+}
+
+// Text cut to what fits from column x of a surface dim_x wide, by display width, e.g. 2 for CJK chars.
+fn cut(text: &str, x: u32, dim_x: u32) -> String {
+    let mut room = dim_x.saturating_sub(x) as usize;
+    text.chars()
+        .take_while(|ch| {
+            let width = ch.width().unwrap_or(0);
+            if width > room { return false; }
+            room -= width;
+            true
+        })
+        .collect()
+}
+
+impl Surface for NcPlane {
+    fn dim_yx(&self) -> (u32, u32) {
+        NcPlane::dim_yx(self)
+    }
+
+    fn fill(&mut self, chnls: NcChannels) -> Result<()> {
+        self.set_base(" ", 0, chnls)?;
+        self.erase();
+        Ok(())
+    }
+
+    fn put_str(&mut self, y: u32, x: u32, text: &str, chnls: NcChannels, styles: NcStyle) -> Result<()> {
+        if y >= Surface::dim_y(self) || x >= Surface::dim_x(self) { return Ok(()); }
+        let text = cut(text, x, Surface::dim_x(self));
+        self.set_channels(chnls);
+        self.set_styles(styles);
+        self.putstr_yx(Some(y), Some(x), &text)?;
+        self.set_styles(NcStyle::None);
+        Ok(())
+    }
+
+    fn stain(&mut self, y: u32, x: u32, len_x: u32, chnls: NcChannels) -> Result<()> {
+        let len_x = len_x.min(Surface::dim_x(self).saturating_sub(x));
+        if y >= Surface::dim_y(self) || len_x == 0 { return Ok(()); }
+        NcPlane::stain(self, Some(y), Some(x), Some(1), Some(len_x), chnls, chnls, chnls, chnls)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub chnls: NcChannels,
+    pub styles: NcStyle
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell { ch: ' ', chnls: NcChannels::default(), styles: NcStyle::None }
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Surface in memory, a row-major grid of cells.
+// * A char takes as many cells as its display width, cells after the first hold WIDE_TAIL.
+// * Zero width chars, e.g. combining marks, are left out.
+// -----------------------------------------------------------------------------------------------------------
+// Cell covered by the wide char before it.
+pub const WIDE_TAIL: char = '\0';
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellBuffer {
+    dim_x: u32,
+    dim_y: u32,
+    cells: Vec<Cell>
+}
+
+impl CellBuffer {
+    pub fn new(dim_x: u32, dim_y: u32) -> CellBuffer {
+        CellBuffer { dim_x, dim_y, cells: vec![Cell::default(); (dim_x * dim_y) as usize] }
+    }
+
+    // Cell at y, x, None outside buffer.
+    pub fn cell(&self, y: u32, x: u32) -> Option<&Cell> {
+        if y >= self.dim_y || x >= self.dim_x { return None; }
+        self.cells.get((y * self.dim_x + x) as usize)
+    }
+
+    // Text of row y, without trailing blanks, empty outside buffer.
+    pub fn row(&self, y: u32) -> String {
+        if y >= self.dim_y { return String::new(); }
+        let start = (y * self.dim_x) as usize;
+        let row: String = self.cells[start..start + self.dim_x as usize].iter()
+            .map(|cell| cell.ch)
+            .filter(|ch| *ch != WIDE_TAIL)
+            .collect();
+        row.trim_end().to_string()
+    }
+
+    // Text of all rows, each ending in a line break.
+    pub fn text(&self) -> String {
+        (0..self.dim_y).map(|y| format!("{}\n", self.row(y))).collect()
+    }
+}
+
+impl Surface for CellBuffer {
+    fn dim_yx(&self) -> (u32, u32) {
+        (self.dim_y, self.dim_x)
+    }
+
+    fn fill(&mut self, chnls: NcChannels) -> Result<()> {
+        for cell in self.cells.iter_mut() {
+            *cell = Cell { chnls, ..Cell::default() };
+        }
+        Ok(())
+    }
+
+    fn put_str(&mut self, y: u32, x: u32, text: &str, chnls: NcChannels, styles: NcStyle) -> Result<()> {
+        if y >= self.dim_y { return Ok(()); }
+        let (row_start, row_end) = ((y * self.dim_x) as usize, ((y + 1) * self.dim_x) as usize);
+        let mut i = row_start + x as usize;
+        // Wide chars partly written over are blanked, as terminals do.
+        if i > row_start && i < row_end && self.cells[i].ch == WIDE_TAIL {
+            self.cells[i - 1].ch = ' ';
+        }
+        for ch in cut(text, x, self.dim_x).chars() {
+            let width = ch.width().unwrap_or(0);
+            if width == 0 { continue; }
+            self.cells[i] = Cell { ch, chnls, styles };
+            for cell in self.cells[i + 1..i + width].iter_mut() {
+                *cell = Cell { ch: WIDE_TAIL, chnls, styles };
+            }
+            i += width;
+        }
+        while i < row_end && self.cells[i].ch == WIDE_TAIL {
+            self.cells[i].ch = ' ';
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn stain(&mut self, y: u32, x: u32, len_x: u32, chnls: NcChannels) -> Result<()> {
+        if y >= self.dim_y { return Ok(()); }
+        for x in x..(x + len_x).min(self.dim_x) {
+            self.cells[(y * self.dim_x + x) as usize].chnls = chnls;
+        }
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// Part of a surface, drawn onto as a surface of its own.
+// * Placed at y, x of the surface it is part of, cut to fit in it.
+// -----------------------------------------------------------------------------------------------------------
+pub struct Region<'s> {
+    surface: &'s mut dyn Surface,
+    y: u32,
+    x: u32,
+    dim_y: u32,
+    dim_x: u32
+}
+
+impl<'s> Region<'s> {
+    pub fn new(surface: &'s mut dyn Surface, y: u32, x: u32, dim_y: u32, dim_x: u32) -> Region<'s> {
+        let dim_y = dim_y.min(surface.dim_y().saturating_sub(y));
+        let dim_x = dim_x.min(surface.dim_x().saturating_sub(x));
+        Region { surface, y, x, dim_y, dim_x }
+    }
+}
+
+impl<'s> Surface for Region<'s> {
+    fn dim_yx(&self) -> (u32, u32) {
+        (self.dim_y, self.dim_x)
+    }
+
+    fn fill(&mut self, chnls: NcChannels) -> Result<()> {
+        let blank = " ".repeat(self.dim_x as usize);
+        for y in 0..self.dim_y {
+            self.surface.put_str(self.y + y, self.x, &blank, chnls, NcStyle::None)?;
+        }
+        Ok(())
+    }
+
+    fn put_str(&mut self, y: u32, x: u32, text: &str, chnls: NcChannels, styles: NcStyle) -> Result<()> {
+        if y >= self.dim_y { return Ok(()); }
+        self.surface.put_str(self.y + y, self.x + x, &cut(text, x, self.dim_x), chnls, styles)
+    }
+
+    fn stain(&mut self, y: u32, x: u32, len_x: u32, chnls: NcChannels) -> Result<()> {
+        if y >= self.dim_y { return Ok(()); }
+        self.surface.stain(self.y + y, self.x + x, len_x.min(self.dim_x.saturating_sub(x)), chnls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libnotcurses_sys::{ NcChannels, NcStyle };
+
+    use super::{ CellBuffer, Region, Surface };
+
+    // Test if text is cut at the edges of buffer and region, and stains keep text.
+    #[test]
+    fn test_cell_buffer() {
+        let chnls = NcChannels::from_rgb(0xffffff, 0x000000);
+        let mut buffer = CellBuffer::new(8, 3);
+        buffer.put_str(0, 2, "ravana rocks", chnls, NcStyle::Bold).unwrap();
+        buffer.put_str(3, 0, "out", chnls, NcStyle::None).unwrap();
+        assert_eq!(buffer.text(), "  ravana\n\n\n");
+        assert_eq!(buffer.cell(0, 2).unwrap().styles, NcStyle::Bold);
+        assert_eq!(buffer.cell(0, 8), None);
+
+        let mut region = Region::new(&mut buffer, 1, 4, 5, 3);
+        assert_eq!(region.dim_yx(), (2, 3));
+        region.fill(chnls).unwrap();
+        region.put_str(1, 1, "abc", chnls, NcStyle::None).unwrap();
+        region.stain(0, 1, 9, NcChannels::default()).unwrap();
+        assert_eq!(buffer.text(), "  ravana\n\n     ab\n");
+        assert_eq!(buffer.cell(1, 4).unwrap().chnls, chnls);
+        assert_eq!(buffer.cell(1, 5).unwrap().chnls, NcChannels::default());
+        assert_eq!(buffer.cell(1, 6).unwrap().chnls, NcChannels::default());
+        assert_eq!(buffer.row(3), "");
+    }
+
+    // Test if wide chars take two cells and are cut by display width.
+    #[test]
+    fn test_cell_buffer_wide() {
+        let chnls = NcChannels::from_rgb(0xffffff, 0x000000);
+        let mut buffer = CellBuffer::new(8, 2);
+        buffer.put_str(0, 2, "日本語x", chnls, NcStyle::None).unwrap();
+        buffer.put_str(1, 3, "日本語", chnls, NcStyle::None).unwrap();
+        assert_eq!(buffer.text(), "  日本語\n   日本\n");
+        assert_eq!(buffer.cell(0, 3).unwrap().ch, super::WIDE_TAIL);
+
+        let mut region = Region::new(&mut buffer, 1, 0, 2, 4);
+        region.put_str(0, 1, "e\u{301}日本", chnls, NcStyle::None).unwrap();
+        assert_eq!(buffer.row(1), " e日 本");
+        buffer.put_str(0, 3, "x", chnls, NcStyle::None).unwrap();
+        assert_eq!(buffer.row(0), "   x本語");
+    }
+}
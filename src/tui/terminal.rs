@@ -0,0 +1,120 @@
+use anyhow::{ anyhow, Result };
+use log::error;
+use nix::sys::{
+    signal::{ raise, signal, SigHandler, Signal },
+    termios::{ tcgetattr, tcsetattr, SetArg, Termios }
+};
+use std::{ io::stdin, os::unix::io::AsRawFd, sync::Mutex };
+
+// Terminal mode from before Nc took over, restored while the terminal is handed out.
+static COOKED_MODE: Mutex<Option<Termios>> = Mutex::new(None);
+
+// Save terminal mode, call before Nc is created.
+pub fn save_cooked_mode() -> Result<()> {
+    let termios = tcgetattr(stdin().as_raw_fd())?;
+    *COOKED_MODE.lock().map_err(|_| anyhow!("Unable to lock saved terminal mode."))? = Some(termios);
+    Ok(())
+}
+
+// Switch to saved terminal mode, returning the mode Nc set.
+pub(super) fn enter_cooked_mode() -> Result<Termios> {
+    let cooked = COOKED_MODE.lock().map_err(|_| anyhow!("Unable to lock saved terminal mode."))?.clone()
+        .ok_or_else(|| anyhow!("Terminal mode was not saved before starting TUI."))?;
+    let raw = tcgetattr(stdin().as_raw_fd())?;
+    tcsetattr(stdin().as_raw_fd(), SetArg::TCSADRAIN, &cooked)?;
+    Ok(raw)
+}
+
+pub(super) fn enter_raw_mode(raw: &Termios) -> Result<()> {
+    tcsetattr(stdin().as_raw_fd(), SetArg::TCSADRAIN, raw)?;
+    Ok(())
+}
+
+// Stop the process as Ctrl-Z in a shell would, returning once continued ( SIGCONT ).
+// The SIGTSTP handler in place before is restored after.
+pub(super) fn stop_self() -> Result<()> {
+    let prev = unsafe { signal(Signal::SIGTSTP, SigHandler::SigDfl)? };
+    let res = raise(Signal::SIGTSTP);
+    unsafe { signal(Signal::SIGTSTP, prev)?; }
+    res?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------------------------------------
+// * Steps taken releasing the terminal, each with how to undo it.
+// * Undone in reverse order by restore(), or on drop, e.g. when a later step fails and returns early, so the
+//   terminal is never left half released.
+// -----------------------------------------------------------------------------------------------------------
+pub(super) struct Released<'a> {
+    undo: Vec<Box<dyn FnOnce() -> Result<()> + 'a>>
+}
+
+impl<'a> Released<'a> {
+    pub(super) fn new() -> Released<'a> {
+        Released { undo: Vec::new() }
+    }
+
+    // Run step, keeping undo to be run with its result once restoring.
+    pub(super) fn step<T: 'a>(&mut self,
+                              step: impl FnOnce() -> Result<T>,
+                              undo: impl FnOnce(T) -> Result<()> + 'a) -> Result<()> {
+        let value = step()?;
+        self.undo.push(Box::new(move || undo(value)));
+        Ok(())
+    }
+
+    // Undo steps, last first, returning the first error after trying all of them.
+    pub(super) fn restore(mut self) -> Result<()> {
+        self.undo_all()
+    }
+
+    fn undo_all(&mut self) -> Result<()> {
+        let mut res = Ok(());
+        while let Some(undo) = self.undo.pop() {
+            if let Err(e) = undo() {
+                if res.is_ok() { res = Err(e); }
+            }
+        }
+        res
+    }
+}
+
+impl Drop for Released<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.undo_all() {
+            error!("Failed to restore terminal: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use std::cell::RefCell;
+
+    use super::Released;
+
+    // Test if steps taken are undone in reverse order when a later step fails, and on restore.
+    #[test]
+    fn test_released_restore() {
+        let log = RefCell::new(Vec::new());
+        let res = (|| {
+            let mut released = Released::new();
+            released.step(|| { log.borrow_mut().push("mice off"); Ok(1) },
+                |n| { log.borrow_mut().push(if n == 1 { "mice on" } else { "?" }); Ok(()) })?;
+            released.step(|| { log.borrow_mut().push("screen left"); Ok(()) },
+                |_| { log.borrow_mut().push("screen entered"); Ok(()) })?;
+            released.step(|| Err::<(), _>(anyhow!("no cooked mode")), |_| { log.borrow_mut().push("raw"); Ok(()) })?;
+            released.restore()
+        })();
+        assert_eq!(res.unwrap_err().to_string(), "no cooked mode");
+        assert_eq!(*log.borrow(), vec!["mice off", "screen left", "screen entered", "mice on"]);
+
+        log.borrow_mut().clear();
+        let mut released = Released::new();
+        released.step(|| Ok(()), |_| { log.borrow_mut().push("first"); Err(anyhow!("failed")) }).unwrap();
+        released.step(|| Ok(()), |_| { log.borrow_mut().push("second"); Ok(()) }).unwrap();
+        assert_eq!(released.restore().unwrap_err().to_string(), "failed");
+        assert_eq!(*log.borrow(), vec!["second", "first"]);
+    }
+}
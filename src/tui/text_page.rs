@@ -0,0 +1,77 @@
+use anyhow::{ bail, Result };
+use libnotcurses_sys::NcStyle;
+
+use crate::api::model::Post;
+use super::{ page::Page, surface::Surface, TuiPrefs, util::{ channels, put_cut } };
+
+// -----------------------------------------------------------------------------------------------------------
+// Page for displaying plain text, e.g. resolved config.
+// * Lines longer than page width are cut.
+// -----------------------------------------------------------------------------------------------------------
+pub struct TextPage {
+    name: String,
+    lines: Vec<String>,
+
+    scrolled: u32, // Lines scrolled down, 0 initially.
+
+    dim_y: u32
+}
+
+impl TextPage {
+    pub fn new(name: &str, text: &str) -> TextPage {
+        TextPage {
+            name: name.to_string(),
+            lines: text.lines().map(|line| line.replace('\t', "    ")).collect(),
+            scrolled: 0,
+            dim_y: 0
+        }
+    }
+}
+
+impl Page for TextPage {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn scroll_down(&mut self) -> Result<()> {
+        if self.scrolled + self.dim_y >= self.lines.len() as u32 {
+            bail!("Bottom reached, cannot scroll down more.");
+        }
+        self.scrolled += 1;
+        Ok(())
+    }
+
+    fn scroll_up(&mut self) -> Result<()> {
+        if self.scrolled == 0 {
+            bail!("Top reached, cannot scroll up more.");
+        }
+        self.scrolled -= 1;
+        Ok(())
+    }
+
+    fn scrolled(&self) -> u32 {
+        self.scrolled
+    }
+
+    fn add_post(&mut self, _post: Post) -> Result<()> {
+        bail!("Text page has no posts.")
+    }
+
+    // Draw lines in view.
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()> {
+        let body_channels = channels(&tui_prefs.theme.post_body_fg, &tui_prefs.theme.post_body_bg);
+        surface.fill(body_channels)?;
+        for (y, line) in self.lines.iter().skip(self.scrolled as usize).take(self.dim_y as usize).enumerate() {
+            put_cut(surface, y as i64, 0, line, body_channels, NcStyle::None)?;
+        }
+        Ok(())
+    }
+
+    fn fetch(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn resize(&mut self, _dim_x: u32, dim_y: u32) {
+        self.dim_y = dim_y;
+    }
+}
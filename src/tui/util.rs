@@ -1,134 +1,56 @@
-use anyhow::{ anyhow, Result };
-use log::error;
-use libnotcurses_sys::{ NcPlane, NcRgb };
-
-use crate::jobs::TuiPrefsDes;
-
-// TODO: Add test to check if we're validating all fields and formats.
-pub fn val_tui_prefs_des(tui_prefs_des: &TuiPrefsDes) -> bool {
-    // Check color format
-    let val_color_fmt = |s: &str| -> bool {
-        let mut res = true;
-        res = res && s.len() == 7;
-        let mut chars = s.chars();
-        res = res && Some('#') == chars.next();
-        res = res && chars.next().map_or(false, |c: char| c.is_ascii_hexdigit() );
-        res = res && chars.next().map_or(false, |c: char| c.is_ascii_hexdigit() );
-        res = res && chars.next().map_or(false, |c: char| c.is_ascii_hexdigit() );
-        res = res && chars.next().map_or(false, |c: char| c.is_ascii_hexdigit() );
-        res = res && chars.next().map_or(false, |c: char| c.is_ascii_hexdigit() );
-        res = res && chars.next().map_or(false, |c: char| c.is_ascii_hexdigit() );
-        res
-    };
-        
-    let mut res = true;
-
-    // Check theme
-    {
-        let theme = &tui_prefs_des.theme;
-        let mut temp_bool: bool;
-
-        temp_bool = val_color_fmt(&theme.highlight_fg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "highlight-fg", theme.highlight_fg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.highlight_bg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "highlight-bg", theme.highlight_bg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.page_bar_fg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "page-bar-fg", theme.page_bar_fg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.page_bar_bg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "page-bar-bg", theme.page_bar_bg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.page_bar_current_bg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "page-bar-current-bg", theme.page_bar_current_bg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.post_header_fg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-header-fg", theme.post_header_fg);
-        }
-        res = res && temp_bool;
+use anyhow::{ anyhow, bail, Result };
+use libnotcurses_sys::{ NcChannel, NcChannels, NcRgb, NcStyle };
+use unicode_width::UnicodeWidthChar;
 
-        temp_bool = val_color_fmt(&theme.post_header_bg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-header-bg", theme.post_header_bg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.post_upvoted_fg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-upvoted-fg", theme.post_upvoted_fg);
-        }
-        res = res && temp_bool;
+use crate::jobs::{ Diagnostic, TuiPrefsDes };
+use super::surface::Surface;
 
-        temp_bool = val_color_fmt(&theme.post_upvoted_bg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-upvoted-bg", theme.post_upvoted_bg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.post_heading_fg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-heading-fg", theme.post_heading_fg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.post_heading_bg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-heading-bg", theme.post_heading_bg);
-        }
-        res = res && temp_bool;
-
-        temp_bool = val_color_fmt(&theme.post_body_fg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-body-fg", theme.post_body_fg);
-        }
-        res = res && temp_bool;
+// Check color format: #rrggbb, a color name or ansi:N.
+pub fn val_color_fmt(s: &str) -> bool {
+    Color::parse(s).is_some()
+}
 
-        temp_bool = val_color_fmt(&theme.post_body_bg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-body-bg", theme.post_body_bg);
+// Validate TUI prefs, reporting colors of wrong format.
+pub fn val_tui_prefs_des(tui_prefs_des: &TuiPrefsDes) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (key, color) in tui_prefs_des.theme.colors().iter() {
+        if !val_color_fmt(color) {
+            diagnostics.push(Diagnostic::error(
+                &["tui".to_string(), "theme".to_string(), key.to_string()],
+                format!("invalid color \"{}\", {}", color, COLOR_FMT_HINT)
+            ));
         }
-        res = res && temp_bool;
+    }
+    diagnostics
+}
 
-        temp_bool = val_color_fmt(&theme.cmd_plt_fg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-body-bg", theme.cmd_plt_fg);
-        }
-        res = res && temp_bool;
+pub const COLOR_FMT_HINT: &str = "expected #rrggbb, a color name or ansi:0 to ansi:255";
 
-        temp_bool = val_color_fmt(&theme.cmd_plt_bg);
-        if !temp_bool {
-            error!("Wrong color format for {} {} - {}", "theme", "post-body-bg", theme.cmd_plt_bg);
-        }
-        res = res && temp_bool;
-    }
+// Names of the 16 terminal colors, by palette index. They follow the terminal's own palette.
+const COLOR_NAMES: [&str; 16] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    "bright-black", "bright-red", "bright-green", "bright-yellow", "bright-blue", "bright-magenta", "bright-cyan",
+    "bright-white"
+];
 
-    res
-}
+// xterm's RGB for the 16 terminal colors, used to pick the nearest one.
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255),
+    (255, 255, 255)
+];
 
+// Levels of each component in the 6x6x6 color cube at palette indices 16 - 231.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
 
 #[derive(Clone)]
 #[cfg_attr(test, derive(PartialEq, Debug))]
 pub struct Color {
     pub r: u8,
     pub g: u8,
-    pub b: u8
+    pub b: u8,
+    // Palette index drawn instead of RGB, for ansi:N, color names and terminals without RGB.
+    pub index: Option<u8>
 }
 
 impl Color {
@@ -152,10 +74,63 @@ impl Color {
         Some(Color {
             r,
             b,
-            g
+            g,
+            index: None
         })
     }    
-    
+
+    // Color from #rrggbb, a color name ( e.g. bright-red ) or ansi:N for palette index N.
+    pub fn parse(color_str: &str) -> Option<Color> {
+        if color_str.starts_with('#') {
+            return if color_str.len() == 7 { Color::get_color_from_str(color_str) } else { None };
+        }
+        let index = match color_str.strip_prefix("ansi:") {
+            Some(index) => index.parse::<u8>().ok()?,
+            None => COLOR_NAMES.iter().position(|&name| name == color_str)? as u8
+        };
+        Some(Color::from_index(index))
+    }
+
+    // Palette color, with RGB as xterm draws it.
+    pub fn from_index(index: u8) -> Color {
+        let (r, g, b) = match index {
+            0..=15 => ANSI_RGB[index as usize],
+            16..=231 => {
+                let i = (index - 16) as usize;
+                (CUBE_LEVELS[i / 36], CUBE_LEVELS[(i / 6) % 6], CUBE_LEVELS[i % 6])
+            },
+            _ => {
+                let level = 8 + 10 * (index - 232);
+                (level, level, level)
+            }
+        };
+        Color { r, g, b, index: Some(index) }
+    }
+
+    // -------------------------------------------------------------------------------------------------------
+    // * Nearest palette index for terminals with palette_size colors.
+    // * With 256 colors the 16 terminal colors are skipped, terminals often redefine them.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn nearest_index(&self, palette_size: u32) -> u8 {
+        let dist = |index: &u8| {
+            let color = Color::from_index(*index);
+            [(self.r, color.r), (self.g, color.g), (self.b, color.b)].iter()
+                .map(|&(a, b)| (a as i32 - b as i32).pow(2))
+                .sum::<i32>()
+        };
+        let first = if palette_size >= 256 { 16 } else { 0 };
+        let last = palette_size.clamp(1, 256) - 1;
+        (first..=last as u8).min_by_key(dist).unwrap_or(0)
+    }
+
+    // Draw with a palette index below palette_size, keeping palette colors already within it.
+    pub fn limit(&mut self, palette_size: u32) {
+        match self.index {
+            Some(index) if (index as u32) < palette_size => {  },
+            _ => { self.index = Some(self.nearest_index(palette_size)); }
+        }
+    }
+
     pub fn to_nc_rgb(&self) -> NcRgb {
        let mut rgb: u32 = 0; 
        rgb = rgb ^ ((self.r as u32) << 16);
@@ -163,6 +138,18 @@ impl Color {
        rgb = rgb ^ ((self.b as u32)      );
        NcRgb(rgb)
     }
+
+    pub fn to_nc_channel(&self) -> NcChannel {
+        match self.index {
+            Some(index) => NcChannel::new().set_palindex(index),
+            None => NcChannel::from_rgb(self.to_nc_rgb())
+        }
+    }
+}
+
+// Channels drawing fg over bg.
+pub fn channels(fg: &Color, bg: &Color) -> NcChannels {
+    NcChannels::combine(fg.to_nc_channel(), bg.to_nc_channel())
 }
 
 // Interface preferences.
@@ -181,6 +168,8 @@ pub struct Theme {
     pub post_header_bg: Color,
     pub post_upvoted_fg: Color,
     pub post_upvoted_bg: Color,
+    pub post_downvoted_fg: Color,
+    pub post_downvoted_bg: Color,
     pub post_heading_fg: Color,
     pub post_heading_bg: Color,
     pub post_body_fg: Color,
@@ -189,10 +178,60 @@ pub struct Theme {
     pub cmd_plt_bg: Color
 }
 
+impl Theme {
+    // Color for a theme key in config, e.g. post-body-fg.
+    fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        match key {
+            "highlight-fg" => Some(&mut self.highlight_fg),
+            "highlight-bg" => Some(&mut self.highlight_bg),
+            "page-bar-fg" => Some(&mut self.page_bar_fg),
+            "page-bar-bg" => Some(&mut self.page_bar_bg),
+            "page-bar-current-bg" => Some(&mut self.page_bar_current_bg),
+            "post-header-fg" => Some(&mut self.post_header_fg),
+            "post-header-bg" => Some(&mut self.post_header_bg),
+            "post-upvoted-fg" => Some(&mut self.post_upvoted_fg),
+            "post-upvoted-bg" => Some(&mut self.post_upvoted_bg),
+            "post-downvoted-fg" => Some(&mut self.post_downvoted_fg),
+            "post-downvoted-bg" => Some(&mut self.post_downvoted_bg),
+            "post-heading-fg" => Some(&mut self.post_heading_fg),
+            "post-heading-bg" => Some(&mut self.post_heading_bg),
+            "post-body-fg" => Some(&mut self.post_body_fg),
+            "post-body-bg" => Some(&mut self.post_body_bg),
+            "cmd-plt-fg" => Some(&mut self.cmd_plt_fg),
+            "cmd-plt-bg" => Some(&mut self.cmd_plt_bg),
+            _ => None
+        }
+    }
+
+    fn colors_mut(&mut self) -> [&mut Color; 17] {
+        [
+            &mut self.highlight_fg,
+            &mut self.highlight_bg,
+            &mut self.page_bar_fg,
+            &mut self.page_bar_bg,
+            &mut self.page_bar_current_bg,
+            &mut self.post_header_fg,
+            &mut self.post_header_bg,
+            &mut self.post_upvoted_fg,
+            &mut self.post_upvoted_bg,
+            &mut self.post_downvoted_fg,
+            &mut self.post_downvoted_bg,
+            &mut self.post_heading_fg,
+            &mut self.post_heading_bg,
+            &mut self.post_body_fg,
+            &mut self.post_body_bg,
+            &mut self.cmd_plt_fg,
+            &mut self.cmd_plt_bg
+        ]
+    }
+}
+
 // TUI preferences.
 pub struct TuiPrefs {
     pub interface: InterfacePrefs,
-    pub theme: Theme
+    pub theme: Theme,
+    // Number of palette colors the terminal is limited to, None if it takes RGB.
+    pub palette_size: Option<u32>
 }
 
 impl TuiPrefs {
@@ -203,64 +242,93 @@ impl TuiPrefs {
                     mouse_events_enable: tui_prefs_des.interface.mouse_events_enable
                 },
                 theme: Theme {
-                    highlight_fg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.highlight_fg) 
+                    highlight_fg: if let Some(color) = Color::parse(&tui_prefs_des.theme.highlight_fg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    highlight_bg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.highlight_bg) 
+                    highlight_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.highlight_bg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    page_bar_fg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.page_bar_fg) 
+                    page_bar_fg: if let Some(color) = Color::parse(&tui_prefs_des.theme.page_bar_fg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    page_bar_bg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.page_bar_bg) 
+                    page_bar_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.page_bar_bg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    page_bar_current_bg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.page_bar_current_bg) 
+                    page_bar_current_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.page_bar_current_bg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    post_header_fg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.post_header_fg) 
+                    post_header_fg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_header_fg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    post_header_bg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.post_header_bg) 
+                    post_header_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_header_bg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    post_upvoted_fg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.post_upvoted_fg) 
+                    post_upvoted_fg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_upvoted_fg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    post_upvoted_bg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.post_upvoted_bg) 
+                    post_upvoted_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_upvoted_bg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    post_heading_fg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.post_heading_fg) 
+                    post_downvoted_fg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_downvoted_fg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    post_heading_bg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.post_heading_bg) 
+                    post_downvoted_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_downvoted_bg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    post_body_fg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.post_body_fg) 
+                    post_heading_fg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_heading_fg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    post_body_bg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.post_body_bg) 
+                    post_heading_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_heading_bg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    cmd_plt_fg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.cmd_plt_fg) 
+                    post_body_fg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_body_fg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                    cmd_plt_bg: if let Some(color) = Color::get_color_from_str(&tui_prefs_des.theme.cmd_plt_bg) 
+                    post_body_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.post_body_bg) 
                         { color } else { return Err(anyhow!("Invalid color format.")); },
-                }
+                    cmd_plt_fg: if let Some(color) = Color::parse(&tui_prefs_des.theme.cmd_plt_fg) 
+                        { color } else { return Err(anyhow!("Invalid color format.")); },
+                    cmd_plt_bg: if let Some(color) = Color::parse(&tui_prefs_des.theme.cmd_plt_bg) 
+                        { color } else { return Err(anyhow!("Invalid color format.")); },
+                },
+                palette_size: None
             }
         )
     }
 }
 
-// -----------------------------------------------------------------------------------------------------------
-// Widget trait
-// * Widgets are functional components that make the App.
-// -----------------------------------------------------------------------------------------------------------
-pub trait Widget: Sized {
-    fn new(tui_prefs: &TuiPrefs,
-            plane: &mut NcPlane,
-            x: i32,
-            y: i32,
-            dim_x: u32,
-            dim_y: u32
-            ) -> Result<Self>;
-    fn draw(&mut self, tui_prefs: &TuiPrefs) -> Result<()>;
+impl TuiPrefs {
+    // -------------------------------------------------------------------------------------------------------
+    // * Set a preference by its key in config, e.g. theme.post-body-fg.
+    // * mouse is short for interface.mouse-events-enable, taking on / off.
+    // -------------------------------------------------------------------------------------------------------
+    pub fn set(&mut self, option: &str, value: &str) -> Result<()> {
+        match option {
+            "mouse" | "interface.mouse-events-enable" => {
+                self.interface.mouse_events_enable = match value {
+                    "on" | "true" => true,
+                    "off" | "false" => false,
+                    _ => bail!("Invalid value for {}: {}, expected on or off", option, value)
+                };
+            },
+            _ => {
+                let palette_size = self.palette_size;
+                let color = option.strip_prefix("theme.")
+                    .and_then(|key| self.theme.color_mut(key))
+                    .ok_or_else(|| anyhow!("Unknown option: {}", option))?;
+                *color = Color::parse(value)
+                    .ok_or_else(|| anyhow!("Wrong color format for {} - {}, {}", option, value, COLOR_FMT_HINT))?;
+                if let Some(palette_size) = palette_size {
+                    color.limit(palette_size);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Degrade theme colors to the terminal's palette of palette_size colors.
+    pub fn limit_colors(&mut self, palette_size: u32) {
+        self.palette_size = Some(palette_size);
+        for color in self.theme.colors_mut() {
+            color.limit(palette_size);
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------------------------------------
-// Group trait
-// * Groups children widgets with a parent widget.
-// * Useful for applying transformations to entire group like moving the group together.
+// Widget trait
+// * Widgets are functional components that make the App.
+// * They hold what to show and draw it onto a surface sized for them.
 // -----------------------------------------------------------------------------------------------------------
-pub trait Group {
-    fn move_rel_xy(&mut self, x_diff: i32, y_diff: i32) -> Result<()>;
+pub trait Widget {
+    // Draw with colors from theme.
+    fn draw(&self, surface: &mut dyn Surface, tui_prefs: &TuiPrefs) -> Result<()>;
 }
 
 macro_rules! new_child_plane {
@@ -270,36 +338,137 @@ macro_rules! new_child_plane {
 }
 pub(super) use new_child_plane;
 
-// Data structures
+// Put text at y, x in channels and styles. Text on rows outside surface is left out.
+pub(super) fn put_cut(surface: &mut dyn Surface, y: i64, x: u32, text: &str, chnls: NcChannels, styles: NcStyle)
+        -> Result<()> {
+    if y < 0 { return Ok(()); }
+    surface.put_str(y as u32, x, text, chnls, styles)
+}
 
-pub struct PostData<'a> {
-    pub upvotes: u32,
-    pub heading: &'a str,
-    pub content: &'a str,
-    pub username: &'a str,
-    pub subreddit_name: &'a str,
-    pub comments: u32,
-    pub body: &'a str
+// Break text into lines of at most width columns, at line breaks or else at the last space. Widths are display
+// widths, e.g. 2 for CJK chars.
+pub(super) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut rest: Vec<char> = paragraph.chars().collect();
+        loop {
+            // Chars fitting in width, a char wider than width taking a line of its own.
+            let mut room = width;
+            let fit = rest.iter().take_while(|c| {
+                let char_width = c.width().unwrap_or(0);
+                if char_width > room { return false; }
+                room -= char_width;
+                true
+            }).count();
+            if fit == rest.len() || width == 0 {
+                lines.push(rest.into_iter().collect());
+                break;
+            }
+            let cut = rest[..=fit].iter().rposition(|c| *c == ' ').filter(|cut| *cut > 0).unwrap_or_else(|| fit.max(1));
+            lines.push(rest[..cut].iter().collect());
+            rest = rest[cut..].iter().skip_while(|c| **c == ' ').copied().collect();
+            if rest.is_empty() { break; }
+        }
+    }
+    lines
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Color;
+    use crate::jobs::Config;
+    use super::{ wrap, Color, TuiPrefs };
 
     #[test]
     fn test_gen_color_from_str() {
         assert_eq!(Some(Color {
             r: 255u8,
             g: 255u8,
-            b: 255u8
+            b: 255u8,
+            index: None
         }), Color::get_color_from_str("#ffffff"));
 
         assert_eq!(Some(Color {
             r: 166u8,
             g: 183u8,
-            b: 200u8
+            b: 200u8,
+            index: None
         }), Color::get_color_from_str("#a6b7c8"));
 
         assert_eq!(None, Color::get_color_from_str("#g6b7c6"));
     }
+
+    // Test if TuiPrefs::set sets interface options and theme colors, rejecting unknown options and bad values.
+    #[test]
+    fn test_tui_prefs_set() {
+        let mut tui_prefs = TuiPrefs::gen_tui_prefs(&Config::default().tui).unwrap();
+
+        tui_prefs.set("mouse", "off").unwrap();
+        assert_eq!(tui_prefs.interface.mouse_events_enable, false);
+        tui_prefs.set("interface.mouse-events-enable", "true").unwrap();
+        assert_eq!(tui_prefs.interface.mouse_events_enable, true);
+        assert!(tui_prefs.set("mouse", "maybe").is_err());
+
+        tui_prefs.set("theme.post-body-fg", "#aabbcc").unwrap();
+        assert_eq!(tui_prefs.theme.post_body_fg, Color { r: 170u8, g: 187u8, b: 204u8, index: None });
+        assert!(tui_prefs.set("theme.post-body-fg", "#aabbccdd").is_err());
+        assert!(tui_prefs.set("theme.post-foot-fg", "#aabbcc").is_err());
+        assert!(tui_prefs.set("post-body-fg", "#aabbcc").is_err());
+
+        tui_prefs.limit_colors(16);
+        tui_prefs.set("theme.post-body-fg", "#ff0000").unwrap();
+        assert_eq!(tui_prefs.theme.post_body_fg.index, Some(9));
+    }
+
+    // Test if hex colors, color names and ansi:N parse, with palette colors keeping their index.
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(Color::parse("#a6b7c8"), Color::get_color_from_str("#a6b7c8"));
+        assert_eq!(Color::parse("red"), Some(Color { r: 205, g: 0, b: 0, index: Some(1) }));
+        assert_eq!(Color::parse("bright-white").unwrap().index, Some(15));
+        assert_eq!(Color::parse("ansi:196"), Some(Color { r: 255, g: 0, b: 0, index: Some(196) }));
+        assert_eq!(Color::parse("ansi:244"), Some(Color { r: 128, g: 128, b: 128, index: Some(244) }));
+
+        assert_eq!(Color::parse("#a6b7c8ff"), None);
+        assert_eq!(Color::parse("#a6b"), None);
+        assert_eq!(Color::parse("ansi:256"), None);
+        assert_eq!(Color::parse("reddish"), None);
+    }
+
+    // Test if RGB colors degrade to the nearest color of the palette, palette colors within it are kept.
+    #[test]
+    fn test_limit_color() {
+        let mut color = Color::parse("#fe0101").unwrap();
+        color.limit(256);
+        assert_eq!(color.index, Some(196));
+
+        let mut color = Color::parse("#fe0101").unwrap();
+        color.limit(16);
+        assert_eq!(color.index, Some(9));
+
+        let mut color = Color::parse("#0df93f").unwrap();
+        color.limit(8);
+        assert_eq!(color.index, Some(2));
+
+        let mut color = Color::parse("ansi:196").unwrap();
+        color.limit(16);
+        assert_eq!(color.index, Some(9));
+
+        let mut color = Color::parse("blue").unwrap();
+        color.limit(256);
+        assert_eq!(color.index, Some(4));
+    }
+
+    // Test if text breaks at spaces within width, else mid-word, keeping line breaks.
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij kl", 4), vec!["abcd", "efgh", "ij", "kl"]);
+        assert_eq!(wrap("one\n\ntwo", 10), vec!["one", "", "two"]);
+        assert!(wrap("", 10).is_empty());
+
+        // Wide chars take 2 columns each.
+        assert_eq!(wrap("日本語のテキスト", 6), vec!["日本語", "のテキ", "スト"]);
+        assert_eq!(wrap("ok 🦀🦀 crab", 6), vec!["ok", "🦀🦀", "crab"]);
+        assert_eq!(wrap("日本", 1), vec!["日", "本"]);
+    }
 }
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use log::error;
+
+use crate::tools::log_err_desc_ret;
+use super::{
+    command_palette::CmdPalette,
+    modal::Modal,
+    model::{ AppModel, Mode },
+    page::PageBar,
+    surface::{ Region, Surface },
+    TuiPrefs,
+    util::Widget
+};
+
+// Lines taken by page bar and command palette, pages get the rest.
+pub const BARS_HEIGHT: u32 = 2;
+
+// -----------------------------------------------------------------------------------------------------------
+// * Draw model onto screen: page bar on top, focused page below it and command palette on the last line.
+// * Returns column of the cursor in command palette, None if it is hidden.
+// -----------------------------------------------------------------------------------------------------------
+pub fn draw_view(model: &AppModel, tui_prefs: &TuiPrefs, screen: &mut dyn Surface) -> Result<Option<u32>> {
+    let (dim_y, dim_x) = screen.dim_yx();
+
+    let page_names = model.pages().iter().map(|page| page.name().to_string()).collect();
+    PageBar::new(page_names, model.foc_page(), model.unread()).draw(&mut Region::new(screen, 0, 0, 1, dim_x), tui_prefs)?;
+
+    let page_dim_y = dim_y.saturating_sub(BARS_HEIGHT);
+    let mut page_region = Region::new(screen, 1, 0, page_dim_y, dim_x);
+    match model.foc_page_ref() {
+        Some(page) => { log_err_desc_ret!(page.draw(&mut page_region, tui_prefs), "Failed to render page")?; },
+        None => { page_region.fill(Default::default())?; }
+    }
+
+    // Modal centered over page.
+    if let Some(prompt) = model.confirm_prompt() {
+        let modal = Modal::new(prompt);
+        let (modal_dim_y, modal_dim_x) = modal.dim_yx();
+        let (y, x) = (1 + page_dim_y.saturating_sub(modal_dim_y) / 2, dim_x.saturating_sub(modal_dim_x) / 2);
+        modal.draw(&mut Region::new(screen, y, x, modal_dim_y.min(page_dim_y), modal_dim_x), tui_prefs)?;
+    }
+
+    let cmd_plt = match model.mode() {
+        Mode::Cmd => CmdPalette::new(&format!(":{}", model.palette().text()), Some(model.palette().cursor() + 1)),
+        Mode::Normal | Mode::Confirm => CmdPalette::new(model.status().unwrap_or(""), None)
+    };
+    cmd_plt.draw(&mut Region::new(screen, dim_y.saturating_sub(1), 0, 1, dim_x), tui_prefs)?;
+    Ok(cmd_plt.cursor_x(dim_x))
+}
+
+#[cfg(test)]
+mod tests {
+    use libnotcurses_sys::NcStyle;
+    use std::{ env, fs, path::PathBuf };
+
+    use crate::{ api::model::Post, jobs::Config };
+    use super::draw_view;
+    use super::super::{
+        model::{ AppModel, Confirm, PaletteKey },
+        page::Page,
+        subreddit_listing_page::SubListPage,
+        surface::CellBuffer,
+        text_page::TextPage,
+        util::channels,
+        TuiPrefs
+    };
+
+    fn tui_prefs() -> TuiPrefs {
+        TuiPrefs::gen_tui_prefs(&Config::default().tui).unwrap()
+    }
+
+    // Model with a listing page of 4 posts and a text page, dim_x x dim_y.
+    fn model(dim_x: u32, dim_y: u32) -> AppModel {
+        let mut model = AppModel::new(dim_x, dim_y - 2);
+        let mut page = SubListPage::new("rust");
+        for i in 0..4 {
+            page.add_post(Post {
+                title: format!("Post number {}", i),
+                author: format!("user_{}", i),
+                subreddit: "rust".to_string(),
+                score: 10 * i,
+                num_comments: i as u32,
+                selftext: "A body long enough to wrap onto a second line of the page.".to_string(),
+                ..Post::default()
+            }).unwrap();
+        }
+        model.add_page(Box::new(page));
+        model.add_page(Box::new(TextPage::new("text", "line 1\n\tindented line 2")));
+        model
+    }
+
+    // Compare text of screen with snapshot in tests/snapshots, writing it instead if UPDATE_SNAPSHOTS is set.
+    fn assert_snapshot(screen: &CellBuffer, name: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(format!("{}.txt", name));
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, screen.text()).unwrap();
+        }
+        let snapshot = fs::read_to_string(&path).unwrap_or_else(|e| panic!("No snapshot {}: {}", path.display(), e));
+        assert_eq!(screen.text(), snapshot, "Screen differs from snapshot {}", path.display());
+    }
+
+    // Test if listing is drawn under page bar, with the selected post's heading highlighted.
+    #[test]
+    fn test_draw_listing() {
+        let (mut model, tui_prefs) = (model(40, 12), tui_prefs());
+        model.set_foc_page(0).unwrap();
+        model.exec("select_next").unwrap();
+        model.show_status("Config reloaded.");
+
+        let mut screen = CellBuffer::new(40, 12);
+        assert_eq!(draw_view(&model, &tui_prefs, &mut screen).unwrap(), None);
+        assert_snapshot(&screen, "listing");
+
+        let theme = &tui_prefs.theme;
+        let heading = screen.cell(7, 0).unwrap();
+        assert_eq!(heading.chnls, channels(&theme.highlight_fg, &theme.highlight_bg));
+        assert_eq!(heading.styles, NcStyle::Bold);
+        assert_eq!(screen.cell(0, 0).unwrap().chnls, channels(&theme.page_bar_fg, &theme.page_bar_current_bg));
+        assert_eq!(screen.cell(0, 7).unwrap().chnls, channels(&theme.page_bar_fg, &theme.page_bar_bg));
+    }
+
+    // Test if command palette scrolls to keep the cursor in view, over a text page.
+    #[test]
+    fn test_draw_palette() {
+        let (mut model, tui_prefs) = (model(20, 5), tui_prefs());
+        model.enter_cmd();
+        for c in "set theme.post-body-fg red".chars() {
+            model.palette_input(PaletteKey::Char(c));
+        }
+
+        let mut screen = CellBuffer::new(20, 5);
+        assert_eq!(draw_view(&model, &tui_prefs, &mut screen).unwrap(), Some(19));
+        assert_snapshot(&screen, "palette");
+
+        model.palette_input(PaletteKey::Home);
+        assert_eq!(draw_view(&model, &tui_prefs, &mut screen).unwrap(), Some(1));
+        assert_eq!(screen.row(4), ":set theme.post-body");
+    }
+
+    // Test if unread messages are counted at the right end of the page bar, once there are any.
+    #[test]
+    fn test_draw_unread() {
+        let (mut model, tui_prefs) = (model(40, 12), tui_prefs());
+        let mut screen = CellBuffer::new(40, 12);
+        model.set_unread(0);
+        draw_view(&model, &tui_prefs, &mut screen).unwrap();
+        assert!(!screen.row(0).contains("unread"));
+
+        model.set_unread(3);
+        draw_view(&model, &tui_prefs, &mut screen).unwrap();
+        assert!(screen.row(0).ends_with(" 3 unread"));
+        assert_eq!(screen.cell(0, 39).unwrap().styles, NcStyle::Bold);
+    }
+
+    // Test if confirmation modal is drawn centered over the page, hiding the keys typed.
+    #[test]
+    fn test_draw_confirm() {
+        let (mut model, tui_prefs) = (model(40, 12), tui_prefs());
+        model.set_foc_page(0).unwrap();
+        model.ask_confirm("Delete this post? y/n", Confirm::Delete { fullname: "t3_0".to_string() });
+
+        let mut screen = CellBuffer::new(40, 12);
+        assert_eq!(draw_view(&model, &tui_prefs, &mut screen).unwrap(), None);
+        assert_snapshot(&screen, "confirm");
+
+        let theme = &tui_prefs.theme;
+        assert_eq!(screen.cell(4, 7).unwrap().chnls, channels(&theme.highlight_fg, &theme.highlight_bg));
+        assert_eq!(screen.cell(5, 9).unwrap().styles, NcStyle::Bold);
+    }
+}
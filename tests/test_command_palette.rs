@@ -1,12 +1,15 @@
 use anyhow::Result;
 use libnotcurses_sys::{ c_api, Nc, NcChannels, NcInput, NcKey, NcPlane, NcReceived, NcStyle };
 use serial_test::serial;
-use std::sync::{ Mutex, Arc };
+use std::{
+    path::Path,
+    sync::{ Mutex, Arc }
+};
 
 use ravana::{
     jobs::config::load_config,
-    tui::{ App, TuiPrefs },
-    input::{ create_key_bindings_trie, handle_input, KeyCombination }
+    tui::{ App, Mode, TuiPrefs },
+    input::{ create_key_bindings_trie, handle_input, InputState, Macros }
 };
 
 // -----------------------------------------------------------------------------------------------------------
@@ -16,7 +19,7 @@ use ravana::{
 // #[test]
 // #[serial]
 // fn test_cmd_plt_pos() -> Result<()> {
-//     let mut config = load_config();
+//     let mut config = load_config(Some(Path::new("docs/.ravana/Config.toml")))?.config;
 //     config.tui.interface.mouse_events_enable = false; // Disable mouse events for GitHub Actions to work.
 //     let nc = Arc::new(Mutex::new(unsafe { Nc::new()? }));
 //
@@ -46,7 +49,7 @@ use ravana::{
 #[test]
 #[serial]
 fn test_cmd_plt_text_display() -> Result<()> {
-    let mut config = load_config();
+    let mut config = load_config(Some(Path::new("docs/.ravana/Config.toml")))?.config;
     config.tui.interface.mouse_events_enable = false; // Disable mouse events for GitHub Actions to work.
     let nc = Arc::new(Mutex::new(unsafe { Nc::new()? }));
 
@@ -59,19 +62,18 @@ fn test_cmd_plt_text_display() -> Result<()> {
     )?;
     app.dummy_render()?;
 
-    let mut buffer: KeyCombination = KeyCombination::new();
-    let mut cmd_mode: bool = false;
-    let kbt = create_key_bindings_trie(&config.key_bindings)?;
+    let mut state = InputState::new(Macros::new());
+    let mut kbt = create_key_bindings_trie(&config.key_bindings, &config.leader)?;
 
     let mut ncin = NcInput::new(':');
     let ncr = NcReceived::Char(':');
-    handle_input(&mut app, &mut buffer, &mut cmd_mode, &ncr, &mut ncin, &kbt)?;
+    handle_input(&mut app, &mut state, &ncr, &mut ncin, &mut kbt)?;
 
     let eg_cmd_chars: Vec<char> = "example".chars().collect();
     for (_, v) in eg_cmd_chars.iter().enumerate() {
         let mut ncin = NcInput::new(*v);
         let ncr = NcReceived::Char(*v);
-        handle_input(&mut app, &mut buffer, &mut cmd_mode, &ncr, &mut ncin, &kbt)?;
+        handle_input(&mut app, &mut state, &ncr, &mut ncin, &mut kbt)?;
     }
     
     app.render().unwrap();
@@ -112,7 +114,7 @@ fn test_cmd_plt_text_display() -> Result<()> {
 #[test]
 #[serial]
 fn test_cmd_mode_switching() -> Result<()> {
-    let mut config = load_config();
+    let mut config = load_config(Some(Path::new("docs/.ravana/Config.toml")))?.config;
     config.tui.interface.mouse_events_enable = false; // Disable mouse events for GitHub Actions to work.
     let nc = Arc::new(Mutex::new(unsafe { Nc::new()? }));
 
@@ -125,33 +127,32 @@ fn test_cmd_mode_switching() -> Result<()> {
     )?;
     app.dummy_render()?;
 
-    let mut buffer: KeyCombination = KeyCombination::new();
-    let mut cmd_mode: bool = false;
-    let kbt = create_key_bindings_trie(&config.key_bindings)?;
+    let mut state = InputState::new(Macros::new());
+    let mut kbt = create_key_bindings_trie(&config.key_bindings, &config.leader)?;
 
 
     // Enters cmd mode on :.
     let mut ncin = NcInput::new(':');
     let ncr = NcReceived::Char(':');
-    handle_input(&mut app, &mut buffer, &mut cmd_mode, &ncr, &mut ncin, &kbt)?;
+    handle_input(&mut app, &mut state, &ncr, &mut ncin, &mut kbt)?;
     app.render().unwrap();
-    assert_eq!(cmd_mode, true);
+    assert_eq!(app.model().mode(), Mode::Cmd);
     
 
     // Exits cmd mode on escape.
     let mut ncin = NcInput::new(':');
     let ncr = NcReceived::Event(NcKey::Esc);
-    handle_input(&mut app, &mut buffer, &mut cmd_mode, &ncr, &mut ncin, &kbt)?;
-    assert_eq!(cmd_mode, false);
+    handle_input(&mut app, &mut state, &ncr, &mut ncin, &mut kbt)?;
+    assert_eq!(app.model().mode(), Mode::Normal);
     
     // Exits cmd mode on enter (and hopefully attempts executing).
     let mut ncin = NcInput::new(':');
     let ncr = NcReceived::Char(':');
-    handle_input(&mut app, &mut buffer, &mut cmd_mode, &ncr, &mut ncin, &kbt)?;
+    handle_input(&mut app, &mut state, &ncr, &mut ncin, &mut kbt)?;
     let mut ncin = NcInput::new(':');
     let ncr = NcReceived::Event(NcKey::Enter);
-    handle_input(&mut app, &mut buffer, &mut cmd_mode, &ncr, &mut ncin, &kbt)?;
-    assert_eq!(cmd_mode, false);
+    handle_input(&mut app, &mut state, &ncr, &mut ncin, &mut kbt)?;
+    assert_eq!(app.model().mode(), Mode::Normal);
 
     Ok(())
 }
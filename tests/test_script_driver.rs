@@ -0,0 +1,65 @@
+use anyhow::Result;
+
+use ravana::{ input::ScriptDriver, jobs::Config };
+
+// -----------------------------------------------------------------------------------------------------------
+// - Scripted inputs drive a headless App, no terminal needed.
+// - Dummy pages are r/rust and r/hhhhhhhh, 13 posts each, the latter focused.
+// -----------------------------------------------------------------------------------------------------------
+
+fn driver() -> Result<ScriptDriver> {
+    let mut driver = ScriptDriver::new(&Config::default(), 80, 24)?;
+    driver.app.dummy_render()?;
+    Ok(driver)
+}
+
+// Test if commands typed in palette and key-bindings switch pages and scroll.
+#[test]
+fn test_switch_and_scroll() -> Result<()> {
+    let mut driver = driver()?;
+    driver.run("")?;
+    driver.assert_foc_page(1).assert_scrolled(0).assert_screen_contains("1:hhhh");
+
+    driver.run(":switch_page 0<CR>jjj")?;
+    driver.assert_foc_page(0).assert_scrolled(6).assert_palette(None);
+
+    // Count repeats key-binding, scrolling stops at the bottom.
+    driver.run("5j")?;
+    driver.assert_scrolled(16);
+    driver.run("50j")?;
+    driver.assert_scrolled(43);
+
+    driver.run("gtgt")?;
+    driver.assert_foc_page(0);
+    Ok(())
+}
+
+// Test if palette shows what is typed until escaped, mapped keys run their command and refused maps are shown.
+#[test]
+fn test_palette_and_map() -> Result<()> {
+    let mut driver = driver()?;
+    driver.run(":switch_pa")?;
+    driver.assert_palette(Some("switch_pa")).assert_screen_contains(":switch_pa");
+    driver.run("<Esc>")?;
+    driver.assert_palette(None).assert_foc_page(1);
+
+    driver.run(":map <lt>C-d> scroll_down<CR><C-d><C-d>")?;
+    driver.assert_scrolled(4);
+
+    // Refused commands say why in status.
+    driver.run(":map x bogus<CR>")?;
+    driver.assert_screen_contains("map x bogus: map: unknown command \"bogus\"");
+    driver.run(":map qx scroll_down<CR>")?;
+    driver.assert_screen_contains("qx starts with a key reserved for counts and macros");
+    Ok(())
+}
+
+// Test if quitting stops the script.
+#[test]
+fn test_quit() -> Result<()> {
+    let mut driver = driver()?;
+    assert!(!driver.run("zzj")?);
+    assert!(driver.has_quit());
+    driver.assert_scrolled(0);
+    Ok(())
+}